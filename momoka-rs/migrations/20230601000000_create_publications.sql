@@ -0,0 +1,45 @@
+-- The addresses which submitted publications to bundlr.
+CREATE TABLE IF NOT EXISTS submitters (
+    address TEXT PRIMARY KEY NOT NULL
+);
+
+-- A publication per DA transaction, as it was signed.
+CREATE TABLE IF NOT EXISTS publications (
+    tx_id TEXT PRIMARY KEY NOT NULL,
+    publication_id TEXT NOT NULL,
+    action TEXT NOT NULL,
+    profile_id TEXT NOT NULL,
+    content_uri TEXT,
+    collect_module TEXT,
+    reference_module TEXT NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_timestamp BIGINT NOT NULL,
+    -- the UNIX (MS precision) timestamp bundlr received the transaction at
+    timestamp BIGINT NOT NULL,
+    submitter TEXT NOT NULL REFERENCES submitters (address)
+);
+
+CREATE INDEX IF NOT EXISTS publications_publication_id ON publications (publication_id);
+CREATE INDEX IF NOT EXISTS publications_profile_id ON publications (profile_id);
+CREATE INDEX IF NOT EXISTS publications_submitter ON publications (submitter);
+
+-- The publication a comment or mirror points to, the DA transaction of it if it is on momoka.
+CREATE TABLE IF NOT EXISTS pointers (
+    tx_id TEXT PRIMARY KEY NOT NULL REFERENCES publications (tx_id),
+    pointer_type TEXT NOT NULL,
+    profile_id_pointed TEXT NOT NULL,
+    pub_id_pointed TEXT NOT NULL,
+    pointer_tx_id TEXT
+);
+
+CREATE INDEX IF NOT EXISTS pointers_pointer_tx_id ON pointers (pointer_tx_id);
+
+-- The latest verdict on each publication.
+CREATE TABLE IF NOT EXISTS verification_results (
+    tx_id TEXT PRIMARY KEY NOT NULL REFERENCES publications (tx_id),
+    result TEXT NOT NULL,
+    error TEXT,
+    message TEXT,
+    -- the UNIX (MS precision) timestamp the verdict was reached at
+    verified_at BIGINT NOT NULL
+);
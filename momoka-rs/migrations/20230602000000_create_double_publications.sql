@@ -0,0 +1,10 @@
+-- DA publications whose typed data signature was also executed on the LensHub, creating the
+-- same publication on-chain.
+CREATE TABLE IF NOT EXISTS double_publications (
+    tx_id TEXT PRIMARY KEY NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    pub_id TEXT NOT NULL,
+    block_number BIGINT NOT NULL,
+    -- the UNIX (MS precision) timestamp the double publication was detected at
+    detected_at BIGINT NOT NULL
+);
@@ -0,0 +1,4 @@
+-- Whether the content a valid publication points to resolved to valid metadata, `NULL` if it
+-- was not checked.
+ALTER TABLE verification_results ADD COLUMN content_status TEXT;
+ALTER TABLE verification_results ADD COLUMN content_message TEXT;
@@ -0,0 +1,427 @@
+use std::{mem, time::Duration};
+
+use ethers::types::Address;
+use hmac::{Hmac, Mac};
+use reqwest::{header::CONTENT_TYPE, Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::Sha256;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinSet,
+};
+use tracing::{error, warn};
+
+use crate::{
+    cache::{read_alert_cache, set_alert_cache},
+    evm::ProviderContext,
+    types::{
+        double_publication::DoublePublication,
+        transaction::{MomokaTxId, TransactionSummary},
+        verification_report::VerificationReport,
+        verifier_error::MomokaVerifierError,
+    },
+};
+
+/// The header the HMAC-SHA256 signature of the alert body is sent in.
+pub const SIGNATURE_HEADER: &str = "X-Momoka-Signature";
+
+/// How long the rate limit of alerts applies over, held alerts are digested at the end of it.
+const ALERT_WINDOW: Duration = Duration::from_secs(60);
+
+/// How many times an alert is sent to a webhook before giving up on it.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+/// How long to wait before sending an alert again, doubled after every failed attempt.
+const INITIAL_DELIVERY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Where alerts are sent and how many are sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertConfig {
+    /// The webhooks every alert is posted to.
+    pub webhooks: Vec<String>,
+    /// The secret the alert bodies are signed with.
+    pub secret: String,
+    /// How many alerts are sent a minute at most.
+    pub rate_limit: usize,
+    /// Whether the alerts over the rate limit are sent as a digest at the end of the minute,
+    /// rather than dropped.
+    pub digest: bool,
+}
+
+/// An alert about a transaction which failed verification for a reason which is not transient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Alert {
+    pub tx_id: MomokaTxId,
+    pub environment: String,
+    pub deployment: String,
+    /// The variant of the error the transaction failed with.
+    pub error: MomokaVerifierError,
+    /// The error the transaction failed with, with its details.
+    pub message: String,
+    /// The address which submitted the transaction to bundlr, if it could be fetched.
+    pub submitter: Option<Address>,
+    /// Every check which was run on the transaction, if it got as far as being checked.
+    pub report: Option<VerificationReport>,
+    /// The DA payload of the transaction, if it could be fetched and parsed.
+    pub payload: Option<Value>,
+    /// The on-chain publication, if the transaction was also published on-chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub double_publication: Option<DoublePublication>,
+}
+
+impl Alert {
+    /// Builds the alert about a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_id` - The ID of the transaction.
+    /// * `transaction` - The transaction, if it could be fetched and parsed.
+    /// * `error` - The error the transaction failed with.
+    /// * `report` - The report of the checks run on the transaction.
+    /// * `provider_context` - The provider context.
+    pub fn new(
+        tx_id: &MomokaTxId,
+        transaction: Option<&TransactionSummary>,
+        error: &MomokaVerifierError,
+        report: Option<VerificationReport>,
+        provider_context: &ProviderContext,
+    ) -> Self {
+        Alert {
+            tx_id: tx_id.clone(),
+            environment: format!("{:?}", provider_context.environment),
+            deployment: format!("{:?}", provider_context.deployment),
+            error: error.clone(),
+            message: error.to_string(),
+            submitter: transaction.map(|transaction| transaction.submitter),
+            report,
+            payload: transaction
+                .and_then(|transaction| serde_json::to_value(&transaction.momoka_tx).ok()),
+            double_publication: None,
+        }
+    }
+
+    /// Builds the alert about a verified DA publication which was also published on-chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `double_publication` - The double publication found.
+    /// * `provider_context` - The provider context.
+    pub fn double_publication(
+        double_publication: &DoublePublication,
+        provider_context: &ProviderContext,
+    ) -> Self {
+        let error = MomokaVerifierError::ChainSignatureAlreadyUsed;
+
+        Alert {
+            double_publication: Some(double_publication.clone()),
+            ..Alert::new(
+                &double_publication.tx_id,
+                None,
+                &error,
+                None,
+                provider_context,
+            )
+        }
+    }
+}
+
+/// The body posted to the webhooks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AlertBody {
+    /// A single alert.
+    Failure(Box<Alert>),
+    /// The alerts which were held back by the rate limit.
+    Digest { alerts: Vec<Alert> },
+}
+
+/// A message to the task sending the alerts.
+#[derive(Debug)]
+pub enum AlertMessage {
+    /// An alert to send.
+    Alert(Box<Alert>),
+    /// Send the held alerts now, and answer once every alert sent so far was delivered or given up on.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Counts the alerts sent in the current window, holding or dropping those over the rate limit.
+#[derive(Debug)]
+struct AlertLimiter {
+    rate_limit: usize,
+    digest: bool,
+    sent: usize,
+    held: Vec<Alert>,
+    dropped: usize,
+}
+
+impl AlertLimiter {
+    fn new(rate_limit: usize, digest: bool) -> Self {
+        AlertLimiter {
+            rate_limit,
+            digest,
+            sent: 0,
+            held: vec![],
+            dropped: 0,
+        }
+    }
+
+    /// Returns the alert if it can be sent now, otherwise holds it for the digest or drops it.
+    fn admit(&mut self, alert: Box<Alert>) -> Option<AlertBody> {
+        if self.sent < self.rate_limit {
+            self.sent += 1;
+            return Some(AlertBody::Failure(alert));
+        }
+
+        if self.digest {
+            self.held.push(*alert);
+        } else {
+            self.dropped += 1;
+        }
+        None
+    }
+
+    /// Starts a new window, returning the digest of the alerts held in the last one if there are any.
+    fn roll(&mut self) -> Option<AlertBody> {
+        if self.dropped > 0 {
+            warn!(
+                "Dropped {} alerts over the rate limit of {} a minute",
+                self.dropped, self.rate_limit
+            );
+        }
+
+        self.sent = 0;
+        self.dropped = 0;
+        if self.held.is_empty() {
+            return None;
+        }
+
+        Some(AlertBody::Digest {
+            alerts: mem::take(&mut self.held),
+        })
+    }
+}
+
+/// Signs an alert body with HMAC-SHA256.
+///
+/// # Arguments
+///
+/// * `secret` - The secret shared with the webhooks.
+/// * `body` - The alert body.
+///
+/// # Returns
+///
+/// The signature as it is sent in the signature header, `sha256=` followed by the hex encoded HMAC.
+fn sign_alert(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Posts an alert body to a webhook, retrying with an exponential backoff.
+///
+/// A webhook answering with a client error (other than 429) is not retried, as it rejected the alert.
+///
+/// # Arguments
+///
+/// * `client` - The HTTP client.
+/// * `webhook` - The URL of the webhook.
+/// * `body` - The alert body.
+/// * `signature` - The signature of the alert body.
+async fn deliver_alert(client: Client, webhook: String, body: Vec<u8>, signature: String) {
+    let mut backoff = INITIAL_DELIVERY_BACKOFF;
+
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        let response = client
+            .post(&webhook)
+            .timeout(Duration::from_millis(10000))
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response)
+                if response.status().is_client_error()
+                    && response.status() != StatusCode::TOO_MANY_REQUESTS =>
+            {
+                error!(
+                    "The alert webhook {} rejected an alert - {}",
+                    webhook,
+                    response.status()
+                );
+                return;
+            }
+            Ok(response) => warn!(
+                "The alert webhook {} failed (attempt {}/{}) - {}",
+                webhook,
+                attempt,
+                MAX_DELIVERY_ATTEMPTS,
+                response.status()
+            ),
+            Err(error) => warn!(
+                "The alert webhook {} could not be reached (attempt {}/{}) - {}",
+                webhook, attempt, MAX_DELIVERY_ATTEMPTS, error
+            ),
+        }
+
+        if attempt < MAX_DELIVERY_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+
+    error!("Gave up sending an alert to the webhook {}", webhook);
+}
+
+/// Sends the alerts it is given to the webhooks until every sender is dropped.
+async fn run_alerts(config: AlertConfig, mut receiver: mpsc::UnboundedReceiver<AlertMessage>) {
+    let client = Client::new();
+    let mut limiter = AlertLimiter::new(config.rate_limit, config.digest);
+    let mut deliveries = JoinSet::new();
+    let mut window =
+        tokio::time::interval_at(tokio::time::Instant::now() + ALERT_WINDOW, ALERT_WINDOW);
+
+    let send = |body: AlertBody, deliveries: &mut JoinSet<()>| {
+        let body = serde_json::to_vec(&body).unwrap();
+        let signature = sign_alert(&config.secret, &body);
+        for webhook in &config.webhooks {
+            deliveries.spawn(deliver_alert(
+                client.clone(),
+                webhook.clone(),
+                body.clone(),
+                signature.clone(),
+            ));
+        }
+    };
+
+    loop {
+        tokio::select! {
+            message = receiver.recv() => match message {
+                Some(AlertMessage::Alert(alert)) => {
+                    if let Some(body) = limiter.admit(alert) {
+                        send(body, &mut deliveries);
+                    }
+                }
+                Some(AlertMessage::Flush(done)) => {
+                    if let Some(body) = limiter.roll() {
+                        send(body, &mut deliveries);
+                    }
+                    while deliveries.join_next().await.is_some() {}
+                    let _ = done.send(());
+                }
+                None => break,
+            },
+            _ = window.tick() => {
+                if let Some(body) = limiter.roll() {
+                    send(body, &mut deliveries);
+                }
+            }
+            Some(_) = deliveries.join_next(), if !deliveries.is_empty() => {}
+        }
+    }
+}
+
+/// Starts sending alerts about transactions which failed verification to the webhooks.
+///
+/// # Arguments
+///
+/// * `config` - Where alerts are sent and how many are sent.
+pub fn start_alerts(config: AlertConfig) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    set_alert_cache(sender);
+    tokio::spawn(run_alerts(config, receiver));
+}
+
+/// Sends an alert, if alerts were started.
+///
+/// # Arguments
+///
+/// * `alert` - The alert to send.
+pub fn send_alert(alert: Alert) {
+    if let Some(sender) = read_alert_cache() {
+        let _ = sender.send(AlertMessage::Alert(Box::new(alert)));
+    }
+}
+
+/// Returns true if alerts were started.
+pub fn is_alerting() -> bool {
+    read_alert_cache().is_some()
+}
+
+/// Sends the alerts held back by the rate limit and waits for every alert to be delivered or
+/// given up on, so none are lost when shutting down.
+pub async fn flush_alerts() {
+    let Some(sender) = read_alert_cache() else {
+        return;
+    };
+
+    let (done, flushed) = oneshot::channel();
+    if sender.send(AlertMessage::Flush(done)).is_ok() {
+        let _ = flushed.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(tx_id: &str) -> Alert {
+        Alert {
+            tx_id: tx_id.to_string(),
+            environment: "Polygon".to_string(),
+            deployment: "Production".to_string(),
+            error: MomokaVerifierError::PublicationNoPointer,
+            message: MomokaVerifierError::PublicationNoPointer.to_string(),
+            submitter: None,
+            report: None,
+            payload: None,
+            double_publication: None,
+        }
+    }
+
+    #[test]
+    fn test_limiter_digests_alerts_over_the_rate_limit() {
+        let mut limiter = AlertLimiter::new(1, true);
+
+        assert_eq!(
+            limiter.admit(Box::new(alert("a"))),
+            Some(AlertBody::Failure(Box::new(alert("a"))))
+        );
+        assert_eq!(limiter.admit(Box::new(alert("b"))), None);
+        assert_eq!(limiter.admit(Box::new(alert("c"))), None);
+        assert_eq!(
+            limiter.roll(),
+            Some(AlertBody::Digest {
+                alerts: vec![alert("b"), alert("c")]
+            })
+        );
+
+        // a new window sends straight away again
+        assert_eq!(
+            limiter.admit(Box::new(alert("d"))),
+            Some(AlertBody::Failure(Box::new(alert("d"))))
+        );
+        assert_eq!(limiter.roll(), None);
+    }
+
+    #[test]
+    fn test_limiter_drops_alerts_over_the_rate_limit() {
+        let mut limiter = AlertLimiter::new(1, false);
+
+        assert!(limiter.admit(Box::new(alert("a"))).is_some());
+        assert_eq!(limiter.admit(Box::new(alert("b"))), None);
+        assert_eq!(limiter.roll(), None);
+    }
+
+    #[test]
+    fn test_sign_alert() {
+        assert_eq!(
+            sign_alert("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+};
+
+use data_encoding::BASE64URL_NOPAD;
+use ethers::types::{Address, Block, H256};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use crate::{
+    bundlr::data_item::BundlrTransactionHeader,
+    cache::{extend_batched_reads_cache, read_batched_reads_cache},
+    contracts::lens_hub::{LensProfileDetails, ProfileDetailsKey},
+    evm::ProviderContext,
+    types::{transaction::MomokaTxId, verifier_error::MomokaVerifierError},
+    utils::{check_same_environment, write_atomically},
+    verifier::batch::RECORDED_READS_PAGE,
+};
+
+/// Whether a transaction archive is written to or read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveMode {
+    /// The data items fetched from bundlr and the chain reads the nodes agreed on are written to the archive.
+    Record,
+    /// The data items are read from the archive and the chain reads from its chain state, nothing is fetched.
+    Replay,
+}
+
+/// A directory the DA payloads, timestamp proofs and chain state of verified transactions are archived in.
+///
+/// Each data item is kept in `transactions/{id}.json` as the bulk api returned it next to its
+/// header, the agreed chain reads in `chain-state.json` and the state local simulations touched in `state`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionArchive {
+    /// The directory of the archive.
+    pub dir: PathBuf,
+    /// Whether the archive is written to or read from.
+    pub mode: ArchiveMode,
+}
+
+/// A data item as the bundlr bulk api returned it, with the header its signature is verified against.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArchivedDataItem {
+    /// The id of the data item.
+    pub id: MomokaTxId,
+    /// The address the bulk api claimed uploaded the data item.
+    pub address: Address,
+    /// The data, base64 encoded.
+    pub data: String,
+    /// The header of the data item.
+    pub header: BundlrTransactionHeader,
+}
+
+/// Profile details the nodes agreed on, next to what they were read for.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RecordedProfileDetails {
+    pub key: ProfileDetailsKey,
+    pub details: LensProfileDetails,
+}
+
+/// The chain reads the nodes agreed on while archiving.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainState {
+    /// The environment the reads are of.
+    pub environment: String,
+    /// The deployment the reads are of.
+    pub deployment: String,
+    /// The agreed blocks keyed by block number.
+    pub blocks: BTreeMap<u64, Block<H256>>,
+    /// The agreed profile details.
+    pub profile_details: Vec<RecordedProfileDetails>,
+}
+
+impl ChainState {
+    /// Adds chain reads to the chain state, replacing any recorded for the same block or key.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks` - The agreed blocks keyed by block number.
+    /// * `profile_details` - The agreed profile details keyed by what they were read for.
+    pub fn merge(
+        &mut self,
+        blocks: HashMap<u64, Block<H256>>,
+        profile_details: HashMap<ProfileDetailsKey, LensProfileDetails>,
+    ) {
+        self.blocks.extend(blocks);
+
+        let mut merged: HashMap<ProfileDetailsKey, LensProfileDetails> = self
+            .profile_details
+            .drain(..)
+            .map(|recorded| (recorded.key, recorded.details))
+            .collect();
+        merged.extend(profile_details);
+
+        self.profile_details = merged
+            .into_iter()
+            .map(|(key, details)| RecordedProfileDetails { key, details })
+            .collect();
+        // keep the file stable between runs so archives can be diffed
+        self.profile_details.sort_by_key(|recorded| {
+            (
+                recorded.key.block_number,
+                recorded.key.profile_id,
+                recorded.key.signer,
+            )
+        });
+    }
+
+    /// Splits the chain state into the reads the batched reads cache holds.
+    ///
+    /// # Returns
+    ///
+    /// The blocks keyed by block number and the profile details keyed by what they were read for.
+    pub fn into_reads(
+        self,
+    ) -> (
+        HashMap<u64, Block<H256>>,
+        HashMap<ProfileDetailsKey, LensProfileDetails>,
+    ) {
+        (
+            self.blocks.into_iter().collect(),
+            self.profile_details
+                .into_iter()
+                .map(|recorded| (recorded.key, recorded.details))
+                .collect(),
+        )
+    }
+}
+
+impl TransactionArchive {
+    /// Creates an archive in the given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory of the archive.
+    /// * `mode` - Whether the archive is written to or read from.
+    pub fn new(dir: impl Into<PathBuf>, mode: ArchiveMode) -> Self {
+        TransactionArchive {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    /// Returns the path a data item is archived at.
+    ///
+    /// The id comes from bundlr, so it is only joined into the path if it is base64url and can not
+    /// point outside the archive.
+    ///
+    /// # Returns
+    ///
+    /// The path, or `None` if the id is not base64url.
+    fn data_item_path(&self, id: &MomokaTxId) -> Option<PathBuf> {
+        BASE64URL_NOPAD
+            .decode(id.as_bytes())
+            .ok()
+            .map(|_| self.dir.join("transactions").join(format!("{}.json", id)))
+    }
+
+    /// Returns the path of the chain state file.
+    fn chain_state_path(&self) -> PathBuf {
+        self.dir.join("chain-state.json")
+    }
+
+    /// Returns the directory the state local simulations touch is cached in.
+    pub fn state_dir(&self) -> String {
+        self.dir.join("state").to_string_lossy().to_string()
+    }
+
+    /// Reads an archived data item.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the data item.
+    ///
+    /// # Returns
+    ///
+    /// The data item, or `None` if it is not archived or the file can not be read.
+    pub fn read_data_item(&self, id: &MomokaTxId) -> Option<ArchivedDataItem> {
+        self.data_item_path(id)
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+    }
+
+    /// Archives a data item, replacing it if it was archived before.
+    ///
+    /// Failing to write is logged and ignored, the transaction will just be missing from the archive.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_item` - The data item to archive.
+    pub fn write_data_item(&self, data_item: &ArchivedDataItem) {
+        let Some(path) = self.data_item_path(&data_item.id) else {
+            warn!(
+                "Could not archive data item {} - the id is not base64url",
+                data_item.id
+            );
+            return;
+        };
+
+        if let Err(error) = write_atomically(&path, &serde_json::to_vec_pretty(data_item).unwrap())
+        {
+            warn!("Could not archive data item {} - {}", data_item.id, error);
+        }
+    }
+
+    /// Reads the chain state of the archive.
+    ///
+    /// # Returns
+    ///
+    /// The chain state, or `None` if nothing was recorded or the file can not be read.
+    pub fn read_chain_state(&self) -> Option<ChainState> {
+        fs::read(self.chain_state_path())
+            .ok()
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+    }
+
+    /// Writes the chain reads agreed on while archiving to the chain state, merged with the ones
+    /// recorded by earlier runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider_context` - The provider context the reads were made with.
+    ///
+    /// # Returns
+    ///
+    /// `CacheError` if the archive holds the chain state of another environment or it could not be written.
+    pub fn save_chain_state(
+        &self,
+        provider_context: &ProviderContext,
+    ) -> Result<(), MomokaVerifierError> {
+        let mut chain_state = match self.read_chain_state() {
+            Some(chain_state) => {
+                check_chain_state_environment(&chain_state, provider_context)?;
+                chain_state
+            }
+            None => ChainState {
+                environment: format!("{:?}", provider_context.environment),
+                deployment: format!("{:?}", provider_context.deployment),
+                ..Default::default()
+            },
+        };
+
+        let (blocks, profile_details) = read_batched_reads_cache();
+        chain_state.merge(blocks, profile_details);
+
+        write_atomically(
+            &self.chain_state_path(),
+            &serde_json::to_vec_pretty(&chain_state).unwrap(),
+        )
+        .map_err(|error| {
+            error!("Could not write the chain state - {}", error);
+            MomokaVerifierError::CacheError
+        })
+    }
+
+    /// Loads the chain state of the archive so chain checks are answered from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider_context` - The provider context the transactions are verified with.
+    ///
+    /// # Returns
+    ///
+    /// `CacheError` if the archive holds the chain state of another environment.
+    pub fn load_chain_state(
+        &self,
+        provider_context: &ProviderContext,
+    ) -> Result<(), MomokaVerifierError> {
+        match self.read_chain_state() {
+            Some(chain_state) => {
+                check_chain_state_environment(&chain_state, provider_context)?;
+                let (blocks, profile_details) = chain_state.into_reads();
+                extend_batched_reads_cache(RECORDED_READS_PAGE, blocks, profile_details);
+            }
+            None => warn!(
+                "The archive has no chain state, transactions which need a chain check will not be checked",
+            ),
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks a chain state was recorded for the environment and deployment being verified.
+///
+/// # Arguments
+///
+/// * `chain_state` - The recorded chain state.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// `CacheError` if it was recorded for another environment or deployment.
+fn check_chain_state_environment(
+    chain_state: &ChainState,
+    provider_context: &ProviderContext,
+) -> Result<(), MomokaVerifierError> {
+    check_same_environment(
+        "The archive chain state",
+        &chain_state.environment,
+        &chain_state.deployment,
+        provider_context,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U256;
+
+    fn temp_archive() -> TransactionArchive {
+        TransactionArchive::new(
+            std::env::temp_dir().join(format!("momoka-archive-{}", uuid::Uuid::new_v4())),
+            ArchiveMode::Record,
+        )
+    }
+
+    #[test]
+    fn test_data_item_round_trips_through_archive() {
+        let archive = temp_archive();
+        let id: MomokaTxId = "PoDx9KfHCIKAdJg2WyfWcx2B_K5aMKq0je7EtIcOc4w".to_string();
+        assert_eq!(archive.read_data_item(&id), None);
+
+        let data_item = ArchivedDataItem {
+            id: id.clone(),
+            address: Address::from_low_u64_be(1),
+            data: "e30=".to_string(),
+            header: BundlrTransactionHeader {
+                id: id.clone(),
+                currency: "matic".to_string(),
+                signature_type: 3,
+                owner: "owner".to_string(),
+                signature: "signature".to_string(),
+                target: String::new(),
+                anchor: String::new(),
+                tags: vec![],
+            },
+        };
+        archive.write_data_item(&data_item);
+
+        assert_eq!(archive.read_data_item(&id), Some(data_item.clone()));
+
+        // an id which is not base64url is never joined into a path
+        let id: MomokaTxId = "../../escaped".to_string();
+        archive.write_data_item(&ArchivedDataItem {
+            id: id.clone(),
+            ..data_item
+        });
+        assert_eq!(archive.read_data_item(&id), None);
+        assert!(!archive.dir.parent().unwrap().join("escaped.json").exists());
+    }
+
+    #[test]
+    fn test_chain_state_merge_replaces_recorded_reads() {
+        let key = ProfileDetailsKey {
+            profile_id: U256::from(1),
+            signer: Address::from_low_u64_be(2),
+            block_number: 100,
+            collect_module: None,
+            reference_module: None,
+        };
+        let details = |sig_nonce: u64| LensProfileDetails {
+            sig_nonce: U256::from(sig_nonce),
+            current_publication_id: U256::from(3),
+            dispatcher_address: Address::zero(),
+            owner_of_address: Address::from_low_u64_be(2),
+            collect_module_whitelisted: true,
+            reference_module_whitelisted: true,
+        };
+
+        let mut chain_state = ChainState::default();
+        chain_state.merge(
+            HashMap::from([(100, Block::default())]),
+            HashMap::from([(key, details(1))]),
+        );
+        chain_state.merge(HashMap::new(), HashMap::from([(key, details(2))]));
+
+        let chain_state: ChainState =
+            serde_json::from_slice(&serde_json::to_vec(&chain_state).unwrap()).unwrap();
+        let (blocks, profile_details) = chain_state.into_reads();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(profile_details, HashMap::from([(key, details(2))]));
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    http::{get_with_timeout, post_with_timeout},
+    types::{transaction::MomokaTxId, verifier_error::MomokaVerifierError},
+};
+
+/// The default Arweave gateway used to check inclusion of the DA data items.
+pub const DEFAULT_ARWEAVE_GATEWAY: &str = "https://arweave.net";
+
+/// The maximum amount of ids the gateway graphql will return in a single page.
+const GATEWAY_PAGE_SIZE: usize = 100;
+
+/// An enum representing the Arweave gateway endpoints we use.
+enum ArweaveEndpoint {
+    GraphQl,
+    Info,
+}
+
+impl ArweaveEndpoint {
+    /// Get the URL of the endpoint on the given gateway.
+    ///
+    /// # Arguments
+    ///
+    /// * `gateway` - The base URL of the Arweave gateway.
+    ///
+    /// # Returns
+    ///
+    /// * A string containing the URL of the endpoint.
+    pub fn url(&self, gateway: &str) -> String {
+        let gateway = gateway.trim_end_matches('/');
+        match self {
+            ArweaveEndpoint::GraphQl => format!("{}/graphql", gateway),
+            ArweaveEndpoint::Info => format!("{}/info", gateway),
+        }
+    }
+}
+
+/// The network information returned by the gateway `/info` endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArweaveNetworkInfo {
+    /// The current Arweave block height
+    pub height: u64,
+}
+
+/// A graphql request body sent to the gateway.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct ArweaveGraphQlRequest {
+    query: String,
+}
+
+/// A graphql response returned by the gateway.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct ArweaveGraphQlResponse<TData> {
+    data: TData,
+}
+
+/// API response for getting transactions from the gateway.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArweaveTransactionsAPIResponse {
+    pub transactions: ArweaveTransactions,
+}
+
+/// The transactions page returned by the gateway.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArweaveTransactions {
+    /// The edges of the arweave transactions
+    pub edges: Vec<ArweaveTransactionEdge>,
+}
+
+/// An arweave edge transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArweaveTransactionEdge {
+    /// The node of the arweave transaction
+    pub node: ArweaveTransactionNode,
+}
+
+/// An arweave node transaction.
+///
+/// For bundled data items the block is the block the parent bundle was mined in,
+/// and is `None` until the bundle has been mined.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArweaveTransactionNode {
+    /// The id of the data item
+    pub id: MomokaTxId,
+
+    /// The block the data item was included in
+    pub block: Option<ArweaveTransactionBlock>,
+}
+
+/// The block an arweave transaction was included in.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArweaveTransactionBlock {
+    /// The block height
+    pub height: u64,
+}
+
+/// Constructs the GraphQL query to fetch the blocks the given data items were included in.
+///
+/// # Arguments
+///
+/// * `tx_ids` - The data item ids to look up.
+///
+/// # Returns
+///
+/// The constructed GraphQL query as a string.
+fn get_transactions_block_query(tx_ids: &[MomokaTxId]) -> String {
+    format!(
+        r#"query DataAvailabilityInclusion {{
+            transactions(ids: {:?}, first: {}) {{
+                edges {{
+                    node {{
+                        id
+                        block {{
+                            height
+                        }}
+                    }}
+                }}
+            }}
+        }}"#,
+        tx_ids,
+        tx_ids.len()
+    )
+}
+
+/// Retrieves the current Arweave block height from the gateway.
+///
+/// # Arguments
+///
+/// * `gateway` - The base URL of the Arweave gateway.
+///
+/// # Returns
+///
+/// A `Result` containing the current block height, or `CannotConnectToArweave` if the gateway could not be reached.
+pub async fn get_network_height_api(gateway: &str) -> Result<u64, MomokaVerifierError> {
+    let info: ArweaveNetworkInfo = get_with_timeout(&ArweaveEndpoint::Info.url(gateway))
+        .await
+        .map_err(|e| MomokaVerifierError::CannotConnectToArweave { source: e.into() })?;
+
+    Ok(info.height)
+}
+
+/// Retrieves the Arweave block height each of the given data items was included in.
+///
+/// Data items which the gateway does not know about yet, or whose bundle has not been
+/// mined yet, are not in the returned map.
+///
+/// # Arguments
+///
+/// * `gateway` - The base URL of the Arweave gateway.
+/// * `tx_ids` - The data item ids to look up.
+///
+/// # Returns
+///
+/// A `Result` containing a map of data item id to block height, or `CannotConnectToArweave` if the gateway could not be reached.
+pub async fn get_transactions_block_height_api(
+    gateway: &str,
+    tx_ids: &[MomokaTxId],
+) -> Result<HashMap<MomokaTxId, u64>, MomokaVerifierError> {
+    let url = ArweaveEndpoint::GraphQl.url(gateway);
+
+    let futures = tx_ids.chunks(GATEWAY_PAGE_SIZE).map(|tx_ids_chunk| {
+        let url = &url;
+        async move {
+            let request = ArweaveGraphQlRequest {
+                query: get_transactions_block_query(tx_ids_chunk),
+            };
+
+            let response: ArweaveGraphQlResponse<ArweaveTransactionsAPIResponse> =
+                post_with_timeout(url, &request).await.map_err(|e| {
+                    MomokaVerifierError::CannotConnectToArweave { source: e.into() }
+                })?;
+
+            Ok::<_, MomokaVerifierError>(response.data.transactions.edges)
+        }
+    });
+
+    let block_heights = futures::future::try_join_all(futures)
+        .await?
+        .into_iter()
+        .flatten()
+        .filter_map(|edge| edge.node.block.map(|block| (edge.node.id, block.height)))
+        .collect();
+
+    Ok(block_heights)
+}
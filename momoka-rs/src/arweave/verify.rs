@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use crate::types::{
+    settlement::SettlementStatus, transaction::MomokaTxId, verifier_error::MomokaVerifierError,
+};
+
+use super::api::{get_network_height_api, get_transactions_block_height_api};
+
+/// Gateways index blocks a little behind the tip they report on `/info`, so we give them
+/// this many blocks past the deadline before deciding a data item was never settled.
+const GATEWAY_INDEXING_GRACE_BLOCKS: u64 = 20;
+
+/// Works out the settlement status of a data item from where it was seen on Arweave.
+///
+/// # Arguments
+///
+/// * `block_height` - The block height the data item was included in, if it has been seen.
+/// * `network_height` - The current Arweave block height.
+/// * `deadline_height` - The deadline height from the timestamp proofs.
+///
+/// # Returns
+///
+/// * `Settled` if the data item was included at or before the deadline height.
+/// * `TimestampedNotSettled` if it has not been seen yet but the deadline has not passed.
+///
+/// # Errors
+///
+/// Returns `NotSettledBeforeDeadline` if the data item was included after the deadline height,
+/// or it has still not been seen once the deadline height has passed.
+pub fn settlement_status(
+    block_height: Option<u64>,
+    network_height: u64,
+    deadline_height: u64,
+) -> Result<SettlementStatus, MomokaVerifierError> {
+    match block_height {
+        Some(block_height) if block_height <= deadline_height => {
+            Ok(SettlementStatus::Settled { block_height })
+        }
+        Some(_) => Err(MomokaVerifierError::NotSettledBeforeDeadline),
+        None if network_height > deadline_height.saturating_add(GATEWAY_INDEXING_GRACE_BLOCKS) => {
+            Err(MomokaVerifierError::NotSettledBeforeDeadline)
+        }
+        None => Ok(SettlementStatus::TimestampedNotSettled { deadline_height }),
+    }
+}
+
+/// Verifies the given data items were included on Arweave before their deadline height.
+///
+/// # Arguments
+///
+/// * `gateway` - The base URL of the Arweave gateway to query.
+/// * `deadlines` - The data item ids mapped to the deadline height in their timestamp proofs.
+///
+/// # Returns
+///
+/// A `Result` containing the settlement result of each data item, or `CannotConnectToArweave`
+/// if the gateway could not be reached, in which case nothing can be said about settlement.
+pub async fn verify_settlements(
+    gateway: &str,
+    deadlines: &HashMap<MomokaTxId, u64>,
+) -> Result<HashMap<MomokaTxId, Result<SettlementStatus, MomokaVerifierError>>, MomokaVerifierError>
+{
+    if deadlines.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // read the height first so anything mined after it is still found by the lookup
+    let network_height = get_network_height_api(gateway).await?;
+
+    // sorted so the same transactions always send the same lookups
+    let mut tx_ids = deadlines.keys().cloned().collect::<Vec<MomokaTxId>>();
+    tx_ids.sort();
+
+    let block_heights = get_transactions_block_height_api(gateway, &tx_ids).await?;
+
+    Ok(deadlines
+        .iter()
+        .map(|(tx_id, deadline_height)| {
+            let status = settlement_status(
+                block_heights.get(tx_id).copied(),
+                network_height,
+                *deadline_height,
+            );
+            (tx_id.clone(), status)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stand_in;
+
+    /// Starts a local stand-in for an Arweave gateway which answers `/info` with the
+    /// given height and `/graphql` with the given transactions body.
+    fn stand_in_gateway(network_height: u64, transactions_body: &'static str) -> String {
+        stand_in::serve(move |request| {
+            Some(if request.method == "GET" && request.path == "/info" {
+                format!("{{\"height\":{}}}", network_height)
+            } else {
+                transactions_body.to_string()
+            })
+        })
+    }
+
+    #[test]
+    fn test_settlement_status() {
+        assert_eq!(
+            settlement_status(Some(100), 120, 100),
+            Ok(SettlementStatus::Settled { block_height: 100 })
+        );
+        assert_eq!(
+            settlement_status(Some(101), 120, 100),
+            Err(MomokaVerifierError::NotSettledBeforeDeadline)
+        );
+        assert_eq!(
+            settlement_status(None, 95, 100),
+            Ok(SettlementStatus::TimestampedNotSettled {
+                deadline_height: 100
+            })
+        );
+        assert_eq!(
+            settlement_status(None, 100 + GATEWAY_INDEXING_GRACE_BLOCKS + 1, 100),
+            Err(MomokaVerifierError::NotSettledBeforeDeadline)
+        );
+        // a deadline height near the top of the range does not overflow
+        assert_eq!(
+            settlement_status(None, u64::MAX, u64::MAX - 1),
+            Ok(SettlementStatus::TimestampedNotSettled {
+                deadline_height: u64::MAX - 1
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_settlements_against_stand_in_gateway() {
+        let gateway = stand_in_gateway(
+            1000,
+            r#"{"data":{"transactions":{"edges":[
+                {"node":{"id":"settled","block":{"height":990}}},
+                {"node":{"id":"late","block":{"height":999}}},
+                {"node":{"id":"pending","block":null}}
+            ]}}}"#,
+        );
+
+        let deadlines = HashMap::from([
+            ("settled".to_string(), 995),
+            ("late".to_string(), 995),
+            ("pending".to_string(), 1010),
+        ]);
+
+        let result = verify_settlements(&gateway, &deadlines).await.unwrap();
+
+        assert_eq!(
+            result["settled"],
+            Ok(SettlementStatus::Settled { block_height: 990 })
+        );
+        assert_eq!(
+            result["late"],
+            Err(MomokaVerifierError::NotSettledBeforeDeadline)
+        );
+        assert_eq!(
+            result["pending"],
+            Ok(SettlementStatus::TimestampedNotSettled {
+                deadline_height: 1010
+            })
+        );
+    }
+}
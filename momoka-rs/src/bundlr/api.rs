@@ -1,25 +1,34 @@
 use crate::{
+    archive::{ArchiveMode, ArchivedDataItem, TransactionArchive},
+    cache::read_transaction_archive_cache,
     environment::{Deployment, Environment},
-    http::post_with_timeout,
+    http::{get_with_timeout, post_with_timeout},
     submitter::state::get_submitters,
     types::{
         transaction::{
             MomokaTransaction, MomokaTransactionName, MomokaTxId, TimestampProofsResponse,
             TimestampProofsSummary, TransactionError, TransactionSummary,
         },
-        verifier_error::MomokaVerifierError,
+        transaction_filter::TransactionFilter,
+        verifier_error::{ErrorSource, MomokaVerifierError},
     },
 };
 use base64::{engine::general_purpose, Engine};
 use ethers::{types::Address, utils};
-use gql_client::Client;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, str::FromStr};
 
+use super::{
+    data_item::{BundlrTransactionHeader, DataItem},
+    verify::verify_data_item_signature,
+};
+
 /// An enum representing various Bundlr endpoints.
 enum BundlrEndpoint {
     GraphQl,
     BulkTxsData,
+    Tx,
 }
 
 impl BundlrEndpoint {
@@ -32,6 +41,7 @@ impl BundlrEndpoint {
         match self {
             BundlrEndpoint::GraphQl => "https://lens.bundlr.network/graphql",
             BundlrEndpoint::BulkTxsData => "https://lens.bundlr.network/bulk/txs/data",
+            BundlrEndpoint::Tx => "https://lens.bundlr.network/tx/",
         }
     }
 }
@@ -71,6 +81,10 @@ pub struct BundlrTransactionNode {
 
     /// The address of the bundlr transaction
     pub address: String,
+
+    /// When the bundlr transaction was uploaded in milliseconds
+    #[serde(default)]
+    pub timestamp: Option<u64>,
 }
 
 /// A bundlr transaction page information.
@@ -108,6 +122,8 @@ pub enum TransactionOrder {
 /// * `owners` - A vector of addresses representing the owners to query transactions for.
 /// * `limit` - The maximum number of transactions to retrieve.
 /// * `after` - An optional cursor indicating the starting point for pagination.
+/// * `order` - The order to retrieve the transactions in.
+/// * `timestamp_to` - An optional timestamp in milliseconds, only transactions uploaded at or before it are retrieved.
 ///
 /// # Returns
 ///
@@ -117,12 +133,18 @@ fn get_transactions_query(
     limit: i32,
     after: &Option<String>,
     order: TransactionOrder,
+    timestamp_to: Option<u64>,
 ) -> String {
     let after_value = match after {
         Some(value) => format!("\"{}\"", value),
         None => "null".to_string(),
     };
 
+    let timestamp_value = match timestamp_to {
+        Some(to) => format!(", timestamp: {{ from: 0, to: {} }}", to),
+        None => "".to_string(),
+    };
+
     let order_value = match order {
         TransactionOrder::Ascending => "ASC",
         TransactionOrder::Descending => "DESC",
@@ -135,11 +157,12 @@ fn get_transactions_query(
 
     format!(
         r#"query DataAvailabilityTransactions {{
-            transactions(owners: {:?}, limit: {}, after: {}, order: {}, hasTags: true) {{
+            transactions(owners: {:?}, limit: {}, after: {}, order: {}, hasTags: true{}) {{
                 edges {{
                     node {{
                         id
                         address
+                        timestamp
                     }}
                     cursor
                 }}
@@ -149,7 +172,7 @@ fn get_transactions_query(
                 }}
             }}
         }}"#,
-        owner_addresses, limit, after_value, order_value
+        owner_addresses, limit, after_value, order_value, timestamp_value
     )
 }
 
@@ -166,17 +189,36 @@ pub async fn get_transactions_api(
     limit: i32,
     end_cursor: &Option<String>,
     order: TransactionOrder,
+    timestamp_to: Option<u64>,
 ) -> Result<TransactionsAPIResponse, MomokaVerifierError> {
     let submitters = get_submitters(environment, deployment);
-    let query = get_transactions_query(submitters, limit, end_cursor, order);
+    let query = get_transactions_query(submitters, limit, end_cursor, order, timestamp_to);
 
-    let client = Client::new(BundlrEndpoint::GraphQl.url());
-    let response = client
-        .query::<TransactionsAPIResponse>(&query)
-        .await
-        .map_err(|_e| MomokaVerifierError::CannotConnectToBundlr)?;
+    let response: BundlrGraphQlResponse<TransactionsAPIResponse> = post_with_timeout(
+        BundlrEndpoint::GraphQl.url(),
+        &BundlrGraphQlRequest { query },
+    )
+    .await
+    .map_err(|e| MomokaVerifierError::CannotConnectToBundlr { source: e.into() })?;
+
+    response
+        .data
+        .ok_or_else(|| MomokaVerifierError::CannotConnectToBundlr {
+            source: ErrorSource::message(format!("{:?}", response.errors)),
+        })
+}
+
+/// A graphql request body sent to the Bundlr node.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct BundlrGraphQlRequest {
+    query: String,
+}
 
-    Ok(response.unwrap())
+/// A graphql response returned by the Bundlr node.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+struct BundlrGraphQlResponse<TData> {
+    data: Option<TData>,
+    errors: Option<serde_json::Value>,
 }
 
 /// API response for grabbing bulk transactions from momoka.
@@ -200,23 +242,83 @@ pub async fn get_last_transaction_api(
     environment: &Environment,
     deployment: &Deployment,
 ) -> Result<BundlrTransactionEdge, MomokaVerifierError> {
+    get_edge_transaction_api(environment, deployment, TransactionOrder::Descending).await
+}
+
+/// Retrieves the first ever transaction using the specified environment and deployment.
+///
+/// # Arguments
+///
+/// * `environment` - A reference to the `Environment` enum representing the environment.
+/// * `deployment` - A reference to the `Deployment` enum representing the deployment.
+///
+/// # Returns
+///
+/// A `Result` containing the first transaction if successful, or a `MomokaVerifierError` if an error occurs.
+pub async fn get_first_transaction_api(
+    environment: &Environment,
+    deployment: &Deployment,
+) -> Result<BundlrTransactionEdge, MomokaVerifierError> {
+    get_edge_transaction_api(environment, deployment, TransactionOrder::Ascending).await
+}
+
+/// Retrieves the transaction at one end of the history.
+///
+/// # Arguments
+///
+/// * `environment` - A reference to the `Environment` enum representing the environment.
+/// * `deployment` - A reference to the `Deployment` enum representing the deployment.
+/// * `order` - `Ascending` for the first transaction, `Descending` for the last.
+///
+/// # Returns
+///
+/// A `Result` containing the transaction, or `NoLastTransactionFound` if there are none.
+async fn get_edge_transaction_api(
+    environment: &Environment,
+    deployment: &Deployment,
+    order: TransactionOrder,
+) -> Result<BundlrTransactionEdge, MomokaVerifierError> {
+    let response = get_transactions_api(environment, deployment, 1, &None, order, None)
+        .await?
+        .transactions;
+
+    let edge = response
+        .edges
+        .first()
+        .ok_or(MomokaVerifierError::NoLastTransactionFound)?;
+
+    // always the first one as we doing limit 1
+    Ok(edge.to_owned())
+}
+
+/// Retrieves the cursor of the last transaction uploaded at or before a timestamp.
+///
+/// # Arguments
+///
+/// * `environment` - A reference to the `Environment` enum representing the environment.
+/// * `deployment` - A reference to the `Deployment` enum representing the deployment.
+/// * `timestamp` - The timestamp in milliseconds.
+///
+/// # Returns
+///
+/// A `Result` containing the cursor of the transaction, or `None` if no transaction was uploaded by then.
+pub async fn get_cursor_at_timestamp_api(
+    environment: &Environment,
+    deployment: &Deployment,
+    timestamp: u64,
+) -> Result<Option<String>, MomokaVerifierError> {
     let response = get_transactions_api(
         environment,
         deployment,
         1,
         &None,
         TransactionOrder::Descending,
+        Some(timestamp),
     )
     .await?
     .transactions;
 
-    let edge = response
-        .edges
-        .first()
-        .ok_or(MomokaVerifierError::NoLastTransactionFound)?;
-
-    // always the first one as we doing limit 1
-    Ok(edge.to_owned())
+    Ok(response.edges.into_iter().next().map(|edge| edge.cursor))
 }
 
 /// Retrieves a bulk of transaction ids from Momoka, up to a maximum number of pulls.
@@ -256,6 +358,7 @@ pub async fn get_bulk_transactions_ids_api(
             1000,
             &result.next,
             TransactionOrder::Ascending,
+            None,
         )
         .await?
         .transactions;
@@ -286,6 +389,9 @@ pub async fn get_bulk_transactions_ids_api(
 }
 
 /// Bundlr transaction in base 64 format aka the data
+///
+/// The `address` is what the bulk api claims uploaded the data item, it is replaced with
+/// the owner derived from the verified data item by `verify_data_items`.
 #[derive(Debug, Deserialize)]
 pub struct BundlrTransactionBase64 {
     pub id: String,
@@ -300,8 +406,57 @@ pub struct BundlrBulkTransactionsResponse<TSuccess> {
     pub failed: std::collections::HashMap<MomokaTxId, MomokaVerifierError>,
 }
 
+/// Reads the archive data items are replayed from, if the verifier is running offline.
+fn replay_archive() -> Option<TransactionArchive> {
+    read_transaction_archive_cache().filter(|archive| archive.mode == ArchiveMode::Replay)
+}
+
+/// The error raised for a data item which is not in the archive being replayed.
+fn not_archived() -> MomokaVerifierError {
+    MomokaVerifierError::CannotConnectToBundlr {
+        source: ErrorSource::message("the data item is not in the archive"),
+    }
+}
+
+/// Reads a bulk set of transactions from the archive as the Bundlr node returned them.
+///
+/// # Arguments
+///
+/// * `archive` - The archive to read the transactions from.
+/// * `tx_ids` - The IDs of the transactions to read.
+///
+/// # Returns
+///
+/// The archived transactions, with the ones which are not archived as failed.
+fn read_archived_transactions_base_64(
+    archive: &TransactionArchive,
+    tx_ids: &[String],
+) -> BundlrBulkTransactionsResponse<BundlrTransactionBase64> {
+    let mut response = BundlrBulkTransactionsResponse {
+        success: vec![],
+        failed: HashMap::new(),
+    };
+
+    for tx_id in tx_ids {
+        match archive.read_data_item(tx_id) {
+            Some(data_item) => response.success.push(BundlrTransactionBase64 {
+                id: data_item.id,
+                address: data_item.address,
+                data: data_item.data,
+            }),
+            None => {
+                response.failed.insert(tx_id.clone(), not_archived());
+            }
+        }
+    }
+
+    response
+}
+
 /// Fetches a bulk set of transactions from the Bundlr node and returns the result as a `BundlrBulkTransactionsResponse` containing base64-encoded transaction data.
 ///
+/// When replaying an archive the transactions are read from it instead.
+///
 /// # Arguments
 ///
 /// * `tx_ids` - A slice of `String` objects representing the IDs of the transactions to fetch.
@@ -331,9 +486,163 @@ pub struct BundlrBulkTransactionsResponse<TSuccess> {
 async fn get_bulk_transactions_base_64_api(
     tx_ids: &[String],
 ) -> Result<BundlrBulkTransactionsResponse<BundlrTransactionBase64>, MomokaVerifierError> {
+    if let Some(archive) = replay_archive() {
+        return Ok(read_archived_transactions_base_64(&archive, tx_ids));
+    }
+
     post_with_timeout(BundlrEndpoint::BulkTxsData.url(), &tx_ids.to_vec())
         .await
-        .map_err(|_| MomokaVerifierError::CannotConnectToBundlr)
+        .map_err(|e| MomokaVerifierError::CannotConnectToBundlr { source: e.into() })
+}
+
+/// How many data item headers are fetched from the Bundlr node at once.
+const DATA_ITEM_HEADER_CONCURRENCY: usize = 50;
+
+/// Fetches the header of a data item (owner, signature, tags etc) from the Bundlr node,
+/// or reads it from the archive when replaying one.
+///
+/// # Arguments
+///
+/// * `tx_id` - The ID of the data item to fetch the header for.
+///
+/// # Returns
+///
+/// A `Result` containing the `BundlrTransactionHeader`, or `CannotConnectToBundlr` if it could not be fetched.
+async fn get_transaction_header_api(
+    tx_id: &MomokaTxId,
+) -> Result<BundlrTransactionHeader, MomokaVerifierError> {
+    if let Some(archive) = replay_archive() {
+        return archive
+            .read_data_item(tx_id)
+            .map(|data_item| data_item.header)
+            .ok_or_else(not_archived);
+    }
+
+    get_with_timeout(&format!("{}{}", BundlrEndpoint::Tx.url(), tx_id))
+        .await
+        .map_err(|e| MomokaVerifierError::CannotConnectToBundlr { source: e.into() })
+}
+
+/// Verifies a data item returned by the bulk api against its header.
+///
+/// The id of a data item is the hash of its signature, so the id is checked against the
+/// signature before the signature is checked against the data, which binds the id the data
+/// item is known by to the data it holds.
+///
+/// # Arguments
+///
+/// * `result` - The data item as the bulk api returned it.
+/// * `header` - The header of the data item.
+///
+/// # Returns
+///
+/// The address of the owner of the data item, or `DataItemInvalidSignature` if its id or
+/// signature do not match.
+fn verify_data_item(
+    result: &BundlrTransactionBase64,
+    header: &BundlrTransactionHeader,
+) -> Result<Address, MomokaVerifierError> {
+    if header.id != result.id {
+        return Err(MomokaVerifierError::DataItemInvalidSignature {
+            source: ErrorSource::default(),
+        });
+    }
+
+    let data = general_purpose::STANDARD
+        .decode(&result.data)
+        .map_err(|e| MomokaVerifierError::InvalidTransactionFormat { source: e.into() })?;
+
+    let data_item = DataItem::from_header(header, data)?;
+    if data_item.id() != result.id {
+        return Err(MomokaVerifierError::DataItemInvalidSignature {
+            source: ErrorSource::message("the id is not the hash of the signature"),
+        });
+    }
+
+    verify_data_item_signature(&data_item)
+}
+
+/// Verifies the ANS-104 signature of each data item returned by the bulk api.
+///
+/// The header of each data item is fetched and put together with the data to rebuild the
+/// data item, its signature is verified and the address the bulk api returned is replaced
+/// with the owner derived from the data item itself, so a bad api response can not spoof
+/// who uploaded it. A header is fetched for every data item passed in, so data items which
+/// are not going to be verified have to be dropped before.
+///
+/// When recording an archive each data item is archived as the bulk api returned it together
+/// with its header, before it is verified so failed data items can be reproduced too.
+///
+/// # Arguments
+///
+/// * `results` - The data items returned by the bulk api.
+///
+/// # Returns
+///
+/// A tuple of the verified data items and the data items which failed verification.
+async fn verify_data_items(
+    results: Vec<BundlrTransactionBase64>,
+) -> (Vec<BundlrTransactionBase64>, Vec<TransactionError>) {
+    let recording =
+        read_transaction_archive_cache().filter(|archive| archive.mode == ArchiveMode::Record);
+    let recording = &recording;
+
+    let verified = futures::stream::iter(results.into_iter().map(|mut result| async move {
+        let verification = async {
+            let header = get_transaction_header_api(&result.id).await?;
+            if let Some(archive) = recording {
+                archive.write_data_item(&ArchivedDataItem {
+                    id: result.id.clone(),
+                    address: result.address,
+                    data: result.data.clone(),
+                    header: header.clone(),
+                });
+            }
+
+            verify_data_item(&result, &header)
+        }
+        .await;
+
+        match verification {
+            Ok(owner_address) => {
+                result.address = owner_address;
+                Ok(result)
+            }
+            Err(error) => Err(TransactionError::new(result.id, error)),
+        }
+    }))
+    .buffered(DATA_ITEM_HEADER_CONCURRENCY)
+    .collect::<Vec<_>>()
+    .await;
+
+    let mut success = vec![];
+    let mut failed = vec![];
+    for result in verified {
+        match result {
+            Ok(result) => success.push(result),
+            Err(tx_error) => failed.push(tx_error),
+        }
+    }
+
+    (success, failed)
+}
+
+/// Decodes the base64-encoded `data` field of a `BundlrTransactionBase64` into a `String`.
+///
+/// # Arguments
+///
+/// * `result` - The `BundlrTransactionBase64` to decode.
+///
+/// # Returns
+///
+/// The decoded `String`, or `InvalidTransactionFormat` if the data is not base64-encoded UTF-8.
+fn decode_base_64(result: &BundlrTransactionBase64) -> Result<String, MomokaVerifierError> {
+    let decoded = general_purpose::STANDARD
+        .decode(&result.data)
+        .map_err(|e| MomokaVerifierError::InvalidTransactionFormat { source: e.into() })?;
+
+    String::from_utf8(decoded)
+        .map_err(|e| MomokaVerifierError::InvalidTransactionFormat { source: e.into() })
 }
 
 /// Decodes the base64-encoded `data` field of each `BundlrTransactionBase64`
@@ -378,12 +687,7 @@ async fn from_base_64<TResult>(
     let mut tasks = Vec::new();
     for result in results {
         let task = async move {
-            let decoded = general_purpose::STANDARD
-                .decode(&result.data)
-                .map_err(|_| MomokaVerifierError::InvalidTransactionFormat)?;
-
-            let transaction = String::from_utf8(decoded)
-                .map_err(|_| MomokaVerifierError::InvalidTransactionFormat)?;
+            let transaction = decode_base_64(result)?;
 
             let result = builder(&transaction, result);
 
@@ -425,7 +729,9 @@ fn transaction_builder(
         serde_json::from_str(decoded_transaction).map_err(|_| {
             TransactionError::new(
                 reference.id.clone(),
-                MomokaVerifierError::InvalidTransactionFormat,
+                MomokaVerifierError::InvalidTransactionFormat {
+                    source: ErrorSource::default(),
+                },
             )
         })?;
 
@@ -435,7 +741,9 @@ fn transaction_builder(
         &MomokaTransactionName::from_str(json_value["type"].as_str().unwrap()).map_err(|_| {
             TransactionError::new(
                 reference.id.clone(),
-                MomokaVerifierError::InvalidTransactionFormat,
+                MomokaVerifierError::InvalidTransactionFormat {
+                    source: ErrorSource::default(),
+                },
             )
         })?,
     )
@@ -448,6 +756,7 @@ fn transaction_builder(
         momoka_tx: transaction_type,
         // For now, we do not have the response from the timestamp proofs.
         timestamp_proofs_response: None,
+        timestamp_proofs_submitter: None,
         pointer_transaction_summary: None,
     };
 
@@ -479,12 +788,15 @@ fn transaction_timestamp_proofs_builder(
         serde_json::from_str::<TimestampProofsResponse>(decoded_transaction).map_err(|_| {
             TransactionError::new(
                 reference.id.clone(),
-                MomokaVerifierError::InvalidTransactionFormat,
+                MomokaVerifierError::InvalidTransactionFormat {
+                    source: ErrorSource::default(),
+                },
             )
         })?;
 
     Ok(TimestampProofsSummary {
         id: reference.id.to_owned(),
+        submitter: reference.address.to_owned(),
         response,
     })
 }
@@ -506,11 +818,14 @@ pub async fn get_transaction_api(
     tx_id: &MomokaTxId,
 ) -> Result<TransactionSummary, MomokaVerifierError> {
     let tx_ids = vec![tx_id.to_owned()];
-    let mut result = get_bulk_transactions_api(&tx_ids).await?;
-    let single_transaction = result
-        .success
-        .pop()
-        .ok_or(MomokaVerifierError::CannotConnectToBundlr)?;
+    let mut result = get_bulk_transactions_api(&tx_ids, None).await?;
+    let single_transaction =
+        result
+            .success
+            .pop()
+            .ok_or(MomokaVerifierError::CannotConnectToBundlr {
+                source: ErrorSource::default(),
+            })?;
     Ok(single_transaction)
 }
 
@@ -526,6 +841,8 @@ pub const CHUNK_SIZE: usize = 1000;
 /// # Arguments
 ///
 /// * `tx_ids` - A slice of transaction IDs
+/// * `filter` - The filter transactions have to pass (optional). Transactions which do not pass it are dropped
+///   as soon as they are parsed, before their data items are verified and their timestamp proofs are fetched.
 ///
 /// # Returns
 ///
@@ -539,7 +856,7 @@ pub const CHUNK_SIZE: usize = 1000;
 ///
 /// async fn my_function() {
 ///     let tx_ids = vec!["id1".to_owned(), "id2".to_owned()];
-///     let result = get_bulk_transactions_api(&tx_ids).await;
+///     let result = get_bulk_transactions_api(&tx_ids, None).await;
 ///     match result {
 ///         Ok(response) => {
 ///             // Process the successful response
@@ -554,6 +871,7 @@ pub const CHUNK_SIZE: usize = 1000;
 /// ```
 pub async fn get_bulk_transactions_api(
     tx_ids: &[MomokaTxId],
+    filter: Option<&TransactionFilter>,
 ) -> Result<BundlrBulkTransactionsResponse<TransactionSummary>, MomokaVerifierError> {
     let mut combined_response = BundlrBulkTransactionsResponse::<TransactionSummary> {
         success: vec![],
@@ -564,9 +882,44 @@ pub async fn get_bulk_transactions_api(
     for tx_ids_chunk in tx_ids.chunks(CHUNK_SIZE) {
         let fut = async move {
             let transactions_base_64 = get_bulk_transactions_base_64_api(tx_ids_chunk).await?;
+            let failed_transactions = transactions_base_64.failed;
 
-            let mut transactions =
-                from_base_64(&transactions_base_64.success, transaction_builder).await?;
+            // parse and filter the transactions before verifying them, so the transactions the
+            // filter drops never have their data item headers fetched
+            let mut transactions = vec![];
+            let mut parsed_transactions = HashMap::new();
+            let mut data_items = vec![];
+            for result in transactions_base_64.success {
+                let parsed = decode_base_64(&result)
+                    .map_err(|e| TransactionError::new(result.id.clone(), e))
+                    .and_then(|decoded| transaction_builder(&decoded, &result));
+
+                match parsed {
+                    Ok(tx_summary) => {
+                        if filter.is_none_or(|filter| filter.matches(&tx_summary.momoka_tx)) {
+                            parsed_transactions.insert(tx_summary.id.clone(), tx_summary);
+                            data_items.push(result);
+                        }
+                    }
+                    Err(tx_error) => transactions.push(Err(tx_error)),
+                }
+            }
+
+            let (data_items, failed_data_items) = verify_data_items(data_items).await;
+            transactions.extend(data_items.into_iter().filter_map(|result| {
+                parsed_transactions
+                    .remove(&result.id)
+                    .map(|mut tx_summary| {
+                        tx_summary.submitter = result.address;
+                        Ok(tx_summary)
+                    })
+            }));
+            transactions.extend(failed_data_items.into_iter().map(Err));
+            transactions.extend(
+                failed_transactions
+                    .into_iter()
+                    .map(|(id, error)| Err(TransactionError::new(id, error))),
+            );
 
             let timestamp_proofs_transaction_ids = transactions
                 .iter()
@@ -584,27 +937,52 @@ pub async fn get_bulk_transactions_api(
                 .collect::<Vec<_>>();
 
             let transactions_timestamp_proofs_base64 =
-                get_bulk_transactions_base_64_api(&timestamp_proofs_transaction_ids)
-                    .await?
-                    .success;
+                get_bulk_transactions_base_64_api(&timestamp_proofs_transaction_ids).await?;
+            let failed_timestamp_proofs_transactions = transactions_timestamp_proofs_base64.failed;
+            let (transactions_timestamp_proofs_base64, failed_timestamp_proofs_data_items) =
+                verify_data_items(transactions_timestamp_proofs_base64.success).await;
 
-            let transaction_timestamp_proofs = from_base_64(
+            let mut transaction_timestamp_proofs: HashMap<MomokaTxId, _> = from_base_64(
                 &transactions_timestamp_proofs_base64,
                 transaction_timestamp_proofs_builder,
             )
-            .await?;
-
-            for (tx_summary, tx_proofs_result) in transactions
-                .iter_mut()
-                .filter_map(|tx_result| tx_result.as_mut().ok())
-                .zip(transaction_timestamp_proofs.into_iter())
-            {
-                if let Ok(tx_proofs) = tx_proofs_result {
-                    let id = &tx_summary.momoka_tx.get_timestamp_proofs()?.response.id;
-
-                    // should not happen but lets panic just incase
-                    assert_eq!(*id, tx_proofs.id);
-                    tx_summary.set_timestamp_proofs_response(tx_proofs.response);
+            .await?
+            .into_iter()
+            .chain(failed_timestamp_proofs_data_items.into_iter().map(Err))
+            .chain(
+                failed_timestamp_proofs_transactions
+                    .into_iter()
+                    .map(|(id, error)| Err(TransactionError::new(id, error))),
+            )
+            .map(|tx_proofs_result| match &tx_proofs_result {
+                Ok(tx_proofs) => (tx_proofs.id.clone(), tx_proofs_result),
+                Err(tx_error) => (tx_error.id.clone(), tx_proofs_result),
+            })
+            .collect();
+
+            for tx_result in transactions.iter_mut() {
+                let failed = match tx_result.as_mut() {
+                    Ok(tx_summary) => {
+                        let id = &tx_summary.momoka_tx.get_timestamp_proofs()?.response.id;
+
+                        match transaction_timestamp_proofs.remove(id) {
+                            Some(Ok(tx_proofs)) => {
+                                tx_summary.set_timestamp_proofs_response(tx_proofs.response);
+                                tx_summary.set_timestamp_proofs_submitter(tx_proofs.submitter);
+                                None
+                            }
+                            // the timestamp proofs data item failed verification so the transaction can not be trusted
+                            Some(Err(tx_error)) => {
+                                Some(TransactionError::new(tx_summary.id.clone(), tx_error.error))
+                            }
+                            None => None,
+                        }
+                    }
+                    Err(_) => None,
+                };
+
+                if let Some(tx_error) = failed {
+                    *tx_result = Err(tx_error);
                 }
             }
 
@@ -625,3 +1003,74 @@ pub async fn get_bulk_transactions_api(
 
     Ok(combined_response)
 }
+
+#[cfg(test)]
+mod tests {
+    use data_encoding::BASE64URL_NOPAD;
+    use ethers::signers::{LocalWallet, Signer};
+
+    use super::*;
+    use crate::bundlr::{data_item::SignatureType, verify::sign_data_item};
+
+    /// Signs a data item with a new wallet, returning it as the bulk api and the header
+    /// endpoint return it.
+    async fn signed_data_item() -> (BundlrTransactionBase64, BundlrTransactionHeader, Address) {
+        let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+        let data = b"{\"type\":\"POST_CREATED\"}".to_vec();
+
+        let mut data_item = DataItem {
+            signature_type: SignatureType::Ethereum,
+            signature: vec![],
+            owner: wallet
+                .signer()
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec(),
+            target: vec![],
+            anchor: vec![],
+            tags: vec![],
+            data: data.clone(),
+        };
+        sign_data_item(&wallet, &mut data_item).await;
+
+        let header = BundlrTransactionHeader {
+            id: data_item.id(),
+            currency: "matic".to_string(),
+            signature_type: 3,
+            owner: BASE64URL_NOPAD.encode(&data_item.owner),
+            signature: BASE64URL_NOPAD.encode(&data_item.signature),
+            target: String::new(),
+            anchor: String::new(),
+            tags: vec![],
+        };
+        let result = BundlrTransactionBase64 {
+            id: data_item.id(),
+            address: Address::zero(),
+            data: general_purpose::STANDARD.encode(&data),
+        };
+
+        (result, header, wallet.address())
+    }
+
+    #[tokio::test]
+    async fn test_verify_data_item_binds_id_to_signature() {
+        let (result, header, owner) = signed_data_item().await;
+        assert_eq!(verify_data_item(&result, &header), Ok(owner));
+
+        // an id which is not the hash of the signature, even if the api and header agree on it
+        let tampered = "PoDx9KfHCIKAdJg2WyfWcx2B_K5aMKq0je7EtIcOc4w".to_string();
+        let result = BundlrTransactionBase64 {
+            id: tampered.clone(),
+            ..result
+        };
+        let header = BundlrTransactionHeader {
+            id: tampered,
+            ..header
+        };
+        assert!(matches!(
+            verify_data_item(&result, &header),
+            Err(MomokaVerifierError::DataItemInvalidSignature { .. })
+        ));
+    }
+}
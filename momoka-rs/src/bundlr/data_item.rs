@@ -0,0 +1,270 @@
+use data_encoding::BASE64URL_NOPAD;
+use ethers::{types::Address, utils::keccak256};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::types::{
+    transaction::MomokaTxId,
+    verifier_error::{ErrorSource, MomokaVerifierError},
+};
+
+/// A tag on an ANS-104 data item.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DataItemTag {
+    /// The tag name
+    pub name: String,
+
+    /// The tag value
+    pub value: String,
+}
+
+/// The header of an ANS-104 data item as the Bundlr node returns it on `/tx/{id}`,
+/// this is everything in the data item apart from the data itself.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BundlrTransactionHeader {
+    /// The id of the data item
+    pub id: MomokaTxId,
+
+    /// The currency the data item was paid with
+    pub currency: String,
+
+    /// The ANS-104 signature type, the first two bytes of the binary data item
+    #[serde(rename = "signatureType")]
+    pub signature_type: u16,
+
+    /// The owner public key (base64url)
+    pub owner: String,
+
+    /// The signature (base64url)
+    pub signature: String,
+
+    /// The target (base64url), empty if not set
+    #[serde(default)]
+    pub target: String,
+
+    /// The anchor (base64url), empty if not set
+    #[serde(default)]
+    pub anchor: String,
+
+    /// The tags
+    #[serde(default)]
+    pub tags: Vec<DataItemTag>,
+}
+
+/// The ANS-104 signature types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureType {
+    /// RSA-PSS 4096 arweave wallets
+    Arweave = 1,
+    /// secp256k1 ethereum style wallets
+    Ethereum = 3,
+}
+
+impl SignatureType {
+    /// Maps the signature type bytes of a data item to the signature type it was signed with.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The signature type of the data item.
+    ///
+    /// # Returns
+    ///
+    /// The signature type, or `DataItemInvalidSignature` if the signature type is not supported.
+    pub fn from_raw(raw: u16) -> Result<Self, MomokaVerifierError> {
+        match raw {
+            1 => Ok(SignatureType::Arweave),
+            3 => Ok(SignatureType::Ethereum),
+            _ => Err(MomokaVerifierError::DataItemInvalidSignature {
+                source: ErrorSource::message(format!("unsupported signature type {}", raw)),
+            }),
+        }
+    }
+
+    /// The length of the owner public key for this signature type.
+    pub fn owner_length(&self) -> usize {
+        match self {
+            SignatureType::Arweave => 512,
+            SignatureType::Ethereum => 65,
+        }
+    }
+
+    /// The length of the signature for this signature type.
+    pub fn signature_length(&self) -> usize {
+        match self {
+            SignatureType::Arweave => 512,
+            SignatureType::Ethereum => 65,
+        }
+    }
+}
+
+/// An ANS-104 data item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataItem {
+    /// The signature type
+    pub signature_type: SignatureType,
+
+    /// The raw signature
+    pub signature: Vec<u8>,
+
+    /// The raw owner public key
+    pub owner: Vec<u8>,
+
+    /// The raw target, empty if not set
+    pub target: Vec<u8>,
+
+    /// The raw anchor, empty if not set
+    pub anchor: Vec<u8>,
+
+    /// The tags
+    pub tags: Vec<DataItemTag>,
+
+    /// The raw data
+    pub data: Vec<u8>,
+}
+
+/// Decodes a base64url field of a data item header.
+fn decode_field(value: &str) -> Result<Vec<u8>, MomokaVerifierError> {
+    BASE64URL_NOPAD
+        .decode(value.trim_end_matches('=').as_bytes())
+        .map_err(|e| MomokaVerifierError::DataItemInvalidSignature { source: e.into() })
+}
+
+/// Writes an avro long (zigzag varint) to the buffer.
+fn write_avro_long(buffer: &mut Vec<u8>, value: i64) {
+    let mut n = ((value << 1) ^ (value >> 63)) as u64;
+    while n & !0x7f != 0 {
+        buffer.push(((n & 0x7f) | 0x80) as u8);
+        n >>= 7;
+    }
+    buffer.push(n as u8);
+}
+
+/// Writes avro bytes (length prefixed) to the buffer.
+fn write_avro_bytes(buffer: &mut Vec<u8>, value: &[u8]) {
+    write_avro_long(buffer, value.len() as i64);
+    buffer.extend_from_slice(value);
+}
+
+impl DataItem {
+    /// Builds a data item from the header returned by the Bundlr node and the data.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The data item header.
+    /// * `data` - The decoded data of the data item.
+    ///
+    /// # Returns
+    ///
+    /// The data item, or `DataItemInvalidSignature` if the header fields are malformed.
+    pub fn from_header(
+        header: &BundlrTransactionHeader,
+        data: Vec<u8>,
+    ) -> Result<Self, MomokaVerifierError> {
+        let signature_type = SignatureType::from_raw(header.signature_type)?;
+
+        let owner = decode_field(&header.owner)?;
+        let signature = decode_field(&header.signature)?;
+        if owner.len() != signature_type.owner_length()
+            || signature.len() != signature_type.signature_length()
+        {
+            return Err(MomokaVerifierError::DataItemInvalidSignature {
+                source: ErrorSource::default(),
+            });
+        }
+
+        Ok(DataItem {
+            signature_type,
+            signature,
+            owner,
+            target: decode_field(&header.target)?,
+            anchor: decode_field(&header.anchor)?,
+            tags: header.tags.clone(),
+            data,
+        })
+    }
+
+    /// Returns the id of the data item, which is the base64url sha256 hash of its signature.
+    pub fn id(&self) -> MomokaTxId {
+        BASE64URL_NOPAD.encode(&Sha256::digest(&self.signature))
+    }
+
+    /// Returns the tags avro encoded as they are laid out in the binary data item.
+    ///
+    /// A data item without tags has no tag bytes at all.
+    pub fn raw_tags(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        if self.tags.is_empty() {
+            return buffer;
+        }
+
+        write_avro_long(&mut buffer, self.tags.len() as i64);
+        for tag in &self.tags {
+            write_avro_bytes(&mut buffer, tag.name.as_bytes());
+            write_avro_bytes(&mut buffer, tag.value.as_bytes());
+        }
+        write_avro_long(&mut buffer, 0);
+
+        buffer
+    }
+
+    /// Derives the address of the owner of the data item.
+    ///
+    /// # Returns
+    ///
+    /// The EVM address of the owner public key, or `DataItemInvalidSignature` if the
+    /// data item was not signed by an ethereum style wallet.
+    pub fn owner_address(&self) -> Result<Address, MomokaVerifierError> {
+        if self.signature_type != SignatureType::Ethereum {
+            return Err(MomokaVerifierError::DataItemInvalidSignature {
+                source: ErrorSource::default(),
+            });
+        }
+
+        // uncompressed public key, drop the 0x04 prefix
+        let hash = keccak256(&self.owner[1..]);
+        Ok(Address::from_slice(&hash[12..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_tags_avro_encoding() {
+        let mut data_item = DataItem {
+            signature_type: SignatureType::Ethereum,
+            signature: vec![],
+            owner: vec![],
+            target: vec![],
+            anchor: vec![],
+            tags: vec![],
+            data: vec![],
+        };
+
+        assert!(data_item.raw_tags().is_empty());
+
+        data_item.tags = vec![DataItemTag {
+            name: "Content-Type".to_string(),
+            value: "application/json".to_string(),
+        }];
+
+        let mut expected = vec![0x02, 0x18];
+        expected.extend_from_slice(b"Content-Type");
+        expected.push(0x20);
+        expected.extend_from_slice(b"application/json");
+        expected.push(0x00);
+
+        assert_eq!(data_item.raw_tags(), expected);
+    }
+
+    #[test]
+    fn test_signature_type_from_raw() {
+        assert_eq!(SignatureType::from_raw(3), Ok(SignatureType::Ethereum));
+        assert_eq!(SignatureType::from_raw(1), Ok(SignatureType::Arweave));
+        assert!(matches!(
+            SignatureType::from_raw(4),
+            Err(MomokaVerifierError::DataItemInvalidSignature { .. })
+        ));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod api;
+pub mod data_item;
 pub mod verify;
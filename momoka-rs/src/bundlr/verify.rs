@@ -1,9 +1,13 @@
 use crate::types::{
-    transaction::TransactionTimestampProofsValidation, verifier_error::MomokaVerifierError,
+    transaction::TransactionTimestampProofsValidation,
+    verifier_error::{ErrorSource, MomokaVerifierError},
 };
 use bundlr_sdk::{
     deep_hash::DeepHashChunk, deep_hash_sync::deep_hash_sync, ArweaveSigner, Verifier,
 };
+use ethers::types::{Address, Signature};
+
+use super::data_item::DataItem;
 
 use data_encoding::BASE64URL_NOPAD;
 
@@ -45,17 +49,76 @@ pub async fn verify_timestamp_proofs(
 
     let pubk = BASE64URL_NOPAD
         .decode(&timestamp_proofs.public_key.clone().into_bytes())
-        .map_err(|_| MomokaVerifierError::TimestampProofInvalidSignature)?;
+        .map_err(|e| MomokaVerifierError::TimestampProofInvalidSignature { source: e.into() })?;
 
-    let msg =
-        deep_hash_sync(fields).map_err(|_| MomokaVerifierError::TimestampProofInvalidSignature)?;
+    let msg = deep_hash_sync(fields).map_err(|_| {
+        MomokaVerifierError::TimestampProofInvalidSignature {
+            source: ErrorSource::default(),
+        }
+    })?;
     let sig = BASE64URL_NOPAD
         .decode(&timestamp_proofs.signature.clone().into_bytes())
-        .map_err(|_| MomokaVerifierError::TimestampProofInvalidSignature)?;
+        .map_err(|e| MomokaVerifierError::TimestampProofInvalidSignature { source: e.into() })?;
 
     // Verify the signature using Arweave's verification algorithm
-    ArweaveSigner::verify(pubk.into(), msg, sig.into())
-        .map_err(|_| MomokaVerifierError::TimestampProofInvalidSignature)
+    ArweaveSigner::verify(pubk.into(), msg, sig.into()).map_err(|_| {
+        MomokaVerifierError::TimestampProofInvalidSignature {
+            source: ErrorSource::default(),
+        }
+    })
+}
+
+/// Verifies the signature of an ANS-104 data item and derives its owner address.
+///
+/// The signed message is the deep hash of the data item fields, the same as any bundler
+/// checks before accepting the data item. This means we do not have to trust the address
+/// a Bundlr API response claims uploaded the data item.
+///
+/// # Arguments
+///
+/// * `data_item` - The data item to verify.
+///
+/// # Returns
+///
+/// The address of the owner which signed the data item.
+///
+/// # Errors
+///
+/// Returns `DataItemInvalidSignature` if the data item was not signed by an ethereum style
+/// wallet or the signature does not match its owner.
+pub fn verify_data_item_signature(data_item: &DataItem) -> Result<Address, MomokaVerifierError> {
+    let owner_address = data_item.owner_address()?;
+
+    let fields = DeepHashChunk::Chunks(vec![
+        DeepHashChunk::Chunk("dataitem".into()),
+        DeepHashChunk::Chunk("1".into()),
+        DeepHashChunk::Chunk((data_item.signature_type as u8).to_string().into()),
+        DeepHashChunk::Chunk(data_item.owner.clone().into()),
+        DeepHashChunk::Chunk(data_item.target.clone().into()),
+        DeepHashChunk::Chunk(data_item.anchor.clone().into()),
+        DeepHashChunk::Chunk(data_item.raw_tags().into()),
+        DeepHashChunk::Chunk(data_item.data.clone().into()),
+    ]);
+
+    let msg =
+        deep_hash_sync(fields).map_err(|_| MomokaVerifierError::DataItemInvalidSignature {
+            source: ErrorSource::default(),
+        })?;
+
+    // ethereum signers sign the deep hash as a personal message
+    let signature = Signature::try_from(data_item.signature.as_slice())
+        .map_err(|e| MomokaVerifierError::DataItemInvalidSignature { source: e.into() })?;
+    let signer = signature
+        .recover(msg.to_vec())
+        .map_err(|e| MomokaVerifierError::DataItemInvalidSignature { source: e.into() })?;
+
+    if signer != owner_address {
+        return Err(MomokaVerifierError::DataItemInvalidSignature {
+            source: ErrorSource::default(),
+        });
+    }
+
+    Ok(owner_address)
 }
 
 #[cfg(test)]
@@ -76,3 +139,70 @@ async fn test_verify_timestamp_proofs() {
     // Verify the timestamp proofs
     let _ = verify_timestamp_proofs(&timestamp_proofs).await.is_ok();
 }
+
+/// Signs a data item with the given wallet the way Bundlr does, for tests.
+///
+/// # Arguments
+///
+/// * `wallet` - The wallet to sign the data item with.
+/// * `data_item` - The data item to sign, its `owner` must be the public key of `wallet`.
+#[cfg(test)]
+pub(crate) async fn sign_data_item(
+    wallet: &ethers::signers::LocalWallet,
+    data_item: &mut DataItem,
+) {
+    use ethers::signers::Signer;
+
+    let msg = deep_hash_sync(DeepHashChunk::Chunks(vec![
+        DeepHashChunk::Chunk("dataitem".into()),
+        DeepHashChunk::Chunk("1".into()),
+        DeepHashChunk::Chunk("3".into()),
+        DeepHashChunk::Chunk(data_item.owner.clone().into()),
+        DeepHashChunk::Chunk(data_item.target.clone().into()),
+        DeepHashChunk::Chunk(data_item.anchor.clone().into()),
+        DeepHashChunk::Chunk(data_item.raw_tags().into()),
+        DeepHashChunk::Chunk(data_item.data.clone().into()),
+    ]))
+    .unwrap();
+    data_item.signature = wallet.sign_message(msg.to_vec()).await.unwrap().to_vec();
+}
+
+#[cfg(test)]
+#[tokio::test]
+async fn test_verify_data_item_signature() {
+    use super::data_item::{DataItemTag, SignatureType};
+    use ethers::signers::{LocalWallet, Signer};
+
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+
+    let mut data_item = DataItem {
+        signature_type: SignatureType::Ethereum,
+        signature: vec![],
+        owner: wallet
+            .signer()
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec(),
+        target: vec![],
+        anchor: vec![],
+        tags: vec![DataItemTag {
+            name: "Content-Type".to_string(),
+            value: "application/json".to_string(),
+        }],
+        data: b"{\"type\":\"POST_CREATED\"}".to_vec(),
+    };
+
+    sign_data_item(&wallet, &mut data_item).await;
+
+    assert_eq!(verify_data_item_signature(&data_item), Ok(wallet.address()));
+
+    // a tampered payload must not recover to the owner
+    data_item.data = b"{\"type\":\"MIRROR_CREATED\"}".to_vec();
+    assert_eq!(
+        verify_data_item_signature(&data_item),
+        Err(MomokaVerifierError::DataItemInvalidSignature {
+            source: ErrorSource::default(),
+        })
+    );
+}
@@ -1,7 +1,18 @@
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
-use crate::types::transaction::MomokaTxId;
+use ethers::types::{Block, H256};
+use tokio::sync::mpsc;
+
+use crate::alert::AlertMessage;
+use crate::archive::TransactionArchive;
+use crate::contracts::lens_hub::{LensProfileDetails, ProfileDetailsKey};
+use crate::export::PublicationExport;
+use crate::output::OutputFormat;
+use crate::transport::Transport;
+use crate::types::double_publication::DaPublicationRecord;
+use crate::types::indexed_publication::IndexedPublication;
+use crate::types::transaction::{MomokaTxId, TransactionSummary};
 use crate::types::verifier_error::MomokaVerifierError;
 
 pub struct TransactionCacheResult {
@@ -12,6 +23,16 @@ pub struct TransactionCacheResult {
 lazy_static::lazy_static! {
     static ref TRANSACTION_CACHE: RwLock<HashMap<MomokaTxId, Arc<TransactionCacheResult>>> = RwLock::new(HashMap::new());
     static ref SIGNATURE_CACHE: RwLock<HashMap<String, Arc<()>>> = RwLock::new(HashMap::new());
+    static ref PENDING_SETTLEMENT_CACHE: RwLock<HashMap<MomokaTxId, TransactionSummary>> = RwLock::new(HashMap::new());
+    static ref DA_PUBLICATION_CACHE: RwLock<HashMap<MomokaTxId, DaPublicationRecord>> = RwLock::new(HashMap::new());
+    static ref INDEXED_PUBLICATION_CACHE: RwLock<HashMap<MomokaTxId, IndexedPublication>> = RwLock::new(HashMap::new());
+    static ref BATCHED_BLOCK_CACHE: RwLock<HashMap<u64, HashMap<u64, Block<H256>>>> = RwLock::new(HashMap::new());
+    static ref BATCHED_PROFILE_DETAILS_CACHE: RwLock<HashMap<u64, HashMap<ProfileDetailsKey, LensProfileDetails>>> = RwLock::new(HashMap::new());
+    static ref TRANSACTION_ARCHIVE_CACHE: RwLock<Option<TransactionArchive>> = RwLock::new(None);
+    static ref TRANSPORT_CACHE: RwLock<Option<Arc<Transport>>> = RwLock::new(None);
+    static ref OUTPUT_FORMAT_CACHE: RwLock<OutputFormat> = RwLock::new(OutputFormat::default());
+    static ref ALERT_CACHE: RwLock<Option<mpsc::UnboundedSender<AlertMessage>>> = RwLock::new(None);
+    static ref PUBLICATION_EXPORT_CACHE: RwLock<Option<PublicationExport>> = RwLock::new(None);
 }
 
 /// Reads a value from the transaction cache dictionary based on the given key.
@@ -61,3 +82,290 @@ pub fn set_signature_cache(key: String) {
     let cache_value = Arc::new(());
     SIGNATURE_CACHE.write().unwrap().insert(key, cache_value);
 }
+
+/// Reads all the transactions which are timestamped but not yet settled on Arweave.
+///
+/// # Returns
+///
+/// The pending transactions, so they can be reported if they are not settled in time.
+pub fn read_pending_settlement_cache() -> Vec<TransactionSummary> {
+    PENDING_SETTLEMENT_CACHE
+        .read()
+        .unwrap()
+        .values()
+        .cloned()
+        .collect()
+}
+
+/// Marks a transaction as timestamped but not yet settled on Arweave.
+///
+/// # Arguments
+///
+/// * `transaction` - The verified transaction.
+pub fn set_pending_settlement_cache(transaction: TransactionSummary) {
+    PENDING_SETTLEMENT_CACHE
+        .write()
+        .unwrap()
+        .insert(transaction.id.clone(), transaction);
+}
+
+/// Removes a transaction from the pending settlement cache once its settlement is known.
+///
+/// # Arguments
+///
+/// * `key` - A momoka tx id slice representing the key to remove from the cache.
+pub fn remove_pending_settlement_cache(key: &MomokaTxId) {
+    PENDING_SETTLEMENT_CACHE.write().unwrap().remove(key);
+}
+
+/// Reads all the verified DA publications whose typed data signature could still be executed on-chain.
+///
+/// # Returns
+///
+/// A vector of the DA publication records.
+pub fn read_da_publication_cache() -> Vec<DaPublicationRecord> {
+    DA_PUBLICATION_CACHE
+        .read()
+        .unwrap()
+        .values()
+        .cloned()
+        .collect()
+}
+
+/// Records a verified DA publication to be watched for its signature being executed on-chain.
+///
+/// # Arguments
+///
+/// * `value` - The DA publication record, keyed by its tx id.
+pub fn set_da_publication_cache(value: DaPublicationRecord) {
+    DA_PUBLICATION_CACHE
+        .write()
+        .unwrap()
+        .insert(value.tx_id.clone(), value);
+}
+
+/// Stops watching a DA publication once its signature can no longer be executed on-chain.
+///
+/// # Arguments
+///
+/// * `key` - A momoka tx id slice representing the key to remove from the cache.
+pub fn remove_da_publication_cache(key: &MomokaTxId) {
+    DA_PUBLICATION_CACHE.write().unwrap().remove(key);
+}
+
+/// Reads a publication indexed in trusted indexing mode.
+///
+/// # Arguments
+///
+/// * `key` - A momoka tx id slice representing the key to look up in the cache.
+///
+/// # Returns
+///
+/// The indexed publication, or `None` if the transaction was not indexed.
+pub fn read_indexed_publication_cache(key: &MomokaTxId) -> Option<IndexedPublication> {
+    INDEXED_PUBLICATION_CACHE.read().unwrap().get(key).cloned()
+}
+
+/// Indexes a publication, replacing it if it was already indexed.
+///
+/// # Arguments
+///
+/// * `value` - The indexed publication, keyed by its tx id.
+pub fn set_indexed_publication_cache(value: IndexedPublication) {
+    INDEXED_PUBLICATION_CACHE
+        .write()
+        .unwrap()
+        .insert(value.tx_id.clone(), value);
+}
+
+/// Reads a block the nodes agreed on in the batched reads of any page being verified.
+///
+/// A block is the same whichever page read it, so pages verified at the same time (for example
+/// by resync partitions) can answer each other.
+///
+/// # Arguments
+///
+/// * `block_number` - The block number to look up in the cache.
+///
+/// # Returns
+///
+/// An `Option` containing the block if it was batched, or `None` if it has to be read from the nodes.
+pub fn read_batched_block_cache(block_number: u64) -> Option<Block<H256>> {
+    BATCHED_BLOCK_CACHE
+        .read()
+        .unwrap()
+        .values()
+        .find_map(|blocks| blocks.get(&block_number))
+        .cloned()
+}
+
+/// Reads the profile details the nodes agreed on in the batched reads of any page being verified.
+///
+/// # Arguments
+///
+/// * `key` - The profile id, signer, block number and modules the details were read for.
+///
+/// # Returns
+///
+/// An `Option` containing the profile details if they were batched, or `None` if they have to be read from the nodes.
+pub fn read_batched_profile_details_cache(key: &ProfileDetailsKey) -> Option<LensProfileDetails> {
+    BATCHED_PROFILE_DETAILS_CACHE
+        .read()
+        .unwrap()
+        .values()
+        .find_map(|profile_details| profile_details.get(key))
+        .cloned()
+}
+
+/// Adds batched reads the nodes agreed on to the ones cached for a page.
+///
+/// # Arguments
+///
+/// * `page` - The page the reads were batched for.
+/// * `blocks` - The agreed blocks keyed by block number.
+/// * `profile_details` - The agreed profile details keyed by what they were read for.
+pub fn extend_batched_reads_cache(
+    page: u64,
+    blocks: HashMap<u64, Block<H256>>,
+    profile_details: HashMap<ProfileDetailsKey, LensProfileDetails>,
+) {
+    BATCHED_BLOCK_CACHE
+        .write()
+        .unwrap()
+        .entry(page)
+        .or_default()
+        .extend(blocks);
+    BATCHED_PROFILE_DETAILS_CACHE
+        .write()
+        .unwrap()
+        .entry(page)
+        .or_default()
+        .extend(profile_details);
+}
+
+/// Removes the batched reads of a page once it has been verified.
+///
+/// # Arguments
+///
+/// * `page` - The page the reads were batched for.
+pub fn remove_batched_reads_cache(page: u64) {
+    BATCHED_BLOCK_CACHE.write().unwrap().remove(&page);
+    BATCHED_PROFILE_DETAILS_CACHE.write().unwrap().remove(&page);
+}
+
+/// Reads every batched read which is cached, whichever page it was batched for.
+///
+/// # Returns
+///
+/// The cached blocks keyed by block number and profile details keyed by what they were read for.
+pub fn read_batched_reads_cache() -> (
+    HashMap<u64, Block<H256>>,
+    HashMap<ProfileDetailsKey, LensProfileDetails>,
+) {
+    (
+        BATCHED_BLOCK_CACHE
+            .read()
+            .unwrap()
+            .values()
+            .flatten()
+            .map(|(block_number, block)| (*block_number, block.clone()))
+            .collect(),
+        BATCHED_PROFILE_DETAILS_CACHE
+            .read()
+            .unwrap()
+            .values()
+            .flatten()
+            .map(|(key, profile_details)| (*key, profile_details.clone()))
+            .collect(),
+    )
+}
+
+/// Reads the archive data items are recorded to or replayed from, if one is in use.
+///
+/// # Returns
+///
+/// An `Option` containing the archive, or `None` if data items are fetched from bundlr as usual.
+pub fn read_transaction_archive_cache() -> Option<TransactionArchive> {
+    TRANSACTION_ARCHIVE_CACHE.read().unwrap().clone()
+}
+
+/// Sets the archive data items are recorded to or replayed from.
+///
+/// # Arguments
+///
+/// * `archive` - The archive to use.
+pub fn set_transaction_archive_cache(archive: TransactionArchive) {
+    *TRANSACTION_ARCHIVE_CACHE.write().unwrap() = Some(archive);
+}
+
+/// Reads the transport requests are recorded to or replayed from, if one is in use.
+///
+/// # Returns
+///
+/// An `Option` containing the transport, or `None` if requests are sent as usual.
+pub fn read_transport_cache() -> Option<Arc<Transport>> {
+    TRANSPORT_CACHE.read().unwrap().clone()
+}
+
+/// Sets the transport requests are recorded to or replayed from.
+///
+/// # Arguments
+///
+/// * `transport` - The transport to use.
+pub fn set_transport_cache(transport: Transport) {
+    *TRANSPORT_CACHE.write().unwrap() = Some(Arc::new(transport));
+}
+
+/// Reads the format the verifier writes its output in.
+///
+/// # Returns
+///
+/// The output format, `Text` unless it was set.
+pub fn read_output_format_cache() -> OutputFormat {
+    *OUTPUT_FORMAT_CACHE.read().unwrap()
+}
+
+/// Sets the format the verifier writes its output in.
+///
+/// # Arguments
+///
+/// * `format` - The output format.
+pub fn set_output_format_cache(format: OutputFormat) {
+    *OUTPUT_FORMAT_CACHE.write().unwrap() = format;
+}
+
+/// Reads the sender alerts are sent through, if alerts were started.
+///
+/// # Returns
+///
+/// An `Option` containing the sender, or `None` if no alert webhooks are configured.
+pub fn read_alert_cache() -> Option<mpsc::UnboundedSender<AlertMessage>> {
+    ALERT_CACHE.read().unwrap().clone()
+}
+
+/// Sets the sender alerts are sent through.
+///
+/// # Arguments
+///
+/// * `sender` - The sender of the task sending the alerts.
+pub fn set_alert_cache(sender: mpsc::UnboundedSender<AlertMessage>) {
+    *ALERT_CACHE.write().unwrap() = Some(sender);
+}
+
+/// Reads the database verified publications are exported to, if one is set.
+///
+/// # Returns
+///
+/// An `Option` containing the export, or `None` if publications are not exported.
+pub fn read_publication_export_cache() -> Option<PublicationExport> {
+    PUBLICATION_EXPORT_CACHE.read().unwrap().clone()
+}
+
+/// Sets the database verified publications are exported to.
+///
+/// # Arguments
+///
+/// * `export` - The export database.
+pub fn set_publication_export_cache(export: PublicationExport) {
+    *PUBLICATION_EXPORT_CACHE.write().unwrap() = Some(export);
+}
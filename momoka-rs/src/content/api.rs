@@ -0,0 +1,82 @@
+use crate::http::{get_bytes_with_timeout, HttpError};
+
+/// The default IPFS gateway used to resolve `ipfs://` content URIs.
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io";
+
+/// The gateways content URIs are resolved through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentGateways {
+    /// The base URL of the Arweave gateway `ar://` URIs are resolved through.
+    pub arweave: String,
+    /// The base URL of the IPFS gateway `ipfs://` URIs are resolved through.
+    pub ipfs: String,
+}
+
+/// Resolves a content URI to the URL it can be fetched from.
+///
+/// # Arguments
+///
+/// * `content_uri` - The content URI in the typed data.
+/// * `gateways` - The gateways to resolve `ar://` and `ipfs://` URIs through.
+///
+/// # Returns
+///
+/// The URL to fetch the content from, or `None` if the URI scheme is not supported.
+pub fn resolve_content_uri(content_uri: &str, gateways: &ContentGateways) -> Option<String> {
+    if let Some(id) = content_uri.strip_prefix("ar://") {
+        Some(format!("{}/{}", gateways.arweave.trim_end_matches('/'), id))
+    } else if let Some(path) = content_uri.strip_prefix("ipfs://") {
+        Some(format!(
+            "{}/ipfs/{}",
+            gateways.ipfs.trim_end_matches('/'),
+            path.trim_start_matches("ipfs/")
+        ))
+    } else if content_uri.starts_with("https://") {
+        Some(content_uri.to_string())
+    } else {
+        None
+    }
+}
+
+/// Retrieves the content at a resolved content URL.
+///
+/// # Arguments
+///
+/// * `url` - The URL the content URI resolved to.
+///
+/// # Returns
+///
+/// A `Result` containing the raw content, or the error if the gateway could not serve it.
+pub async fn get_content_api(url: &str) -> Result<Vec<u8>, HttpError> {
+    get_bytes_with_timeout(url).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_content_uri() {
+        let gateways = ContentGateways {
+            arweave: "https://arweave.net/".to_string(),
+            ipfs: "https://ipfs.io".to_string(),
+        };
+
+        assert_eq!(
+            resolve_content_uri("ar://abc", &gateways),
+            Some("https://arweave.net/abc".to_string())
+        );
+        assert_eq!(
+            resolve_content_uri("ipfs://Qm123/metadata.json", &gateways),
+            Some("https://ipfs.io/ipfs/Qm123/metadata.json".to_string())
+        );
+        assert_eq!(
+            resolve_content_uri("https://example.com/1.json", &gateways),
+            Some("https://example.com/1.json".to_string())
+        );
+        assert_eq!(
+            resolve_content_uri("http://example.com/1.json", &gateways),
+            None
+        );
+    }
+}
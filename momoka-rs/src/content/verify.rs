@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::types::{content::ContentStatus, transaction::MomokaTxId};
+
+use super::api::{get_content_api, resolve_content_uri, ContentGateways};
+
+/// The Lens publication metadata schema versions content is validated against.
+const METADATA_VERSION_1: &str = "1.0.0";
+const METADATA_VERSION_2: &str = "2.0.0";
+
+/// The values `mainContentFocus` can take in version 2 metadata.
+const MAIN_CONTENT_FOCUSES: [&str; 7] = [
+    "VIDEO",
+    "IMAGE",
+    "ARTICLE",
+    "TEXT_ONLY",
+    "AUDIO",
+    "LINK",
+    "EMBED",
+];
+
+/// The values `contentWarning` can take in version 2 metadata.
+const CONTENT_WARNINGS: [&str; 3] = ["NSFW", "SENSITIVE", "SPOILER"];
+
+/// The values `displayType` can take on a metadata attribute.
+const DISPLAY_TYPES: [&str; 3] = ["number", "string", "date"];
+
+/// Checks a field of a metadata object is a string.
+///
+/// # Arguments
+///
+/// * `object` - The metadata object holding the field.
+/// * `field` - The name of the field.
+/// * `required` - Whether the field has to be set, optional fields can be missing or null.
+///
+/// # Returns
+///
+/// The string if it is set, or the reason the field is invalid.
+fn check_string<'a>(
+    object: &'a Map<String, Value>,
+    field: &str,
+    required: bool,
+) -> Result<Option<&'a str>, String> {
+    match object.get(field) {
+        Some(Value::String(value)) => Ok(Some(value)),
+        None | Some(Value::Null) if !required => Ok(None),
+        None | Some(Value::Null) => Err(format!("{} is required", field)),
+        Some(_) => Err(format!("{} must be a string", field)),
+    }
+}
+
+/// Checks a field of a metadata object is one of the allowed values.
+///
+/// # Arguments
+///
+/// * `object` - The metadata object holding the field.
+/// * `field` - The name of the field.
+/// * `allowed` - The values the field can take.
+/// * `required` - Whether the field has to be set.
+///
+/// # Returns
+///
+/// The value if it is set, or the reason the field is invalid.
+fn check_one_of<'a>(
+    object: &'a Map<String, Value>,
+    field: &str,
+    allowed: &[&str],
+    required: bool,
+) -> Result<Option<&'a str>, String> {
+    match check_string(object, field, required)? {
+        Some(value) if !allowed.contains(&value) => Err(format!(
+            "{} must be one of {:?} but is {}",
+            field, allowed, value
+        )),
+        value => Ok(value),
+    }
+}
+
+/// Checks a field of a metadata object is an array of objects, each checked with `check_item`.
+///
+/// # Arguments
+///
+/// * `object` - The metadata object holding the field.
+/// * `field` - The name of the field.
+/// * `required` - Whether the field has to be set.
+/// * `check_item` - The check to run on each item.
+///
+/// # Returns
+///
+/// The amount of items, or the reason the field is invalid.
+fn check_array(
+    object: &Map<String, Value>,
+    field: &str,
+    required: bool,
+    check_item: impl Fn(&Map<String, Value>) -> Result<(), String>,
+) -> Result<usize, String> {
+    let items = match object.get(field) {
+        Some(Value::Array(items)) => items,
+        None | Some(Value::Null) if !required => return Ok(0),
+        None | Some(Value::Null) => return Err(format!("{} is required", field)),
+        Some(_) => return Err(format!("{} must be an array", field)),
+    };
+
+    for (index, item) in items.iter().enumerate() {
+        let item = item
+            .as_object()
+            .ok_or(format!("{}[{}] must be an object", field, index))?;
+        check_item(item).map_err(|reason| format!("{}[{}].{}", field, index, reason))?;
+    }
+
+    Ok(items.len())
+}
+
+/// Validates content against the Lens publication metadata schema of the version it declares.
+///
+/// # Arguments
+///
+/// * `metadata` - The parsed content.
+///
+/// # Returns
+///
+/// The metadata version the content is valid against, or the reason it is not valid metadata.
+pub fn validate_metadata(metadata: &Value) -> Result<String, String> {
+    let metadata = metadata
+        .as_object()
+        .ok_or("metadata must be a JSON object".to_string())?;
+
+    let version = check_string(metadata, "version", true)?.unwrap_or_default();
+    if version != METADATA_VERSION_1 && version != METADATA_VERSION_2 {
+        return Err(format!("unknown metadata version {}", version));
+    }
+
+    check_string(metadata, "metadata_id", true)?;
+    for field in [
+        "description",
+        "external_url",
+        "image",
+        "imageMimeType",
+        "animation_url",
+        "appId",
+    ] {
+        check_string(metadata, field, false)?;
+    }
+    let content = check_string(metadata, "content", false)?;
+
+    check_array(metadata, "attributes", true, |attribute| {
+        check_one_of(attribute, "displayType", &DISPLAY_TYPES, false)?;
+        check_string(attribute, "traitType", false)?;
+        match attribute.get("value") {
+            None | Some(Value::Null) => Err("value is required".to_string()),
+            Some(_) => Ok(()),
+        }
+    })?;
+    let media = check_array(metadata, "media", false, |media| {
+        check_string(media, "item", true)?;
+        check_string(media, "type", false)?;
+        check_string(media, "altTag", false)?;
+        check_string(media, "cover", false)?;
+        Ok(())
+    })?;
+
+    if version == METADATA_VERSION_1 {
+        check_string(metadata, "name", true)?;
+        return Ok(version.to_string());
+    }
+
+    check_string(metadata, "name", false)?;
+    check_string(metadata, "locale", true)?;
+    check_one_of(metadata, "contentWarning", &CONTENT_WARNINGS, false)?;
+    match metadata.get("tags") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(tags)) if tags.iter().all(Value::is_string) => {}
+        Some(_) => return Err("tags must be an array of strings".to_string()),
+    }
+
+    match check_one_of(metadata, "mainContentFocus", &MAIN_CONTENT_FOCUSES, true)? {
+        Some(focus @ ("TEXT_ONLY" | "ARTICLE" | "LINK"))
+            if content.unwrap_or_default().is_empty() =>
+        {
+            Err(format!("content is required for {}", focus))
+        }
+        Some(focus @ ("IMAGE" | "VIDEO" | "AUDIO")) if media == 0 => {
+            Err(format!("media is required for {}", focus))
+        }
+        _ => Ok(version.to_string()),
+    }
+}
+
+/// Resolves the content a publication points to and validates it is Lens publication metadata.
+///
+/// # Arguments
+///
+/// * `content_uri` - The content URI in the typed data.
+/// * `gateways` - The gateways to resolve `ar://` and `ipfs://` URIs through.
+///
+/// # Returns
+///
+/// The status of the content.
+pub async fn verify_content(content_uri: &str, gateways: &ContentGateways) -> ContentStatus {
+    let url = match resolve_content_uri(content_uri, gateways) {
+        Some(url) => url,
+        None => {
+            return ContentStatus::UnsupportedUri {
+                uri: content_uri.to_string(),
+            }
+        }
+    };
+
+    let content = match get_content_api(&url).await {
+        Ok(content) => content,
+        Err(err) => {
+            return ContentStatus::Unavailable {
+                reason: err.to_string(),
+            }
+        }
+    };
+
+    let metadata: Value = match serde_json::from_slice(&content) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            return ContentStatus::InvalidMetadata {
+                reason: format!("content is not JSON - {}", err),
+            }
+        }
+    };
+
+    match validate_metadata(&metadata) {
+        Ok(version) => ContentStatus::Valid { version },
+        Err(reason) => ContentStatus::InvalidMetadata { reason },
+    }
+}
+
+/// Resolves and validates the content of many publications at once.
+///
+/// # Arguments
+///
+/// * `content_uris` - The transaction ids mapped to the content URI in their typed data.
+/// * `gateways` - The gateways to resolve `ar://` and `ipfs://` URIs through.
+///
+/// # Returns
+///
+/// A map of the transaction ids to the status of their content.
+pub async fn verify_contents(
+    content_uris: &HashMap<MomokaTxId, String>,
+    gateways: &ContentGateways,
+) -> HashMap<MomokaTxId, ContentStatus> {
+    let futures = content_uris.iter().map(|(tx_id, content_uri)| async move {
+        (tx_id.clone(), verify_content(content_uri, gateways).await)
+    });
+
+    futures::future::join_all(futures)
+        .await
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::stand_in;
+
+    /// Serves the given paths from a local stand-in for a gateway, anything else is a 404.
+    fn serve_gateway(routes: Vec<(&'static str, &'static str)>) -> String {
+        stand_in::serve(move |request| {
+            routes
+                .iter()
+                .find(|(route, _)| *route == request.path)
+                .map(|(_, body)| body.to_string())
+        })
+    }
+
+    fn metadata_v2() -> Value {
+        json!({
+            "version": "2.0.0",
+            "metadata_id": "6f7c2bd1-5b4e-4b2a-9b3e-3c1d0c3f4d1e",
+            "content": "gm",
+            "name": "Post by @momoka.lens",
+            "attributes": [{ "traitType": "type", "value": "post" }],
+            "media": [],
+            "locale": "en",
+            "mainContentFocus": "TEXT_ONLY",
+            "appId": "momoka"
+        })
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        assert_eq!(validate_metadata(&metadata_v2()), Ok("2.0.0".to_string()));
+
+        let mut metadata = metadata_v2();
+        metadata["version"] = json!("1.0.0");
+        assert_eq!(validate_metadata(&metadata), Ok("1.0.0".to_string()));
+
+        metadata["name"] = Value::Null;
+        assert_eq!(
+            validate_metadata(&metadata),
+            Err("name is required".to_string())
+        );
+
+        let mut metadata = metadata_v2();
+        metadata["mainContentFocus"] = json!("IMAGE");
+        assert_eq!(
+            validate_metadata(&metadata),
+            Err("media is required for IMAGE".to_string())
+        );
+
+        let mut metadata = metadata_v2();
+        metadata["attributes"] = json!([{ "traitType": "type" }]);
+        assert_eq!(
+            validate_metadata(&metadata),
+            Err("attributes[0].value is required".to_string())
+        );
+
+        let mut metadata = metadata_v2();
+        metadata["version"] = json!("3.0.0");
+        assert_eq!(
+            validate_metadata(&metadata),
+            Err("unknown metadata version 3.0.0".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_content_through_gateways() {
+        let valid = Box::leak(metadata_v2().to_string().into_boxed_str());
+        let gateway = serve_gateway(vec![
+            ("/valid", valid),
+            ("/ipfs/Qm123/metadata.json", "{\"version\":\"2.0.0\"}"),
+        ]);
+        let gateways = ContentGateways {
+            arweave: gateway.clone(),
+            ipfs: gateway,
+        };
+
+        assert_eq!(
+            verify_content("ar://valid", &gateways).await,
+            ContentStatus::Valid {
+                version: "2.0.0".to_string()
+            }
+        );
+        assert_eq!(
+            verify_content("ipfs://Qm123/metadata.json", &gateways).await,
+            ContentStatus::InvalidMetadata {
+                reason: "metadata_id is required".to_string()
+            }
+        );
+        assert!(matches!(
+            verify_content("ar://missing", &gateways).await,
+            ContentStatus::Unavailable { .. }
+        ));
+        assert_eq!(
+            verify_content("lens://valid", &gateways).await,
+            ContentStatus::UnsupportedUri {
+                uri: "lens://valid".to_string()
+            }
+        );
+    }
+}
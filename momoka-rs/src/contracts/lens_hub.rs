@@ -3,11 +3,12 @@ use std::{fs, sync::Arc};
 use ethers::{
     abi::Abi,
     prelude::{abigen, Contract, Multicall},
-    providers::{Http, Provider, RetryClient},
+    providers::{Provider, RetryClient},
     types::{Address, BlockNumber, U256},
 };
 
-use crate::types::{profile_id::ProfileId, verifier_error::MomokaVerifierError};
+use crate::transport::NodeTransport;
+use crate::types::verifier_error::{ErrorSource, MomokaVerifierError};
 
 abigen!(
     ILensHub,
@@ -15,6 +16,17 @@ abigen!(
     event_derives(serde::Deserialize, serde::Serialize)
 );
 
+// The publication events are emitted by the LensHub from the `Events` library so they are not in the hub abi
+abigen!(
+    LensHubEvents,
+    r#"[
+        event PostCreated(uint256 indexed profileId, uint256 indexed pubId, string contentURI, address collectModule, bytes collectModuleReturnData, address referenceModule, bytes referenceModuleReturnData, uint256 timestamp)
+        event CommentCreated(uint256 indexed profileId, uint256 indexed pubId, string contentURI, uint256 profileIdPointed, uint256 pubIdPointed, bytes referenceModuleData, address collectModule, bytes collectModuleReturnData, address referenceModule, bytes referenceModuleReturnData, uint256 timestamp)
+        event MirrorCreated(uint256 indexed profileId, uint256 indexed pubId, uint256 profileIdPointed, uint256 pubIdPointed, bytes referenceModuleData, address referenceModule, bytes referenceModuleReturnData, uint256 timestamp)
+    ]"#,
+    event_derives(serde::Deserialize, serde::Serialize)
+);
+
 /// Returns a new instance of `ILensHub`, representing the Lens Protocol Hub contract
 /// deployed on the Polygon (Matic) mainnet.
 /// # Arguments
@@ -30,18 +42,36 @@ abigen!(
 pub fn lens_hub_contract(
     lens_hub: Address,
     // provider: &RetryClient<Http>,
-    provider: &Provider<RetryClient<Http>>,
-) -> ILensHub<&Provider<RetryClient<Http>>> {
+    provider: &Provider<RetryClient<NodeTransport>>,
+) -> ILensHub<&Provider<RetryClient<NodeTransport>>> {
     // Create a new client from the provider
     let client = Arc::new(provider);
 
     // Create a new instance of the ILensHub contract using the address and client
-    let contract: ILensHub<&Provider<RetryClient<Http>>> = ILensHub::new(lens_hub, client);
+    let contract: ILensHub<&Provider<RetryClient<NodeTransport>>> = ILensHub::new(lens_hub, client);
 
     contract
 }
 
+/// What the profile details of a publication are read for.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize, serde::Serialize,
+)]
+pub struct ProfileDetailsKey {
+    /// The profile which made the publication.
+    pub profile_id: U256,
+    /// The address which signed the typed data.
+    pub signer: Address,
+    /// The block the publication was made against.
+    pub block_number: u64,
+    /// The collect module of the publication, if it sets one.
+    pub collect_module: Option<Address>,
+    /// The reference module of the publication, if it sets one.
+    pub reference_module: Option<Address>,
+}
+
 /// Represents the details of a lens profile.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct LensProfileDetails {
     /// The signature nonce of the lens profile.
     pub sig_nonce: U256,
@@ -51,14 +81,19 @@ pub struct LensProfileDetails {
     pub dispatcher_address: Address,
     /// The owner of the address.
     pub owner_of_address: Address,
+    /// Whether the collect module was whitelisted on the LensHub, true if the publication sets none.
+    pub collect_module_whitelisted: bool,
+    /// Whether the reference module was whitelisted on the LensHub, true if the publication sets none.
+    pub reference_module_whitelisted: bool,
 }
 
-/// Gets the details of a Lens profile, including the current publication ID and the owner of the address.
+/// Gets the details of a Lens profile, including the current publication ID and the owner of the address,
+/// and whether the modules of the publication were whitelisted.
 ///
 /// # Arguments
 ///
-/// * `profile_id` - The ID of the Lens profile in hexadecimal format.
-/// * `signed_by_address` - The address of the signer.
+/// * `lens_hub` - The address of the LensHub.
+/// * `key` - The profile, signer, block and modules to read the details for.
 /// * `provider` - The ethers provider
 ///
 /// # Errors
@@ -72,66 +107,104 @@ pub struct LensProfileDetails {
 /// use std::str::FromStr;
 /// use std::sync::Arc;
 ///
-/// let profile_id = "0x01";
-/// let signed_by_address = Address::from_str("0x1234...").unwrap();
+/// let key = ProfileDetailsKey {
+///     profile_id: U256::from(1),
+///     signer: Address::from_str("0x1234...").unwrap(),
+///     block_number: 42_000_000,
+///     collect_module: None,
+///     reference_module: None,
+/// };
 ///
-/// let details = get_profile_details(&profile_id, &signed_by_address).await.unwrap();
+/// let details = get_profile_details(lens_hub, &key, &provider).await.unwrap();
 /// assert_eq!(details.sig_nonce, U256::from(42));
 /// assert_eq!(details.current_publication_id, U256::from(1234));
 /// ```
 pub async fn get_profile_details(
     lens_hub: Address,
-    profile_id: &ProfileId,
-    signed_by_address: Address,
-    block_number: u64,
-    provider: &Provider<RetryClient<Http>>,
+    key: &ProfileDetailsKey,
+    provider: &Provider<RetryClient<NodeTransport>>,
 ) -> Result<LensProfileDetails, MomokaVerifierError> {
     let abi: Abi = serde_json::from_str(
         &fs::read_to_string("./src/abi/lens_hub_contract_abi.json")
-            .map_err(|_| MomokaVerifierError::SimulationNodeCouldNotRun)?,
+            .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun { source: e.into() })?,
     )
-    .map_err(|_| MomokaVerifierError::SimulationNodeCouldNotRun)?;
+    .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun { source: e.into() })?;
 
     let provider = Arc::new(&(provider));
 
     let contract = Contract::new(lens_hub, abi, provider.clone());
 
-    let profile_id = <&ProfileId as Into<U256>>::into(profile_id);
+    let profile_id = key.profile_id;
 
     let sig_nonce_call = contract
-        .method::<_, U256>("sigNonces", (signed_by_address,))
-        .map_err(|_| MomokaVerifierError::SimulationNodeCouldNotRun)?;
+        .method::<_, U256>("sigNonces", (key.signer,))
+        .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun {
+            source: ErrorSource::message(e),
+        })?;
     let get_pub_count = contract
         .method::<_, U256>("getPubCount", profile_id)
-        .map_err(|_| MomokaVerifierError::SimulationNodeCouldNotRun)?;
+        .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun {
+            source: ErrorSource::message(e),
+        })?;
     let get_dispatcher = contract
         .method::<_, Address>("getDispatcher", profile_id)
-        .map_err(|_| MomokaVerifierError::SimulationNodeCouldNotRun)?;
+        .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun {
+            source: ErrorSource::message(e),
+        })?;
 
     let owner_of = contract
         .method::<_, Address>("ownerOf", profile_id)
-        .map_err(|_| MomokaVerifierError::SimulationNodeCouldNotRun)?;
+        .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun {
+            source: ErrorSource::message(e),
+        })?;
+
+    // the zero address is never whitelisted, its result is ignored when the publication sets no module
+    let collect_module_whitelisted = contract
+        .method::<_, bool>(
+            "isCollectModuleWhitelisted",
+            key.collect_module.unwrap_or_default(),
+        )
+        .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun {
+            source: ErrorSource::message(e),
+        })?;
+    let reference_module_whitelisted = contract
+        .method::<_, bool>(
+            "isReferenceModuleWhitelisted",
+            key.reference_module.unwrap_or_default(),
+        )
+        .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun {
+            source: ErrorSource::message(e),
+        })?;
 
     let mut multicall = Multicall::new(provider.clone(), None)
         .await
-        .map_err(|_| MomokaVerifierError::SimulationNodeCouldNotRun)?
-        .block(BlockNumber::from(block_number));
+        .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun {
+            source: ErrorSource::message(e),
+        })?
+        .block(BlockNumber::from(key.block_number));
 
     multicall
         .add_call(sig_nonce_call, false)
         .add_call(get_pub_count, false)
         .add_call(get_dispatcher, false)
-        .add_call(owner_of, false);
-
-    let return_data: (U256, U256, Address, Address) = multicall
-        .call()
-        .await
-        .map_err(|_| MomokaVerifierError::SimulationNodeCouldNotRun)?;
+        .add_call(owner_of, false)
+        .add_call(collect_module_whitelisted, false)
+        .add_call(reference_module_whitelisted, false);
+
+    let return_data: (U256, U256, Address, Address, bool, bool) =
+        multicall
+            .call()
+            .await
+            .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun {
+                source: ErrorSource::message(e),
+            })?;
 
     Ok(LensProfileDetails {
         sig_nonce: return_data.0,
         current_publication_id: return_data.1,
         dispatcher_address: return_data.2,
         owner_of_address: return_data.3,
+        collect_module_whitelisted: key.collect_module.is_none() || return_data.4,
+        reference_module_whitelisted: key.reference_module.is_none() || return_data.5,
     })
 }
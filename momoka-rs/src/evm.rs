@@ -1,16 +1,35 @@
-use std::{str::FromStr, time::Duration};
+use std::{
+    fmt::{Display, Formatter},
+    future::Future,
+    time::Duration,
+};
 
 use ethers::{
-    providers::{Http, HttpRateLimitRetryPolicy, Provider, RetryClient, RetryClientBuilder},
+    abi::AbiEncode,
+    contract::MULTICALL_ADDRESS,
+    providers::{HttpRateLimitRetryPolicy, Middleware, Provider, RetryClient, RetryClientBuilder},
+    types::{Address, BlockId, BlockNumber, TransactionRequest},
     utils::hex,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    environment::{Deployment, Environment},
-    types::verifier_error::MomokaVerifierError,
+    content::api::ContentGateways,
+    contracts::lens_hub::SigNoncesCall,
+    environment::{
+        environment_to_chain_id, environment_to_lens_hub_contract, Deployment, Environment,
+    },
+    transport::NodeTransport,
+    types::{
+        transaction_filter::TransactionFilter,
+        verifier_error::{ErrorSource, MomokaVerifierError},
+    },
 };
 
+/// How many blocks behind the head the node is asked to run a historical `eth_call`, far
+/// past what a pruned node keeps so only an archive node can answer.
+const PROBE_HISTORICAL_BLOCK_DEPTH: u64 = 100_000;
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct SigRequest {
     pub v: u8,
@@ -49,7 +68,8 @@ pub struct SigRequest {
 /// }
 /// ```
 pub fn parse_signature(signature: &str, deadline: u64) -> Result<SigRequest, MomokaVerifierError> {
-    let bytes = hex::decode(&signature[2..]).map_err(|_| MomokaVerifierError::SimulationFailed)?;
+    let bytes = hex::decode(&signature[2..])
+        .map_err(|e| MomokaVerifierError::SimulationFailed { source: e.into() })?;
     let v = bytes[64];
     let mut r_bytes = [0u8; 32];
     r_bytes.copy_from_slice(&bytes[..32]);
@@ -69,10 +89,30 @@ pub fn parse_signature(signature: &str, deadline: u64) -> Result<SigRequest, Mom
 pub struct ProviderContext {
     /// The environment configuration.
     pub environment: Environment,
-    /// The node provider with retry capabilities.
-    pub node: Provider<RetryClient<Http>>,
+    /// The node provider with retry capabilities, this is the first of the nodes.
+    pub node: Provider<RetryClient<NodeTransport>>,
+    /// All the node providers, reads which decide a verdict are sent to each of them.
+    pub nodes: Vec<Provider<RetryClient<NodeTransport>>>,
+    /// The URLs of the nodes in the same order, used to send JSON-RPC batches.
+    pub node_urls: Vec<String>,
+    /// How many of the nodes have to agree on a read for it to be trusted.
+    pub quorum: usize,
     /// The deployment details.
     pub deployment: Deployment,
+    /// The Arweave gateway used to check DA transactions settled before their deadline height, if enabled.
+    pub arweave_gateway: Option<String>,
+    /// Whether verified DA publications are watched for their typed data signature also being executed on-chain.
+    pub detect_double_publications: bool,
+    /// The directory state snapshots are cached in when simulations run in the local EVM, if enabled.
+    pub local_simulation: Option<String>,
+    /// The gateways publication content is resolved through to check it is valid metadata, if enabled.
+    pub content_gateways: Option<ContentGateways>,
+    /// The filter transactions have to pass to be verified when following new transactions, if any.
+    pub transaction_filter: Option<TransactionFilter>,
+    /// Whether the nodes must not be used, chain reads are only answered from a recorded chain state.
+    pub offline: bool,
+    /// Whether publications are indexed as unverified as soon as they are fetched, and upgraded once their proofs are checked.
+    pub trusted_indexing: bool,
 }
 
 /// Creates an EVM provider using the provided node URL.
@@ -84,15 +124,278 @@ pub struct ProviderContext {
 /// # Returns
 ///
 /// A provider instance with retry capabilities for interacting with the EVM.
-pub fn evm_provider(node_url: &str) -> Provider<RetryClient<Http>> {
+pub fn evm_provider(node_url: &str) -> Provider<RetryClient<NodeTransport>> {
     Provider::new(
         RetryClientBuilder::default()
             .rate_limit_retries(10)
             .timeout_retries(10)
             .initial_backoff(Duration::from_millis(500))
             .build(
-                Http::from_str(node_url).unwrap(),
+                NodeTransport::new(node_url),
                 Box::<HttpRateLimitRetryPolicy>::default(),
             ),
     )
 }
+
+/// A reason the node can not be used to verify publications.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeCapabilityError {
+    /// The node did not answer.
+    Unreachable,
+    /// The node is on a different chain to the environment.
+    WrongChain { expected: u64, actual: u64 },
+    /// The node could not run an `eth_call` at an old block.
+    NotArchive { block_number: u64 },
+    /// Multicall3 has no code on the node chain.
+    MulticallNotDeployed,
+    /// The LensHub of the environment has no code on the node chain.
+    LensHubNotDeployed { lens_hub: Address },
+}
+
+impl Display for NodeCapabilityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeCapabilityError::Unreachable => write!(f, "the node could not be reached"),
+            NodeCapabilityError::WrongChain { expected, actual } => write!(
+                f,
+                "the node is on chain {} but the environment is on chain {}, check the -e and -n values match",
+                actual, expected
+            ),
+            NodeCapabilityError::NotArchive { block_number } => write!(
+                f,
+                "the node could not run an eth_call at block {}, an archive node is needed to simulate publications",
+                block_number
+            ),
+            NodeCapabilityError::MulticallNotDeployed => write!(
+                f,
+                "Multicall3 is not deployed at {:?} on the node chain",
+                MULTICALL_ADDRESS
+            ),
+            NodeCapabilityError::LensHubNotDeployed { lens_hub } => write!(
+                f,
+                "the LensHub {:?} is not deployed on the node chain",
+                lens_hub
+            ),
+        }
+    }
+}
+
+/// Checks the node can answer everything the verifier needs from it.
+///
+/// This checks the node is on the chain of the environment, can run an `eth_call` at an old
+/// block (so it is an archive node), and that Multicall3 and the LensHub are deployed.
+///
+/// # Arguments
+///
+/// * `provider` - The node provider.
+/// * `environment` - The environment the node should be on.
+///
+/// # Returns
+///
+/// A `Result` which is the first capability the node is missing if it fails.
+pub async fn probe_node(
+    provider: &Provider<RetryClient<NodeTransport>>,
+    environment: &Environment,
+) -> Result<(), NodeCapabilityError> {
+    let expected = environment_to_chain_id(environment.clone()).unwrap() as u64;
+    let actual = provider
+        .get_chainid()
+        .await
+        .map_err(|_| NodeCapabilityError::Unreachable)?
+        .as_u64();
+    if actual != expected {
+        return Err(NodeCapabilityError::WrongChain { expected, actual });
+    }
+
+    let lens_hub = environment_to_lens_hub_contract(environment).unwrap();
+
+    let code = provider
+        .get_code(MULTICALL_ADDRESS, None)
+        .await
+        .map_err(|_| NodeCapabilityError::Unreachable)?;
+    if code.is_empty() {
+        return Err(NodeCapabilityError::MulticallNotDeployed);
+    }
+
+    let code = provider
+        .get_code(lens_hub, None)
+        .await
+        .map_err(|_| NodeCapabilityError::Unreachable)?;
+    if code.is_empty() {
+        return Err(NodeCapabilityError::LensHubNotDeployed { lens_hub });
+    }
+
+    let latest_block_number = provider
+        .get_block_number()
+        .await
+        .map_err(|_| NodeCapabilityError::Unreachable)?
+        .as_u64();
+    let block_number = latest_block_number.saturating_sub(PROBE_HISTORICAL_BLOCK_DEPTH);
+
+    let call = TransactionRequest::new()
+        .to(lens_hub)
+        .data(SigNoncesCall(Address::zero()).encode());
+    provider
+        .call(
+            &call.into(),
+            Some(BlockId::Number(BlockNumber::Number(block_number.into()))),
+        )
+        .await
+        .map_err(|_| NodeCapabilityError::NotArchive { block_number })?;
+
+    Ok(())
+}
+
+/// Returns true if the error means the node could not answer, rather than being an answer itself.
+fn is_node_failure(error: &MomokaVerifierError) -> bool {
+    matches!(
+        error,
+        MomokaVerifierError::BlockCantBeReadFromNode { .. }
+            | MomokaVerifierError::DataCantBeReadFromNode { .. }
+            | MomokaVerifierError::SimulationNodeCouldNotRun { .. }
+    )
+}
+
+/// Picks the outcome which at least `quorum` of the nodes returned.
+///
+/// # Arguments
+///
+/// * `outcomes` - The outcome of the read from each node.
+/// * `quorum` - How many nodes have to return the same outcome.
+///
+/// # Returns
+///
+/// The agreed outcome. If no outcome reaches the quorum, `NodesDisagree` is returned when the
+/// nodes which answered gave different answers, otherwise the failure of the nodes which could not answer.
+pub fn quorum_outcome<T: PartialEq>(
+    outcomes: Vec<Result<T, MomokaVerifierError>>,
+    quorum: usize,
+) -> Result<T, MomokaVerifierError> {
+    let mut agreed: Vec<(Result<T, MomokaVerifierError>, usize)> = Vec::new();
+    for outcome in outcomes {
+        match agreed.iter_mut().find(|(seen, _)| seen == &outcome) {
+            Some((_, count)) => *count += 1,
+            None => agreed.push((outcome, 1)),
+        }
+    }
+
+    let answers = agreed
+        .iter()
+        .filter(|(outcome, _)| !matches!(outcome, Err(error) if is_node_failure(error)))
+        .count();
+
+    match agreed.iter().position(|(_, count)| *count >= quorum) {
+        Some(index) => agreed.swap_remove(index).0,
+        None if answers > 1 => Err(MomokaVerifierError::NodesDisagree),
+        None => agreed
+            .into_iter()
+            .map(|(outcome, _)| outcome)
+            .find(|outcome| matches!(outcome, Err(error) if is_node_failure(error)))
+            .unwrap_or(Err(MomokaVerifierError::NodesDisagree)),
+    }
+}
+
+/// Runs the same read against every node and returns the outcome a quorum of them agree on.
+///
+/// Both successful reads and verdict errors (for example `SimulationFailed`) count as outcomes,
+/// so a single lagging or misbehaving node can not decide the verdict on its own.
+///
+/// # Arguments
+///
+/// * `provider_context` - The provider context holding the nodes and quorum.
+/// * `read` - The read to run against each node.
+///
+/// # Returns
+///
+/// The outcome at least `quorum` nodes agreed on, or `NodesDisagree` if the nodes gave different answers.
+/// When offline nothing is read and `DataCantBeReadFromNode` is returned.
+pub async fn quorum_read<'a, T, F, Fut>(
+    provider_context: &'a ProviderContext,
+    read: F,
+) -> Result<T, MomokaVerifierError>
+where
+    T: PartialEq,
+    F: Fn(&'a Provider<RetryClient<NodeTransport>>) -> Fut,
+    Fut: Future<Output = Result<T, MomokaVerifierError>>,
+{
+    if provider_context.offline {
+        return Err(MomokaVerifierError::DataCantBeReadFromNode {
+            source: ErrorSource::message("the read is not in the recorded chain state"),
+        });
+    }
+
+    let outcomes = futures::future::join_all(provider_context.nodes.iter().map(read)).await;
+
+    quorum_outcome(outcomes, provider_context.quorum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quorum_outcome_agreement() {
+        assert_eq!(quorum_outcome(vec![Ok(1), Ok(1), Ok(2)], 2), Ok(1));
+        assert_eq!(
+            quorum_outcome(
+                vec![
+                    Err(MomokaVerifierError::SimulationFailed {
+                        source: ErrorSource::default(),
+                    }),
+                    Err(MomokaVerifierError::SimulationFailed {
+                        source: ErrorSource::default(),
+                    }),
+                    Ok(2)
+                ],
+                2
+            ),
+            Err(MomokaVerifierError::SimulationFailed {
+                source: ErrorSource::default(),
+            })
+        );
+        assert_eq!(
+            quorum_outcome(
+                vec![
+                    Ok(1),
+                    Err(MomokaVerifierError::BlockCantBeReadFromNode {
+                        source: ErrorSource::default(),
+                    }),
+                    Ok(1)
+                ],
+                2
+            ),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn test_quorum_outcome_without_quorum() {
+        assert_eq!(
+            quorum_outcome(
+                vec![
+                    Ok(1),
+                    Err(MomokaVerifierError::NotClosestBlock {
+                        chosen: Some(1),
+                        closest: Some(2)
+                    })
+                ],
+                2
+            ),
+            Err(MomokaVerifierError::NodesDisagree)
+        );
+        assert_eq!(
+            quorum_outcome(
+                vec![
+                    Ok(1),
+                    Err(MomokaVerifierError::BlockCantBeReadFromNode {
+                        source: ErrorSource::default(),
+                    })
+                ],
+                2
+            ),
+            Err(MomokaVerifierError::BlockCantBeReadFromNode {
+                source: ErrorSource::default(),
+            })
+        );
+    }
+}
@@ -1,11 +1,63 @@
+use base64::{engine::general_purpose, Engine};
 use reqwest::Client;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::thread::sleep;
 use std::time::Duration;
 
+use crate::transport::{send_through, TransportError};
+
 const MAX_RETRIES: i32 = 5;
 
+/// The error an HTTP request failed with.
+#[derive(Debug)]
+pub enum HttpError {
+    /// The request could not be sent, or the response could not be read.
+    Request(reqwest::Error),
+    /// The response is not of the expected shape.
+    Decode(serde_json::Error),
+    /// The request is not in the recording being replayed.
+    NotRecorded,
+}
+
+impl Display for HttpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            HttpError::Request(error) => write!(f, "{}", error),
+            HttpError::Decode(error) => write!(f, "{}", error),
+            HttpError::NotRecorded => write!(f, "the request is not in the recording"),
+        }
+    }
+}
+
+impl Error for HttpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HttpError::Request(error) => Some(error),
+            HttpError::Decode(error) => Some(error),
+            HttpError::NotRecorded => None,
+        }
+    }
+}
+
+impl From<TransportError<reqwest::Error>> for HttpError {
+    fn from(error: TransportError<reqwest::Error>) -> Self {
+        match error {
+            TransportError::Send(error) => HttpError::Request(error),
+            TransportError::NotRecorded => HttpError::NotRecorded,
+        }
+    }
+}
+
+impl From<serde_json::Error> for HttpError {
+    fn from(error: serde_json::Error) -> Self {
+        HttpError::Decode(error)
+    }
+}
+
 /// Sends a POST request to the specified URL with a JSON-encoded body, and returns the response as a deserialized object.
 ///
 /// # Arguments
@@ -45,7 +97,7 @@ const MAX_RETRIES: i32 = 5;
 pub async fn post_with_timeout<TResponse, TBody>(
     url: &str,
     body: &TBody,
-) -> Result<TResponse, reqwest::Error>
+) -> Result<TResponse, HttpError>
 where
     TBody: Serialize,
     TResponse: DeserializeOwned,
@@ -56,7 +108,72 @@ where
         match post_request(url, body).await {
             Ok(response) => return Ok(response),
             Err(err) => {
-                if retries >= MAX_RETRIES {
+                if retries >= MAX_RETRIES || matches!(err, HttpError::NotRecorded) {
+                    return Err(err);
+                }
+                // sleep for 100ms and go again
+                sleep(Duration::from_millis(100));
+                retries += 1;
+            }
+        }
+    }
+}
+
+/// Sends a GET request to the specified URL, and returns the response as a deserialized object.
+///
+/// # Arguments
+///
+/// * `url` - The URL to which the request will be sent.
+///
+/// # Returns
+///
+/// The deserialized response object, if the request was successful.
+///
+/// # Errors
+///
+/// This function will return an error if the request fails after all retries, or if the response cannot be deserialized into the specified type.
+pub async fn get_with_timeout<TResponse>(url: &str) -> Result<TResponse, HttpError>
+where
+    TResponse: DeserializeOwned,
+{
+    let mut retries = 0;
+
+    loop {
+        match get_request(url).await {
+            Ok(response) => return Ok(response),
+            Err(err) => {
+                if retries >= MAX_RETRIES || matches!(err, HttpError::NotRecorded) {
+                    return Err(err);
+                }
+                // sleep for 100ms and go again
+                sleep(Duration::from_millis(100));
+                retries += 1;
+            }
+        }
+    }
+}
+
+/// Sends a GET request to the specified URL, and returns the raw body of a successful response.
+///
+/// # Arguments
+///
+/// * `url` - The URL to which the request will be sent.
+///
+/// # Returns
+///
+/// The body of the response, if the request was successful.
+///
+/// # Errors
+///
+/// This function will return an error if the request fails after all retries, or if the response status is not a success.
+pub async fn get_bytes_with_timeout(url: &str) -> Result<Vec<u8>, HttpError> {
+    let mut retries = 0;
+
+    loop {
+        match get_bytes_request(url).await {
+            Ok(response) => return Ok(response),
+            Err(err) => {
+                if retries >= MAX_RETRIES || matches!(err, HttpError::NotRecorded) {
                     return Err(err);
                 }
                 // sleep for 100ms and go again
@@ -67,6 +184,68 @@ where
     }
 }
 
+/// Performs a GET request with a timeout.
+///
+/// # Arguments
+///
+/// * `url` - The URL to send the GET request to.
+///
+/// # Returns
+///
+/// A `Result` containing the deserialized response if the request is successful, or an `Error` if an error occurs.
+async fn get_request<TResponse>(url: &str) -> Result<TResponse, HttpError>
+where
+    TResponse: serde::de::DeserializeOwned,
+{
+    let response = send_through("GET", url, Value::Null, || async {
+        let client = Client::new();
+
+        client
+            .get(url)
+            .timeout(Duration::from_millis(10000))
+            .send()
+            .await?
+            .json::<Value>()
+            .await
+    })
+    .await?;
+
+    Ok(serde_json::from_value(response)?)
+}
+
+/// Performs a GET request with a timeout, failing on a non success status.
+///
+/// # Arguments
+///
+/// * `url` - The URL to send the GET request to.
+///
+/// # Returns
+///
+/// A `Result` containing the response body if the request is successful, or an `Error` if an error occurs.
+async fn get_bytes_request(url: &str) -> Result<Vec<u8>, HttpError> {
+    // the body is recorded base64 encoded
+    let response = send_through("GET", url, Value::Null, || async {
+        let client = Client::new();
+
+        let response = client
+            .get(url)
+            .timeout(Duration::from_millis(10000))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(Value::String(
+            general_purpose::STANDARD.encode(response.bytes().await?),
+        ))
+    })
+    .await?;
+
+    let body: String = serde_json::from_value(response)?;
+    general_purpose::STANDARD
+        .decode(body)
+        .map_err(|error| HttpError::Decode(serde::de::Error::custom(error)))
+}
+
 /// Performs a POST request with a timeout and JSON body serialization.
 ///
 /// # Arguments
@@ -77,23 +256,27 @@ where
 /// # Returns
 ///
 /// A `Result` containing the deserialized response if the request is successful, or an `Error` if an error occurs.
-async fn post_request<TResponse, TBody>(
-    url: &str,
-    body: &TBody,
-) -> Result<TResponse, reqwest::Error>
+async fn post_request<TResponse, TBody>(url: &str, body: &TBody) -> Result<TResponse, HttpError>
 where
     TBody: serde::Serialize,
     TResponse: serde::de::DeserializeOwned,
 {
-    let client = Client::new();
+    let body = serde_json::to_value(body)?;
+
+    let response = send_through("POST", url, body.clone(), || async {
+        let client = Client::new();
 
-    let response = client
-        .post(url)
-        .timeout(Duration::from_millis(10000))
-        .header("Content-Type", "application/json")
-        .json(body)
-        .send()
-        .await?;
+        client
+            .post(url)
+            .timeout(Duration::from_millis(10000))
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await?
+            .json::<Value>()
+            .await
+    })
+    .await?;
 
-    response.json().await
+    Ok(serde_json::from_value(response)?)
 }
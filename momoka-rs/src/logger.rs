@@ -1,52 +1,197 @@
-use crate::utils::get_current_utc_string;
+use std::fmt;
 
-/// A logger for printing log messages in different colors.
-pub struct Logger;
+use clap::ValueEnum;
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    fmt::{
+        format::{FmtSpan, Writer},
+        FmtContext, FormatEvent, FormatFields, FormattedFields,
+    },
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
 
-#[allow(dead_code)]
-impl Logger {
-    fn print_colored_message(&self, color_code: &str, content: &str) {
-        println!(
-            "{}LENS VERIFICATION NODE - {} - {}\x1b[0m",
+use crate::{
+    output::{emit, is_jsonl_output, OutputEvent},
+    utils::get_current_utc_string,
+};
+
+/// The filter used when `RUST_LOG` is not set.
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// The format log lines are written in when the output is text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LogFormat {
+    /// A colored line per event.
+    #[default]
+    Text,
+    /// Several lines per event with its fields, spans and source location, for debugging.
+    Pretty,
+    /// A JSON object per event with its fields and spans, for a log pipeline.
+    Json,
+}
+
+/// Returns the name a level is written as, as it was before levels could be filtered.
+fn level_name(level: &Level) -> &'static str {
+    match *level {
+        Level::ERROR => "error",
+        Level::WARN => "warning",
+        Level::INFO => "info",
+        Level::DEBUG => "debug",
+        Level::TRACE => "trace",
+    }
+}
+
+/// Formats an event as a colored line, prefixed by the spans it happened in.
+struct TextFormat;
+
+impl<S, N> FormatEvent<S, N> for TextFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let color_code = match *event.metadata().level() {
+            Level::ERROR => "\x1b[31m", // Red
+            Level::WARN => "\x1b[33m",  // Yellow
+            Level::INFO => "\x1b[36m",  // Blue
+            _ => "\x1b[90m",            // Grey
+        };
+        write!(
+            writer,
+            "{}LENS VERIFICATION NODE - {} - ",
             color_code,
-            get_current_utc_string(),
-            content
-        );
+            get_current_utc_string()
+        )?;
+
+        for span in ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+        {
+            write!(writer, "{}", span.name())?;
+            if let Some(fields) = span.extensions().get::<FormattedFields<N>>() {
+                if !fields.is_empty() {
+                    write!(writer, "{{{}}}", fields)?;
+                }
+            }
+            write!(writer, ": ")?;
+        }
+
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer, "\x1b[0m")
     }
+}
+
+/// Collects the message of an event followed by its other fields.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
 
-    /// Prints an error message with the specified content in red.
-    ///
-    /// # Arguments
-    ///
-    /// * `content` - The content of the error message.
-    pub fn error(&self, content: &str) {
-        self.print_colored_message("\x1b[31m", content); // Red
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{}", value));
     }
 
-    /// Prints a warning message with the specified content in yellow.
-    ///
-    /// # Arguments
-    ///
-    /// * `content` - The content of the warning message.
-    pub fn warning(&self, content: &str) {
-        self.print_colored_message("\x1b[33m", content); // Yellow
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields += &format!(" {}={:?}", field.name(), value);
+        }
     }
+}
+
+/// Writes events as `log` events when the output is JSON lines, so it stays parseable.
+struct JsonlLogLayer;
+
+impl<S: Subscriber> Layer<S> for JsonlLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
 
-    /// Prints an informational message with the specified content in blue.
-    ///
-    /// # Arguments
-    ///
-    /// * `content` - The content of the informational message.
-    pub fn info(&self, content: &str) {
-        self.print_colored_message("\x1b[36m", content); // Blue
+        emit(OutputEvent::Log {
+            level: level_name(event.metadata().level()),
+            message: visitor.message + &visitor.fields,
+        });
     }
+}
+
+/// Installs the subscriber log events and spans are written through.
+///
+/// Events are filtered with `RUST_LOG` (for example `warn` or `momoka::verifier=debug`), only
+/// `info` and above are written if it is not set. The spans of each transaction are written as
+/// they close with the time they were busy and idle, which is at `debug`.
+///
+/// # Arguments
+///
+/// * `format` - The format log lines are written in, unless the output is JSON lines.
+pub fn init_logger(format: LogFormat) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+
+    let layer: Box<dyn Layer<Registry> + Send + Sync> = if is_jsonl_output() {
+        JsonlLogLayer.boxed()
+    } else {
+        match format {
+            LogFormat::Text => tracing_subscriber::fmt::layer()
+                .with_span_events(FmtSpan::CLOSE)
+                .event_format(TextFormat)
+                .boxed(),
+            LogFormat::Pretty => tracing_subscriber::fmt::layer()
+                .pretty()
+                .with_span_events(FmtSpan::CLOSE)
+                .boxed(),
+            LogFormat::Json => tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .with_span_events(FmtSpan::CLOSE)
+                .boxed(),
+        }
+    };
+
+    tracing_subscriber::registry()
+        .with(layer)
+        .with(filter)
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn test_message_visitor_appends_fields() {
+        struct Record(Arc<Mutex<MessageVisitor>>);
+        impl<S: Subscriber> Layer<S> for Record {
+            fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+                event.record(&mut *self.0.lock().unwrap());
+            }
+        }
+
+        let visitor = Arc::new(Mutex::new(MessageVisitor::default()));
+        let subscriber = tracing_subscriber::registry().with(Record(visitor.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(tx_id = "abc", attempts = 2, "{} - FAILED", "abc");
+        });
 
-    /// Prints a success message with the specified content in green.
-    ///
-    /// # Arguments
-    ///
-    /// * `content` - The content of the success message.
-    pub fn success(&self, content: &str) {
-        self.print_colored_message("\x1b[38;2;0;128;0m", content); // Green
+        let visitor = visitor.lock().unwrap();
+        assert_eq!(visitor.message, "abc - FAILED");
+        assert_eq!(visitor.fields, " tx_id=abc attempts=2");
     }
 }
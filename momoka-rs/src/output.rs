@@ -0,0 +1,203 @@
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use ethers::types::Address;
+use serde::Serialize;
+
+use crate::{
+    cache::read_output_format_cache,
+    types::{
+        content::ContentStatus,
+        double_publication::DoublePublication,
+        indexed_publication::IndexedPublication,
+        transaction::{MomokaTxId, TransactionAction, TransactionSummary},
+        verifier_error::MomokaVerifierError,
+    },
+    verifier::report::ProofStatus,
+};
+
+/// The format the verifier writes its output in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored log lines for reading in a terminal.
+    #[default]
+    Text,
+    /// A JSON object per line, for piping into `jq` or a log pipeline.
+    Jsonl,
+}
+
+/// The verdict on a transaction, written once it is verified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionVerified {
+    pub tx_id: MomokaTxId,
+    /// The address which submitted the transaction to bundlr, if it could be fetched.
+    pub submitter: Option<Address>,
+    pub action: Option<TransactionAction>,
+    pub profile_id: Option<String>,
+    pub publication_id: Option<String>,
+    pub block_number: Option<u64>,
+    pub result: ProofStatus,
+    /// The variant of the error which made the transaction invalid or unchecked.
+    pub error: Option<MomokaVerifierError>,
+    /// The status of the content of a valid publication, `None` if content is not checked.
+    pub content: Option<ContentStatus>,
+    /// How long the proof took to check, `None` if the transaction could not be fetched.
+    pub duration_ms: Option<u64>,
+}
+
+impl TransactionVerified {
+    /// Builds the verdict on a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_id` - The ID of the transaction.
+    /// * `transaction` - The transaction, if it could be fetched and parsed.
+    /// * `result` - The result of checking its proof.
+    /// * `content` - The status of its content, if it was checked.
+    /// * `duration` - How long the proof took to check.
+    pub fn new(
+        tx_id: &MomokaTxId,
+        transaction: Option<&TransactionSummary>,
+        result: &Result<(), MomokaVerifierError>,
+        content: Option<&ContentStatus>,
+        duration: Option<Duration>,
+    ) -> Self {
+        let momoka_tx = transaction.map(|transaction| &transaction.momoka_tx);
+
+        TransactionVerified {
+            tx_id: tx_id.clone(),
+            submitter: transaction.map(|transaction| transaction.submitter),
+            action: momoka_tx.and_then(|tx| tx.transaction_type().ok().cloned()),
+            profile_id: momoka_tx.and_then(|tx| tx.profile_id().ok().map(|id| id.to_string())),
+            publication_id: momoka_tx.and_then(|tx| tx.publication_id().ok().cloned()),
+            block_number: momoka_tx.and_then(|tx| tx.block_number().ok().copied()),
+            result: ProofStatus::of(result),
+            error: result.as_ref().err().cloned(),
+            content: content.cloned(),
+            duration_ms: duration.map(|duration| duration.as_millis() as u64),
+        }
+    }
+}
+
+/// An event written as a line of JSON in the `jsonl` output format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum OutputEvent {
+    /// A message which is logged as free text in the `text` output format.
+    Log {
+        level: &'static str,
+        message: String,
+    },
+    /// The verifier started and connected to the nodes.
+    Started {
+        environment: String,
+        deployment: String,
+        nodes: usize,
+        quorum: usize,
+    },
+    /// The verifier started following new transactions after the cursor.
+    Following { cursor: Option<String> },
+    /// A page of new transactions was verified, the counts are running totals as in the follow checkpoint.
+    PageVerified {
+        cursor: Option<String>,
+        listed: usize,
+        valid: usize,
+        invalid: usize,
+    },
+    /// A transaction was verified.
+    TransactionVerified(TransactionVerified),
+    /// A publication was indexed as unverified in trusted indexing mode, before its proof is checked.
+    PublicationIndexed(IndexedPublication),
+    /// An indexed publication was upgraded to verified or invalid once its proof was checked, or
+    /// flagged as also published on-chain.
+    PublicationUpgraded(IndexedPublication),
+    /// A verified DA publication was also published on-chain by executing its typed data signature.
+    DoublePublication(DoublePublication),
+    /// The verifier shut down gracefully, following resumes after the cursor.
+    ShutDown {
+        cursor: Option<String>,
+        valid: usize,
+        invalid: usize,
+    },
+    /// The verifier shut down before the in-flight transactions were verified, as the drain
+    /// timeout expired. Following resumes after the cursor and verifies them again.
+    DrainTimedOut {
+        cursor: Option<String>,
+        valid: usize,
+        invalid: usize,
+    },
+}
+
+/// A line of the `jsonl` output, the event with the time it happened.
+#[derive(Serialize)]
+struct OutputLine<'a> {
+    timestamp: String,
+    #[serde(flatten)]
+    event: &'a OutputEvent,
+}
+
+/// Returns true if the output is written as JSON lines.
+pub fn is_jsonl_output() -> bool {
+    read_output_format_cache() == OutputFormat::Jsonl
+}
+
+/// Writes an event as a line of JSON, if the output is written as JSON lines.
+///
+/// # Arguments
+///
+/// * `event` - The event to write.
+pub fn emit(event: OutputEvent) {
+    if is_jsonl_output() {
+        println!("{}", format_output_line(&event));
+    }
+}
+
+/// Formats an event as a line of JSON stamped with the current time.
+fn format_output_line(event: &OutputEvent) -> String {
+    serde_json::to_string(&OutputLine {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        event,
+    })
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    #[test]
+    fn test_format_output_line() {
+        let event = OutputEvent::TransactionVerified(TransactionVerified::new(
+            &"tx".to_string(),
+            None,
+            &Err(MomokaVerifierError::InvalidTxID),
+            None,
+            None,
+        ));
+
+        let mut line: Value = serde_json::from_str(&format_output_line(&event)).unwrap();
+        assert!(line["timestamp"].is_string());
+        line.as_object_mut().unwrap().remove("timestamp");
+
+        assert_eq!(
+            line,
+            json!({
+                "event": "transaction_verified",
+                "txId": "tx",
+                "submitter": null,
+                "action": null,
+                "profileId": null,
+                "publicationId": null,
+                "blockNumber": null,
+                "result": "INVALID",
+                "error": "InvalidTxID",
+                "content": null,
+                "durationMs": null,
+            })
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::{future::Future, process::exit, time::Duration};
+
+use tokio::{signal, sync::watch};
+use tracing::{error, warn};
+
+lazy_static::lazy_static! {
+    static ref SHUTDOWN: watch::Sender<bool> = watch::channel(false).0;
+}
+
+/// Waits for a SIGTERM, or a Ctrl-C, to be sent to the process.
+async fn signal_received() {
+    #[cfg(unix)]
+    {
+        let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("could not listen for SIGTERM");
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = signal::ctrl_c() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = signal::ctrl_c().await;
+    }
+}
+
+/// Starts listening for SIGTERM and Ctrl-C to shut down gracefully.
+///
+/// The first signal requests a shutdown, which stops new pages from being fetched and lets the
+/// in-flight verification finish. A second signal exits straight away.
+pub fn listen_for_shutdown() {
+    tokio::spawn(async {
+        signal_received().await;
+        warn!("Shutdown requested, finishing the in-flight transactions...");
+        request_shutdown();
+
+        signal_received().await;
+        error!("Shutdown requested again, exiting now");
+        exit(1);
+    });
+}
+
+/// Requests a graceful shutdown.
+pub fn request_shutdown() {
+    SHUTDOWN.send_replace(true);
+}
+
+/// Returns true if a graceful shutdown has been requested.
+pub fn is_shutdown_requested() -> bool {
+    *SHUTDOWN.borrow()
+}
+
+/// Waits until a graceful shutdown is requested.
+pub async fn shutdown_requested() {
+    let mut receiver = SHUTDOWN.subscribe();
+    while !*receiver.borrow_and_update() {
+        if receiver.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Runs in-flight work to completion, or until the drain timeout once a shutdown is requested.
+///
+/// # Arguments
+///
+/// * `future` - The in-flight work.
+/// * `timeout` - How long the work is given to finish after a shutdown is requested.
+///
+/// # Returns
+///
+/// The output of the work, or `None` if it did not finish within the timeout.
+pub async fn drain<F: Future>(future: F, timeout: Duration) -> Option<F::Output> {
+    tokio::pin!(future);
+
+    tokio::select! {
+        output = &mut future => Some(output),
+        _ = shutdown_requested() => tokio::time::timeout(timeout, future).await.ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_after_shutdown_requested() {
+        assert_eq!(drain(async { 1 }, Duration::from_secs(1)).await, Some(1));
+
+        request_shutdown();
+        assert!(is_shutdown_requested());
+        assert_eq!(
+            drain(futures::future::pending::<()>(), Duration::from_millis(10)).await,
+            None
+        );
+        assert_eq!(drain(async { 1 }, Duration::from_secs(1)).await, Some(1));
+    }
+}
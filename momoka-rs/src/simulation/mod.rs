@@ -0,0 +1,493 @@
+pub mod snapshot;
+
+use std::{collections::HashSet, convert::Infallible};
+
+use ethers::{
+    providers::{Middleware, Provider, RetryClient},
+    types::{Address, BlockId, Bytes, H256, U256},
+};
+use futures::future::join_all;
+use revm::{
+    primitives::{
+        AccountInfo, Address as EvmAddress, BlobExcessGasAndPrice, Bytecode, ExecutionResult,
+        TransactTo, B256, U256 as EvmU256,
+    },
+    Database, Evm,
+};
+
+use crate::{
+    environment::environment_to_chain_id,
+    evm::{quorum_read, ProviderContext},
+    transport::NodeTransport,
+    types::verifier_error::{ErrorSource, MomokaVerifierError},
+};
+
+use self::snapshot::{
+    read_snapshot, write_snapshot, AccountSnapshot, BlockSnapshot, StateSnapshot,
+};
+
+/// The most times a call is re-executed after fetching the state it touched, every round
+/// can only discover the slots whose keys depend on the state fetched in the round before.
+const MAX_STATE_FETCH_ROUNDS: usize = 32;
+
+fn to_evm_address(address: Address) -> EvmAddress {
+    EvmAddress::from(address.0)
+}
+
+fn to_evm_u256(value: U256) -> EvmU256 {
+    EvmU256::from_limbs(value.0)
+}
+
+fn from_evm_u256(value: EvmU256) -> U256 {
+    U256(value.into_limbs())
+}
+
+/// The state a local execution read which was not in the snapshot.
+#[derive(Debug, Default)]
+struct MissingState {
+    accounts: HashSet<Address>,
+    storage: HashSet<(Address, U256)>,
+    block_hashes: HashSet<u64>,
+}
+
+impl MissingState {
+    fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.storage.is_empty() && self.block_hashes.is_empty()
+    }
+}
+
+/// A revm database backed by a state snapshot.
+///
+/// Reads which are not in the snapshot return empty state and are recorded, so the
+/// execution can be re-ran once they have been fetched from the nodes.
+struct SnapshotDatabase<'a> {
+    snapshot: &'a StateSnapshot,
+    missing: MissingState,
+}
+
+impl Database for SnapshotDatabase<'_> {
+    type Error = Infallible;
+
+    fn basic(&mut self, address: EvmAddress) -> Result<Option<AccountInfo>, Self::Error> {
+        let address = Address::from(address.into_array());
+        match self.snapshot.accounts.get(&address) {
+            Some(account) => {
+                let code = Bytecode::new_raw(account.code.to_vec().into());
+                Ok(Some(AccountInfo::new(
+                    to_evm_u256(account.balance),
+                    account.nonce,
+                    code.hash_slow(),
+                    code,
+                )))
+            }
+            None => {
+                self.missing.accounts.insert(address);
+                Ok(None)
+            }
+        }
+    }
+
+    fn code_by_hash(&mut self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+        // the code is always handed over with the account so this is never needed
+        Ok(Bytecode::new())
+    }
+
+    fn storage(&mut self, address: EvmAddress, index: EvmU256) -> Result<EvmU256, Self::Error> {
+        let address = Address::from(address.into_array());
+        let index = from_evm_u256(index);
+        match self
+            .snapshot
+            .storage
+            .get(&address)
+            .and_then(|slots| slots.get(&index))
+        {
+            Some(value) => Ok(to_evm_u256(*value)),
+            None => {
+                self.missing.storage.insert((address, index));
+                Ok(EvmU256::ZERO)
+            }
+        }
+    }
+
+    fn block_hash(&mut self, number: EvmU256) -> Result<B256, Self::Error> {
+        let number = number.saturating_to::<u64>();
+        match self.snapshot.block_hashes.get(&number) {
+            Some(hash) => Ok(B256::from(hash.0)),
+            None => {
+                self.missing.block_hashes.insert(number);
+                Ok(B256::ZERO)
+            }
+        }
+    }
+}
+
+/// Fetches the block environment of a block from a node.
+async fn fetch_block(
+    provider: &Provider<RetryClient<NodeTransport>>,
+    block_number: u64,
+    chain_id: u64,
+) -> Result<BlockSnapshot, MomokaVerifierError> {
+    let block = provider
+        .get_block(block_number)
+        .await
+        .map_err(|e| MomokaVerifierError::BlockCantBeReadFromNode { source: e.into() })?
+        .ok_or(MomokaVerifierError::BlockCantBeReadFromNode {
+            source: ErrorSource::default(),
+        })?;
+
+    Ok(BlockSnapshot {
+        number: block_number,
+        timestamp: block.timestamp,
+        coinbase: block.author.unwrap_or_default(),
+        gas_limit: block.gas_limit,
+        difficulty: block.difficulty,
+        prevrandao: block.mix_hash.unwrap_or_default(),
+        chain_id,
+    })
+}
+
+/// Fetches the balance, nonce and code of an account at a block from a node.
+async fn fetch_account(
+    provider: &Provider<RetryClient<NodeTransport>>,
+    address: Address,
+    block_number: u64,
+) -> Result<AccountSnapshot, MomokaVerifierError> {
+    let block = Some(BlockId::from(block_number));
+
+    let (balance, nonce, code) = futures::try_join!(
+        provider.get_balance(address, block),
+        provider.get_transaction_count(address, block),
+        provider.get_code(address, block),
+    )
+    .map_err(|e| MomokaVerifierError::DataCantBeReadFromNode { source: e.into() })?;
+
+    Ok(AccountSnapshot {
+        balance,
+        nonce: nonce.as_u64(),
+        code,
+    })
+}
+
+/// Fetches a storage slot of an account at a block from a node.
+async fn fetch_storage(
+    provider: &Provider<RetryClient<NodeTransport>>,
+    address: Address,
+    index: U256,
+    block_number: u64,
+) -> Result<U256, MomokaVerifierError> {
+    let mut slot = [0u8; 32];
+    index.to_big_endian(&mut slot);
+
+    let value = provider
+        .get_storage_at(address, H256::from(slot), Some(BlockId::from(block_number)))
+        .await
+        .map_err(|e| MomokaVerifierError::DataCantBeReadFromNode { source: e.into() })?;
+
+    Ok(U256::from_big_endian(value.as_bytes()))
+}
+
+/// Fetches the hash of a block from a node.
+async fn fetch_block_hash(
+    provider: &Provider<RetryClient<NodeTransport>>,
+    block_number: u64,
+) -> Result<H256, MomokaVerifierError> {
+    provider
+        .get_block(block_number)
+        .await
+        .map_err(|e| MomokaVerifierError::BlockCantBeReadFromNode { source: e.into() })?
+        .and_then(|block| block.hash)
+        .ok_or(MomokaVerifierError::BlockCantBeReadFromNode {
+            source: ErrorSource::default(),
+        })
+}
+
+/// An in-process EVM which executes calls against the chain state at a block.
+///
+/// The state is fetched lazily, a call is executed against the snapshot and any account,
+/// storage slot or block hash it touched which is not in the snapshot is fetched from the
+/// nodes (checked against the quorum) before executing it again. The snapshot is cached on
+/// disk per block so repeat simulations, including offline re-runs, do not need an archive node.
+pub struct LocalEvm<'a> {
+    provider_context: &'a ProviderContext,
+    cache_dir: &'a str,
+    block_number: u64,
+    snapshot: StateSnapshot,
+    fetched: bool,
+}
+
+impl<'a> LocalEvm<'a> {
+    /// Creates a local EVM at a block, loading the cached snapshot of the block if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider_context` - The provider context used to fetch missing state.
+    /// * `cache_dir` - The directory the snapshots are cached in.
+    /// * `block_number` - The block the calls are executed at.
+    ///
+    /// # Returns
+    ///
+    /// The local EVM, or an error if the block environment could not be fetched.
+    pub async fn at_block(
+        provider_context: &'a ProviderContext,
+        cache_dir: &'a str,
+        block_number: u64,
+    ) -> Result<LocalEvm<'a>, MomokaVerifierError> {
+        let mut snapshot = read_snapshot(cache_dir, &provider_context.environment, block_number);
+        let fetched = snapshot.block.is_none();
+
+        if snapshot.block.is_none() {
+            let chain_id =
+                environment_to_chain_id(provider_context.environment.clone()).map_err(|e| {
+                    MomokaVerifierError::SimulationNodeCouldNotRun {
+                        source: ErrorSource::message(e),
+                    }
+                })? as u64;
+
+            snapshot.block = Some(
+                quorum_read(provider_context, |node| {
+                    fetch_block(node, block_number, chain_id)
+                })
+                .await?,
+            );
+        }
+
+        Ok(LocalEvm {
+            provider_context,
+            cache_dir,
+            block_number,
+            snapshot,
+            fetched,
+        })
+    }
+
+    /// Executes the call once against the current snapshot.
+    fn execute(
+        &self,
+        to: Address,
+        data: &Bytes,
+    ) -> Result<(ExecutionResult, MissingState), MomokaVerifierError> {
+        let block =
+            self.snapshot
+                .block
+                .as_ref()
+                .ok_or(MomokaVerifierError::SimulationNodeCouldNotRun {
+                    source: ErrorSource::default(),
+                })?;
+
+        let mut database = SnapshotDatabase {
+            snapshot: &self.snapshot,
+            missing: MissingState::default(),
+        };
+
+        // matches an `eth_call` without a sender, no gas price and no base fee
+        let mut evm = Evm::builder()
+            .with_db(&mut database)
+            .modify_cfg_env(|cfg| cfg.chain_id = block.chain_id)
+            .modify_block_env(|env| {
+                env.number = EvmU256::from(block.number);
+                env.timestamp = to_evm_u256(block.timestamp);
+                env.coinbase = to_evm_address(block.coinbase);
+                env.gas_limit = to_evm_u256(block.gas_limit);
+                env.difficulty = to_evm_u256(block.difficulty);
+                env.prevrandao = Some(B256::from(block.prevrandao.0));
+                env.basefee = EvmU256::ZERO;
+                env.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice::new(0));
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = EvmAddress::ZERO;
+                tx.transact_to = TransactTo::Call(to_evm_address(to));
+                tx.data = data.to_vec().into();
+                tx.gas_limit = block.gas_limit.low_u64();
+                tx.gas_price = EvmU256::ZERO;
+            })
+            .build();
+
+        let result = evm
+            .transact()
+            .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun { source: e.into() })?
+            .result;
+        drop(evm);
+
+        Ok((result, database.missing))
+    }
+
+    /// Fetches the missing state from the nodes into the snapshot.
+    async fn fetch(&mut self, missing: MissingState) -> Result<(), MomokaVerifierError> {
+        let provider_context = self.provider_context;
+        let block_number = self.block_number;
+
+        let accounts = join_all(missing.accounts.into_iter().map(|address| async move {
+            quorum_read(provider_context, |node| {
+                fetch_account(node, address, block_number)
+            })
+            .await
+            .map(|account| (address, account))
+        }))
+        .await;
+
+        let storage = join_all(
+            missing
+                .storage
+                .into_iter()
+                .map(|(address, index)| async move {
+                    quorum_read(provider_context, |node| {
+                        fetch_storage(node, address, index, block_number)
+                    })
+                    .await
+                    .map(|value| (address, index, value))
+                }),
+        )
+        .await;
+
+        let block_hashes = join_all(missing.block_hashes.into_iter().map(|number| async move {
+            quorum_read(provider_context, |node| fetch_block_hash(node, number))
+                .await
+                .map(|hash| (number, hash))
+        }))
+        .await;
+
+        for account in accounts {
+            let (address, account) = account?;
+            self.snapshot.accounts.insert(address, account);
+        }
+        for value in storage {
+            let (address, index, value) = value?;
+            self.snapshot
+                .storage
+                .entry(address)
+                .or_default()
+                .insert(index, value);
+        }
+        for block_hash in block_hashes {
+            let (number, hash) = block_hash?;
+            self.snapshot.block_hashes.insert(number, hash);
+        }
+
+        self.fetched = true;
+
+        Ok(())
+    }
+
+    /// Executes a call at the block, fetching the state it touches until it has all it needs.
+    ///
+    /// # Arguments
+    ///
+    /// * `to` - The contract called.
+    /// * `data` - The calldata.
+    ///
+    /// # Returns
+    ///
+    /// The return data of the call, `SimulationFailed` if the call reverted, or
+    /// `SimulationNodeCouldNotRun` if the state it needed could not be fetched.
+    pub async fn call(&mut self, to: Address, data: Bytes) -> Result<Bytes, MomokaVerifierError> {
+        for _ in 0..MAX_STATE_FETCH_ROUNDS {
+            let (result, missing) = self.execute(to, &data)?;
+
+            if missing.is_empty() {
+                return match result {
+                    ExecutionResult::Success { output, .. } => {
+                        Ok(Bytes::from(output.into_data().to_vec()))
+                    }
+                    _ => Err(MomokaVerifierError::SimulationFailed {
+                        source: ErrorSource::default(),
+                    }),
+                };
+            }
+
+            self.fetch(missing).await?;
+        }
+
+        Err(MomokaVerifierError::SimulationNodeCouldNotRun {
+            source: ErrorSource::default(),
+        })
+    }
+
+    /// Caches the snapshot on disk if any state was fetched for it.
+    pub fn save(&self) {
+        if self.fetched {
+            write_snapshot(
+                self.cache_dir,
+                &self.provider_context.environment,
+                self.block_number,
+                &self.snapshot,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        environment::{Deployment, Environment},
+        evm::evm_provider,
+    };
+
+    #[tokio::test]
+    async fn test_call_runs_offline_from_cached_snapshot() {
+        let cache_dir = std::env::temp_dir().join(format!("momoka-{}", uuid::Uuid::new_v4()));
+        let cache_dir = cache_dir.to_str().unwrap();
+
+        // a node which can not be reached, the call must be answered from the cache
+        let node = "http://127.0.0.1:1";
+        let provider_context = ProviderContext {
+            environment: Environment::Polygon,
+            node: evm_provider(node),
+            nodes: vec![evm_provider(node)],
+            node_urls: vec![node.to_string()],
+            quorum: 1,
+            deployment: Deployment::Production,
+            arweave_gateway: None,
+            detect_double_publications: false,
+            local_simulation: Some(cache_dir.to_string()),
+            content_gateways: None,
+            transaction_filter: None,
+            offline: false,
+            trusted_indexing: false,
+        };
+
+        // returns the value in storage slot 0
+        let contract = Address::from_low_u64_be(0x1234);
+        let code = Bytes::from(vec![
+            0x60, 0x00, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ]);
+
+        let mut snapshot = StateSnapshot {
+            block: Some(BlockSnapshot {
+                number: 1,
+                timestamp: U256::from(1684234571u64),
+                coinbase: Address::zero(),
+                gas_limit: U256::from(30_000_000u64),
+                difficulty: U256::zero(),
+                prevrandao: H256::zero(),
+                chain_id: 137,
+            }),
+            ..Default::default()
+        };
+        for (address, code) in [(Address::zero(), Bytes::default()), (contract, code)] {
+            snapshot.accounts.insert(
+                address,
+                AccountSnapshot {
+                    balance: U256::zero(),
+                    nonce: 0,
+                    code,
+                },
+            );
+        }
+        snapshot
+            .storage
+            .entry(contract)
+            .or_default()
+            .insert(U256::zero(), U256::from(42));
+        write_snapshot(cache_dir, &Environment::Polygon, 1, &snapshot);
+
+        let mut local_evm = LocalEvm::at_block(&provider_context, cache_dir, 1)
+            .await
+            .unwrap();
+        let output = local_evm.call(contract, Bytes::default()).await.unwrap();
+
+        assert_eq!(U256::from_big_endian(&output), U256::from(42));
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
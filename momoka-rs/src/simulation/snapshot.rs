@@ -0,0 +1,178 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use ethers::types::{Address, Bytes, H256, U256};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{environment::Environment, utils::write_atomically};
+
+/// The block environment the simulation runs in.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockSnapshot {
+    pub number: u64,
+    pub timestamp: U256,
+    pub coinbase: Address,
+    pub gas_limit: U256,
+    pub difficulty: U256,
+    pub prevrandao: H256,
+    pub chain_id: u64,
+}
+
+/// The state of an account at the snapshot block.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSnapshot {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Bytes,
+}
+
+/// The chain state fetched at a block, only the accounts and storage slots which a
+/// simulation touched are held.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateSnapshot {
+    pub block: Option<BlockSnapshot>,
+    pub accounts: HashMap<Address, AccountSnapshot>,
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    pub block_hashes: HashMap<u64, H256>,
+}
+
+impl StateSnapshot {
+    /// Merges another snapshot of the same block into this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The snapshot to merge in.
+    pub fn merge(&mut self, other: StateSnapshot) {
+        if self.block.is_none() {
+            self.block = other.block;
+        }
+        self.accounts.extend(other.accounts);
+        for (address, slots) in other.storage {
+            self.storage.entry(address).or_default().extend(slots);
+        }
+        self.block_hashes.extend(other.block_hashes);
+    }
+}
+
+/// Returns the path of the snapshot file for a block.
+///
+/// # Arguments
+///
+/// * `cache_dir` - The directory the snapshots are cached in.
+/// * `environment` - The environment the block is on.
+/// * `block_number` - The block number.
+///
+/// # Returns
+///
+/// The path of the snapshot file.
+fn snapshot_path(cache_dir: &str, environment: &Environment, block_number: u64) -> PathBuf {
+    PathBuf::from(cache_dir)
+        .join(format!("{:?}", environment).to_lowercase())
+        .join(format!("{}.json", block_number))
+}
+
+/// Reads the cached snapshot of a block from disk.
+///
+/// # Arguments
+///
+/// * `cache_dir` - The directory the snapshots are cached in.
+/// * `environment` - The environment the block is on.
+/// * `block_number` - The block number.
+///
+/// # Returns
+///
+/// The cached snapshot, or an empty snapshot if the block has not been cached or the file can not be read.
+pub fn read_snapshot(
+    cache_dir: &str,
+    environment: &Environment,
+    block_number: u64,
+) -> StateSnapshot {
+    fs::read(snapshot_path(cache_dir, environment, block_number))
+        .ok()
+        .and_then(|contents| serde_json::from_slice(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Writes the snapshot of a block to disk, merged with anything another run cached meanwhile.
+///
+/// The file is written atomically so a reader never sees a partially written snapshot. Failing to write is logged and ignored, the state will just
+/// be fetched again next time.
+///
+/// # Arguments
+///
+/// * `cache_dir` - The directory the snapshots are cached in.
+/// * `environment` - The environment the block is on.
+/// * `block_number` - The block number.
+/// * `snapshot` - The snapshot to write.
+pub fn write_snapshot(
+    cache_dir: &str,
+    environment: &Environment,
+    block_number: u64,
+    snapshot: &StateSnapshot,
+) {
+    let path = snapshot_path(cache_dir, environment, block_number);
+
+    let mut merged = read_snapshot(cache_dir, environment, block_number);
+    merged.merge(snapshot.clone());
+
+    if let Err(error) = write_atomically(&path, &serde_json::to_vec(&merged).unwrap()) {
+        warn!(
+            "Could not cache the state snapshot of block {} - {}",
+            block_number, error
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trips_through_disk() {
+        let cache_dir = std::env::temp_dir().join(format!("momoka-{}", uuid::Uuid::new_v4()));
+        let cache_dir = cache_dir.to_str().unwrap();
+
+        assert_eq!(
+            read_snapshot(cache_dir, &Environment::Polygon, 1),
+            StateSnapshot::default()
+        );
+
+        let address = Address::from_low_u64_be(1);
+        let mut first = StateSnapshot::default();
+        first.accounts.insert(
+            address,
+            AccountSnapshot {
+                balance: U256::from(1),
+                nonce: 2,
+                code: Bytes::from(vec![0x60, 0x00]),
+            },
+        );
+        first
+            .storage
+            .entry(address)
+            .or_default()
+            .insert(U256::from(1), U256::from(10));
+        write_snapshot(cache_dir, &Environment::Polygon, 1, &first);
+
+        let mut second = StateSnapshot::default();
+        second
+            .storage
+            .entry(address)
+            .or_default()
+            .insert(U256::from(2), U256::from(20));
+        write_snapshot(cache_dir, &Environment::Polygon, 1, &second);
+
+        let cached = read_snapshot(cache_dir, &Environment::Polygon, 1);
+        assert_eq!(cached.accounts, first.accounts);
+        assert_eq!(cached.storage[&address].len(), 2);
+        assert_eq!(
+            read_snapshot(cache_dir, &Environment::Mumbai, 1),
+            StateSnapshot::default()
+        );
+
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+/// A request received by a stand-in server.
+pub struct StandInRequest {
+    pub method: String,
+    pub path: String,
+}
+
+/// Reads a whole request, so the client never sees the connection reset before it is answered.
+fn read_request(stream: &mut TcpStream) -> StandInRequest {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+
+    loop {
+        let read = stream.read(&mut buffer).unwrap_or_default();
+        request.extend_from_slice(&buffer[..read]);
+
+        let text = String::from_utf8_lossy(&request);
+        if let Some(headers_end) = text.find("\r\n\r\n") {
+            let content_length = text[..headers_end]
+                .lines()
+                .find_map(|line| {
+                    line.to_ascii_lowercase()
+                        .strip_prefix("content-length:")
+                        .and_then(|value| value.trim().parse::<usize>().ok())
+                })
+                .unwrap_or(0);
+            if request.len() >= headers_end + 4 + content_length {
+                break;
+            }
+        }
+        if read == 0 {
+            break;
+        }
+    }
+
+    let text = String::from_utf8_lossy(&request).to_string();
+    let mut request_line = text.split_whitespace();
+
+    StandInRequest {
+        method: request_line.next().unwrap_or_default().to_string(),
+        path: request_line.next().unwrap_or_default().to_string(),
+    }
+}
+
+/// Starts a local stand-in for a gateway or node, so requests can be tested without reaching one.
+///
+/// # Arguments
+///
+/// * `respond` - Answers a request with a JSON body, or `None` for a 404.
+///
+/// # Returns
+///
+/// The base URL of the stand-in.
+pub fn serve<F>(respond: F) -> String
+where
+    F: Fn(&StandInRequest) -> Option<String> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let request = read_request(&mut stream);
+
+            let (status, body) = match respond(&request) {
+                Some(body) => ("200 OK", body),
+                None => ("404 Not Found", "not found".to_string()),
+            };
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+        }
+    });
+
+    format!("http://{}", address)
+}
@@ -0,0 +1,351 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    fs,
+    future::Future,
+    path::PathBuf,
+    str::FromStr,
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use ethers::{
+    providers::{Http, HttpClientError, JsonRpcClient, JsonRpcError},
+    utils::{hex, keccak256},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::{cache::read_transport_cache, utils::write_atomically};
+
+/// The JSON-RPC error code returned for a node request which is not in the recording being replayed.
+const NOT_RECORDED_ERROR_CODE: i64 = -32099;
+
+/// Whether the traffic of the verifier is recorded or replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportMode {
+    /// Every request is sent and its response written to the recording.
+    Record,
+    /// Every request is answered from the recording, nothing is sent.
+    Replay,
+}
+
+/// A request the verifier made.
+///
+/// The URL of a node is replaced by its position in the nodes (`node-0`, `node-1` and so on), so
+/// recordings do not hold node API keys and can be replayed against any node URLs.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RecordedRequest {
+    /// The HTTP method, or the JSON-RPC method for requests sent through a node provider.
+    pub method: String,
+    /// The URL the request was sent to.
+    pub url: String,
+    /// The JSON body of the request, or the params of a JSON-RPC request.
+    pub body: Value,
+}
+
+impl RecordedRequest {
+    /// Returns the key the request is recorded under, a hash of the request.
+    fn key(&self) -> String {
+        hex::encode(&keccak256(serde_json::to_vec(self).unwrap())[..16])
+    }
+}
+
+/// A request and every response it got, in the order they were received.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RecordedExchange {
+    pub request: RecordedRequest,
+    pub responses: Vec<Value>,
+}
+
+/// Records the requests the verifier makes to bundlr, arweave, content gateways and the nodes to
+/// a directory, or replays them from it.
+///
+/// Each distinct request is kept in its own file named after its key. A request made more than
+/// once is answered with its responses in the order they were recorded, then with the last one.
+#[derive(Debug)]
+pub struct Transport {
+    dir: PathBuf,
+    mode: TransportMode,
+    node_urls: Vec<String>,
+    /// The exchanges recorded or loaded so far, next to how many of their responses were replayed.
+    exchanges: Mutex<HashMap<String, (RecordedExchange, usize)>>,
+}
+
+/// The error a request sent through the transport failed with.
+#[derive(Debug)]
+pub enum TransportError<E> {
+    /// The request was sent and failed.
+    Send(E),
+    /// The request is not in the recording being replayed.
+    NotRecorded,
+}
+
+impl Transport {
+    /// Creates a transport recording to or replaying from a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory of the recording.
+    /// * `mode` - Whether the traffic is recorded or replayed.
+    /// * `node_urls` - The URLs of the nodes, recorded by their position.
+    pub fn new(dir: impl Into<PathBuf>, mode: TransportMode, node_urls: Vec<String>) -> Self {
+        Transport {
+            dir: dir.into(),
+            mode,
+            node_urls,
+            exchanges: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the directory of the recording.
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    /// Returns whether the traffic is recorded or replayed.
+    pub fn mode(&self) -> TransportMode {
+        self.mode
+    }
+
+    /// Builds the request as it is recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP or JSON-RPC method.
+    /// * `url` - The URL the request is sent to.
+    /// * `body` - The body or params of the request.
+    pub fn request(&self, method: &str, url: &str, body: Value) -> RecordedRequest {
+        let url = match self.node_urls.iter().position(|node_url| node_url == url) {
+            Some(index) => format!("node-{}", index),
+            None => url.to_string(),
+        };
+
+        RecordedRequest {
+            method: method.to_string(),
+            url,
+            body,
+        }
+    }
+
+    /// Returns the path an exchange is recorded at.
+    fn exchange_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Records the response to a request.
+    ///
+    /// Failing to write is logged and ignored, the request will just be missing from the recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request.
+    /// * `response` - The response it got.
+    pub fn record(&self, request: RecordedRequest, response: Value) {
+        let key = request.key();
+        let mut exchanges = self.exchanges.lock().unwrap();
+        let (exchange, _) = exchanges.entry(key.clone()).or_insert_with(|| {
+            (
+                RecordedExchange {
+                    request,
+                    responses: vec![],
+                },
+                0,
+            )
+        });
+        exchange.responses.push(response);
+
+        if let Err(error) = write_atomically(
+            &self.exchange_path(&key),
+            &serde_json::to_vec_pretty(exchange).unwrap(),
+        ) {
+            warn!(
+                "Could not record the {} request to {} - {}",
+                exchange.request.method, exchange.request.url, error
+            );
+        }
+    }
+
+    /// Replays the next response to a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request.
+    ///
+    /// # Returns
+    ///
+    /// The response, or `None` if the request is not in the recording.
+    pub fn replay(&self, request: &RecordedRequest) -> Option<Value> {
+        let key = request.key();
+        let mut exchanges = self.exchanges.lock().unwrap();
+
+        if !exchanges.contains_key(&key) {
+            let exchange: RecordedExchange = fs::read(self.exchange_path(&key))
+                .ok()
+                .and_then(|contents| serde_json::from_slice(&contents).ok())?;
+            exchanges.insert(key.clone(), (exchange, 0));
+        }
+
+        let (exchange, replayed) = exchanges.get_mut(&key)?;
+        let response = exchange
+            .responses
+            .get(*replayed)
+            .or(exchange.responses.last())
+            .cloned();
+        *replayed += 1;
+
+        response
+    }
+}
+
+impl<E: Display> Display for TransportError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            TransportError::Send(error) => write!(f, "{}", error),
+            TransportError::NotRecorded => write!(f, "the request is not in the recording"),
+        }
+    }
+}
+
+/// Sends a request, or records or replays it when a transport is set.
+///
+/// # Arguments
+///
+/// * `method` - The HTTP or JSON-RPC method.
+/// * `url` - The URL the request is sent to.
+/// * `body` - The body or params of the request.
+/// * `send` - Sends the request, only successful responses are recorded.
+///
+/// # Returns
+///
+/// The response, the error sending failed with, or `NotRecorded` when replaying a request which was not recorded.
+pub async fn send_through<E, F, Fut>(
+    method: &str,
+    url: &str,
+    body: Value,
+    send: F,
+) -> Result<Value, TransportError<E>>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Value, E>>,
+{
+    let Some(transport) = read_transport_cache() else {
+        return send().await.map_err(TransportError::Send);
+    };
+
+    let request = transport.request(method, url, body);
+    match transport.mode {
+        TransportMode::Record => {
+            let response = send().await.map_err(TransportError::Send)?;
+            transport.record(request, response.clone());
+            Ok(response)
+        }
+        TransportMode::Replay => transport
+            .replay(&request)
+            .ok_or(TransportError::NotRecorded),
+    }
+}
+
+/// The JSON-RPC transport of a node, which records or replays the requests when a transport is set.
+///
+/// JSON-RPC errors (for example a reverted `eth_call`) are recorded too, as verdicts depend on them.
+#[derive(Debug)]
+pub struct NodeTransport {
+    url: String,
+    http: Http,
+}
+
+impl NodeTransport {
+    /// Creates the transport of a node.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the node.
+    pub fn new(url: &str) -> Self {
+        NodeTransport {
+            url: url.to_string(),
+            http: Http::from_str(url).unwrap(),
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for NodeTransport {
+    type Error = HttpClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, HttpClientError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        if read_transport_cache().is_none() {
+            return self.http.request(method, params).await;
+        }
+
+        let params = serde_json::to_value(&params).map_err(|err| HttpClientError::SerdeJson {
+            err,
+            text: format!("{:?}", params),
+        })?;
+
+        let response = send_through(method, &self.url, params.clone(), || async {
+            match self.http.request::<_, Value>(method, &params).await {
+                Ok(result) => Ok(json!({ "result": result })),
+                Err(HttpClientError::JsonRpcError(error)) => Ok(json!({
+                    "error": { "code": error.code, "message": error.message, "data": error.data }
+                })),
+                Err(error) => Err(error),
+            }
+        })
+        .await
+        .map_err(|error| match error {
+            TransportError::Send(error) => error,
+            TransportError::NotRecorded => HttpClientError::JsonRpcError(JsonRpcError {
+                code: NOT_RECORDED_ERROR_CODE,
+                message: TransportError::<HttpClientError>::NotRecorded.to_string(),
+                data: None,
+            }),
+        })?;
+
+        let text = response.to_string();
+        match response.get("error") {
+            Some(error) => Err(HttpClientError::JsonRpcError(
+                serde_json::from_value(error.clone())
+                    .map_err(|err| HttpClientError::SerdeJson { err, text })?,
+            )),
+            None => serde_json::from_value(response.get("result").cloned().unwrap_or_default())
+                .map_err(|err| HttpClientError::SerdeJson { err, text }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replays_recorded_responses_in_order() {
+        let dir = std::env::temp_dir().join(format!("momoka-recording-{}", uuid::Uuid::new_v4()));
+        let node_url = "https://node.example/v2/secret-key".to_string();
+
+        let recorder = Transport::new(&dir, TransportMode::Record, vec![node_url.clone()]);
+        let request = recorder.request("eth_blockNumber", &node_url, json!([]));
+        assert_eq!(request.url, "node-0");
+        recorder.record(request.clone(), json!({ "result": "0x1" }));
+        recorder.record(request, json!({ "result": "0x2" }));
+
+        // replayed against another node url, which is recorded by its position all the same
+        let replayer = Transport::new(
+            &dir,
+            TransportMode::Replay,
+            vec!["http://localhost:8545".to_string()],
+        );
+        let request = replayer.request("eth_blockNumber", "http://localhost:8545", json!([]));
+        assert_eq!(replayer.replay(&request), Some(json!({ "result": "0x1" })));
+        assert_eq!(replayer.replay(&request), Some(json!({ "result": "0x2" })));
+        assert_eq!(replayer.replay(&request), Some(json!({ "result": "0x2" })));
+
+        let other = replayer.request("eth_chainId", "http://localhost:8545", json!([]));
+        assert_eq!(replayer.replay(&other), None);
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt::{Display, Formatter, Result};
+
+use serde::{Deserialize, Serialize};
+
+/// Represents whether the content a publication points to could be resolved and is valid metadata.
+///
+/// This is not part of the verdict on the transaction, a publication is valid on momoka even if its
+/// content has gone missing, so it is reported next to the verification result.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContentStatus {
+    /// The content was resolved and is valid Lens publication metadata.
+    Valid {
+        /// The metadata schema version the content was validated against.
+        version: String,
+    },
+    /// The content could not be resolved through the gateway.
+    Unavailable {
+        /// Why the content could not be resolved.
+        reason: String,
+    },
+    /// The content was resolved but is not valid Lens publication metadata.
+    InvalidMetadata {
+        /// Why the content is not valid metadata.
+        reason: String,
+    },
+    /// The content URI is not an `ar://`, `ipfs://` or `https://` URI.
+    UnsupportedUri {
+        /// The content URI in the typed data.
+        uri: String,
+    },
+}
+
+impl ContentStatus {
+    /// The name of the status as it is written in reports and exports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentStatus::Valid { .. } => "VALID",
+            ContentStatus::Unavailable { .. } => "UNAVAILABLE",
+            ContentStatus::InvalidMetadata { .. } => "INVALID_METADATA",
+            ContentStatus::UnsupportedUri { .. } => "UNSUPPORTED_URI",
+        }
+    }
+}
+
+impl Display for ContentStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            ContentStatus::Valid { version } => write!(f, "valid metadata {}", version),
+            ContentStatus::Unavailable { reason } => write!(f, "unavailable - {}", reason),
+            ContentStatus::InvalidMetadata { reason } => {
+                write!(f, "invalid metadata - {}", reason)
+            }
+            ContentStatus::UnsupportedUri { uri } => write!(f, "unsupported uri {}", uri),
+        }
+    }
+}
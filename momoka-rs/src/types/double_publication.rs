@@ -0,0 +1,64 @@
+use ethers::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    profile_id::ProfileId,
+    publication_id::PublicationId,
+    transaction::{MomokaTxId, TransactionAction},
+};
+
+/// A verified DA publication whose typed data signature could still be executed on-chain.
+///
+/// Momoka publications do not consume the `sigNonces` of the signer so the same signed typed
+/// data is valid on the LensHub until its deadline passes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaPublicationRecord {
+    /// The ID of the DA transaction.
+    pub tx_id: MomokaTxId,
+
+    /// The action of the DA transaction.
+    pub action: TransactionAction,
+
+    /// The profile which made the publication.
+    pub profile_id: ProfileId,
+
+    /// The address which signed the typed data.
+    pub signer: Address,
+
+    /// The signature nonce in the typed data.
+    pub nonce: u64,
+
+    /// The signature deadline in the typed data.
+    pub deadline: u64,
+
+    /// The typed data signature.
+    pub signature: String,
+
+    /// The block number the DA publication was made against.
+    pub block_number: u64,
+}
+
+/// A DA publication whose typed data signature was also executed on the LensHub,
+/// meaning the same content exists as an on-chain publication as well.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoublePublication {
+    /// The ID of the DA transaction.
+    pub tx_id: MomokaTxId,
+
+    /// The action of the DA transaction.
+    pub action: TransactionAction,
+
+    /// The profile which made the publication.
+    pub profile_id: ProfileId,
+
+    /// The hash of the on-chain transaction which executed the same signature.
+    pub transaction_hash: H256,
+
+    /// The publication id of the on-chain publication.
+    pub pub_id: PublicationId,
+
+    /// The block number of the on-chain publication.
+    pub block_number: u64,
+}
@@ -0,0 +1,92 @@
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    double_publication::DoublePublication,
+    profile_id::ProfileId,
+    transaction::{MomokaTxId, TransactionAction, TransactionSummary},
+    verifier_error::MomokaVerifierError,
+};
+
+/// How far a publication indexed in trusted indexing mode has been verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PublicationStatus {
+    /// The publication was indexed as submitted to bundlr, its proof has not been checked yet.
+    Unverified,
+    /// The proof of the publication was checked and is valid.
+    Verified,
+    /// The proof of the publication was checked and is invalid.
+    Invalid,
+}
+
+/// A publication indexed as soon as it is fetched, before its proof is checked.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedPublication {
+    /// The ID of the DA transaction.
+    pub tx_id: MomokaTxId,
+
+    /// The action of the DA transaction.
+    pub action: TransactionAction,
+
+    /// The profile which made the publication.
+    pub profile_id: ProfileId,
+
+    /// The publication id of the DA publication.
+    pub publication_id: String,
+
+    /// The address which submitted the transaction to bundlr.
+    pub submitter: Address,
+
+    /// The block number the DA publication was made against.
+    pub block_number: u64,
+
+    /// The content uri of the publication, mirrors have none.
+    pub content_uri: Option<String>,
+
+    /// The DA transaction a comment or mirror points to, if it points to a DA publication.
+    pub pointer_tx_id: Option<MomokaTxId>,
+
+    /// How far the publication has been verified.
+    pub status: PublicationStatus,
+
+    /// The error which made the publication invalid.
+    pub error: Option<MomokaVerifierError>,
+
+    /// The on-chain publication, if the publication was also published on-chain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub double_publication: Option<DoublePublication>,
+}
+
+impl IndexedPublication {
+    /// Builds the unverified publication of a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - The transaction as fetched from bundlr.
+    ///
+    /// # Returns
+    ///
+    /// The publication, or an error if the transaction is not a publication.
+    pub fn unverified(transaction: &TransactionSummary) -> Result<Self, MomokaVerifierError> {
+        let momoka_tx = &transaction.momoka_tx;
+
+        Ok(IndexedPublication {
+            tx_id: transaction.id.clone(),
+            action: momoka_tx.transaction_type()?.clone(),
+            profile_id: momoka_tx.profile_id()?.clone(),
+            publication_id: momoka_tx.publication_id()?.clone(),
+            submitter: transaction.submitter,
+            block_number: *momoka_tx.block_number()?,
+            content_uri: momoka_tx.content_uri()?.cloned(),
+            pointer_tx_id: momoka_tx
+                .pointer()?
+                .as_ref()
+                .map(|pointer| pointer.location.replace("ar://", "")),
+            status: PublicationStatus::Unverified,
+            error: None,
+            double_publication: None,
+        })
+    }
+}
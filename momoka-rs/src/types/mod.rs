@@ -1,8 +1,14 @@
 pub mod chain_proofs;
+pub mod content;
+pub mod double_publication;
 pub mod eip721;
 pub mod evm_event;
 pub mod hex;
+pub mod indexed_publication;
 pub mod profile_id;
 pub mod publication_id;
+pub mod settlement;
 pub mod transaction;
+pub mod transaction_filter;
+pub mod verification_report;
 pub mod verifier_error;
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Represents how far a DA transaction has got in being settled on Arweave.
+///
+/// Bundlr hands back a timestamp proof with a `deadline_height` which is the maximum Arweave
+/// block height the data item should be included by. Until the bundle holding the data item
+/// is seen on Arweave the transaction is only timestamped, not settled.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SettlementStatus {
+    /// The data item was included in an Arweave block at or before the deadline height.
+    Settled {
+        /// The Arweave block height the data item was included in.
+        block_height: u64,
+    },
+    /// The data item is timestamped by Bundlr but not yet seen on Arweave, and the deadline height has not passed.
+    TimestampedNotSettled {
+        /// The deadline height the data item has to be included by.
+        deadline_height: u64,
+    },
+}
@@ -4,6 +4,7 @@ use crate::evm::ProviderContext;
 use crate::verifier::transactions::comment::verifier_comment;
 use crate::verifier::transactions::mirror::verifier_mirror;
 use crate::verifier::transactions::post::verifier_post;
+use crate::verifier::transactions::{comment, mirror, post};
 
 use super::eip721::{
     CreateCommentEIP712TypedDataValue, CreateCommentEIP712Types, CreateMirrorEIP712TypedDataValue,
@@ -12,7 +13,8 @@ use super::eip721::{
 use super::evm_event::EvmEvent;
 use super::profile_id::ProfileId;
 use super::publication_id::PublicationId;
-use super::verifier_error::MomokaVerifierError;
+use super::verification_report::VerificationReport;
+use super::verifier_error::{ErrorSource, MomokaVerifierError};
 use super::{
     chain_proofs::ChainProofs,
     eip721::{
@@ -24,14 +26,13 @@ use super::{
     },
 };
 use ethers::types::Address;
-use json::JsonValue;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub type MomokaTxId = String;
 
 /// An enum representing the type of action associated with a transaction.
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 #[allow(clippy::enum_variant_names)]
 pub enum TransactionAction {
@@ -97,6 +98,9 @@ pub struct TimestampProofsSummary {
     /// The ID of the transaction.
     pub id: MomokaTxId,
 
+    /// The owner of the timestamp proofs data item.
+    pub submitter: Address,
+
     /// The response to the timestamp proof validation.
     pub response: TimestampProofsResponse,
 }
@@ -154,6 +158,11 @@ where
 
     /// The event that generated this publication.
     pub event: TEvent,
+
+    /// The publication JSON as it was received, which is what the submitter signed.
+    /// `None` if the publication was not parsed from the JSON it was received as.
+    #[serde(skip)]
+    pub received_json: Option<String>,
 }
 
 /// A `BasePublication` representing a post created on the platform.
@@ -277,18 +286,33 @@ impl MomokaTransaction {
         match transaction_type {
             MomokaTransactionName::PostCreated => {
                 serde_json::from_str::<PostCreatedPublication>(json)
-                    .map(MomokaTransaction::PostCreated)
-                    .map_err(|_| MomokaVerifierError::InvalidTransactionFormat)
+                    .map(|e| {
+                        MomokaTransaction::PostCreated(BasePublication {
+                            received_json: Some(json.to_owned()),
+                            ..e
+                        })
+                    })
+                    .map_err(|e| MomokaVerifierError::InvalidTransactionFormat { source: e.into() })
             }
             MomokaTransactionName::CommentCreated => {
                 serde_json::from_str::<CommentCreatedPublication>(json)
-                    .map(MomokaTransaction::CommentCreated)
-                    .map_err(|_| MomokaVerifierError::InvalidTransactionFormat)
+                    .map(|e| {
+                        MomokaTransaction::CommentCreated(BasePublication {
+                            received_json: Some(json.to_owned()),
+                            ..e
+                        })
+                    })
+                    .map_err(|e| MomokaVerifierError::InvalidTransactionFormat { source: e.into() })
             }
             MomokaTransactionName::MirrorCreated => {
                 serde_json::from_str::<MirrorCreatedPublication>(json)
-                    .map(MomokaTransaction::MirrorCreated)
-                    .map_err(|_| MomokaVerifierError::InvalidTransactionFormat)
+                    .map(|e| {
+                        MomokaTransaction::MirrorCreated(BasePublication {
+                            received_json: Some(json.to_owned()),
+                            ..e
+                        })
+                    })
+                    .map_err(|e| MomokaVerifierError::InvalidTransactionFormat { source: e.into() })
             }
             _ => Err(MomokaVerifierError::InvalidTransactionType),
         }
@@ -364,6 +388,7 @@ impl MomokaTransaction {
     ///
     /// * `publication` - A `PostCreatedPublication` to be verified.
     /// * `provider_context` - The provider context
+    /// * `report` - The report the checks are recorded in.
     ///
     /// # Returns
     ///
@@ -371,11 +396,16 @@ impl MomokaTransaction {
     pub async fn validate_transaction(
         &self,
         provider_context: &ProviderContext,
+        report: &mut VerificationReport,
     ) -> Result<(), MomokaVerifierError> {
         match self {
-            MomokaTransaction::CommentCreated(e) => verifier_comment(e, provider_context).await,
-            MomokaTransaction::MirrorCreated(e) => verifier_mirror(e, provider_context).await,
-            MomokaTransaction::PostCreated(e) => verifier_post(e, provider_context).await,
+            MomokaTransaction::CommentCreated(e) => {
+                verifier_comment(e, provider_context, report).await
+            }
+            MomokaTransaction::MirrorCreated(e) => {
+                verifier_mirror(e, provider_context, report).await
+            }
+            MomokaTransaction::PostCreated(e) => verifier_post(e, provider_context, report).await,
             _ => Err(MomokaVerifierError::InvalidTransactionType),
         }
     }
@@ -422,6 +452,30 @@ impl MomokaTransaction {
         }
     }
 
+    /// Returns the publication JSON as it was received, which is what the submitter signed.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(&str)`: The received JSON.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid, or `InvalidTransactionFormat`
+    ///   if the transaction was not parsed with `from_json`.
+    pub fn received_json(&self) -> Result<&str, MomokaVerifierError> {
+        let received_json = match self {
+            MomokaTransaction::CommentCreated(e) => &e.received_json,
+            MomokaTransaction::MirrorCreated(e) => &e.received_json,
+            MomokaTransaction::PostCreated(e) => &e.received_json,
+            _ => return Err(MomokaVerifierError::InvalidTransactionType),
+        };
+
+        received_json
+            .as_deref()
+            .ok_or_else(|| MomokaVerifierError::InvalidTransactionFormat {
+                source: ErrorSource::message(
+                    "the transaction was not parsed from the JSON it was received as",
+                ),
+            })
+    }
+
     /// Returns a reference to the timestamp in the third-party timestamp proofs of the transaction.
     ///
     /// # Errors
@@ -510,7 +564,10 @@ impl MomokaTransaction {
             .to_string()
             .split('-')
             .next()
-            .ok_or(MomokaVerifierError::GeneratedPublicationIdMismatch)?
+            .ok_or_else(|| MomokaVerifierError::GeneratedPublicationIdMismatch {
+                expected: String::new(),
+                actual: data_availability_id.to_string(),
+            })?
             .to_string();
 
         Ok(format!("{}-{}-DA-{}", profile_id, pub_id, end_id))
@@ -536,7 +593,7 @@ impl MomokaTransaction {
     ///
     /// A `Result` containing a reference to the generated publication ID string, or an error if
     /// the transaction type is invalid or an error occurs during for
-    fn generate_publication_id(&self) -> Result<String, MomokaVerifierError> {
+    pub fn generate_publication_id(&self) -> Result<String, MomokaVerifierError> {
         match self {
             MomokaTransaction::CommentCreated(e) => Ok(self.format_publication_id(
                 &e.event.profile_id,
@@ -580,41 +637,6 @@ impl MomokaTransaction {
         }
     }
 
-    /// Returns the address of the verifying contract for the transaction.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error of type `MomokaVerifierError::InvalidTransactionType` if the transaction
-    /// type is not supported.
-    ///
-    /// # Returns
-    ///
-    /// Returns a reference to the address of the verifying contract.
-    ///
-    pub fn verifying_contract(&self) -> Result<&Address, MomokaVerifierError> {
-        match self {
-            MomokaTransaction::CommentCreated(e) => Ok(&e
-                .chain_proofs
-                .this_publication
-                .typed_data
-                .domain
-                .verifying_contract),
-            MomokaTransaction::MirrorCreated(e) => Ok(&e
-                .chain_proofs
-                .this_publication
-                .typed_data
-                .domain
-                .verifying_contract),
-            MomokaTransaction::PostCreated(e) => Ok(&e
-                .chain_proofs
-                .this_publication
-                .typed_data
-                .domain
-                .verifying_contract),
-            _ => Err(MomokaVerifierError::InvalidTransactionType),
-        }
-    }
-
     /// Returns the transaction type associated with the `MomokaTransaction`.
     ///
     /// # Returns
@@ -645,111 +667,218 @@ impl MomokaTransaction {
         }
     }
 
-    /// Returns the collect module address specified in the typed data of the transaction.
+    /// Returns the publication id the submitter gave the publication.
     ///
-    /// # Errors
+    /// # Returns
     ///
-    /// Returns an error if the transaction type is not supported.
+    /// - `Ok(&String)`: The publication id.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn publication_id(&self) -> Result<&String, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(t) => Ok(&t.publication_id),
+            MomokaTransaction::MirrorCreated(t) => Ok(&t.publication_id),
+            MomokaTransaction::PostCreated(t) => Ok(&t.publication_id),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the timestamp of the block the publication was made against.
     ///
-    /// # Examples
+    /// # Returns
     ///
-    /// ```
-    /// use ethers::types::Address;
-    /// use crate::MomokaVerifierError;
-    ///
-    /// let tx = MomokaTransaction::PostCreated(PostCreatedPublication {
-    ///     event: PostCreatedEventEmittedResponse {
-    ///         profile_id: "profile_id".to_string(),
-    ///         pub_id: "pub_id".to_string(),
-    ///         title: "title".to_string(),
-    ///         author: "author".to_string(),
-    ///         timestamp: 123456789,
-    ///         data_type: "data_type".to_string(),
-    ///     },
-    ///     chain_proofs: PublicationChainData {
-    ///         this_publication: PublicationProofData {
-    ///             typed_data: EIP712TypedData {
-    ///                 domain: EIP712TypedDataDomain {
-    ///                     name: "name".to_string(),
-    ///                     version: "version".to_string(),
-    ///                     chain_id: 1.into(),
-    ///                     verifying_contract: Address::zero(),
-    ///                 },
-    ///                 types: EIP712Types::default(),
-    ///                 primary_type: "primary_type".to_string(),
-    ///                 message: CreatePostEIP712TypedDataValue {
-    ///                     author: "author".to_string(),
-    ///                     title: "title".to_string(),
-    ///                     data_type: "data_type".to_string(),
-    ///                     profile_id: "profile_id".to_string(),
-    ///                     pub_id: "pub_id".to_string(),
-    ///                     deadline: 123456789,
-    ///                 },
-    ///             },
-    ///             block_timestamp: 123456789,
-    ///             block_number: 1.into(),
-    ///         },
-    ///         pointer: None,
-    ///     },
-    ///     timestamp_proofs: TimestampResponse {
-    ///         publisher: "publisher".to_string(),
-    ///         data_availability_id: "data_availability_id".to_string(),
-    ///         response: TimestampProof {
-    ///             timestamp: 123456789,
-    ///             signature: "signature".to_string(),
-    ///         },
-    ///     },
-    ///     data_availability_id: "data_availability_id".to_string(),
-    /// });
+    /// - `Ok(&u64)`: The block timestamp in the chain proofs.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn block_timestamp(&self) -> Result<&u64, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => {
+                Ok(&e.chain_proofs.this_publication.block_timestamp)
+            }
+            MomokaTransaction::MirrorCreated(e) => {
+                Ok(&e.chain_proofs.this_publication.block_timestamp)
+            }
+            MomokaTransaction::PostCreated(e) => {
+                Ok(&e.chain_proofs.this_publication.block_timestamp)
+            }
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the timestamp in the emitted event of the publication.
     ///
-    /// let result = tx.typed_data_collect_module();
+    /// # Returns
     ///
-    /// assert_eq!(result.unwrap(), &Address::zero());
-    /// ```
-    pub fn typed_data_collect_module(&self) -> Result<Option<&Address>, MomokaVerifierError> {
+    /// - `Ok(&u64)`: The event timestamp.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn event_timestamp(&self) -> Result<&u64, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => Ok(&e.event.timestamp),
+            MomokaTransaction::MirrorCreated(e) => Ok(&e.event.timestamp),
+            MomokaTransaction::PostCreated(e) => Ok(&e.event.timestamp),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the content URI in the typed data of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(&String))`: The content URI of a post or comment.
+    /// - `Ok(None)`: If the transaction is a mirror, which has no content of its own.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn content_uri(&self) -> Result<Option<&String>, MomokaVerifierError> {
         match self {
-            MomokaTransaction::CommentCreated(e) => Ok(Some(
-                &e.chain_proofs
-                    .this_publication
-                    .typed_data
-                    .value
-                    .collect_module,
-            )),
+            MomokaTransaction::CommentCreated(e) => Ok(Some(&e.typed_data().value.content_uri)),
             MomokaTransaction::MirrorCreated(_) => Ok(None),
-            MomokaTransaction::PostCreated(e) => Ok(Some(
-                &e.chain_proofs
-                    .this_publication
-                    .typed_data
-                    .value
-                    .collect_module,
-            )),
+            MomokaTransaction::PostCreated(e) => Ok(Some(&e.typed_data_value().content_uri)),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the profile id which made the publication.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(&ProfileId)`: The profile id in the typed data.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn profile_id(&self) -> Result<&ProfileId, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => Ok(e.profile_id()),
+            MomokaTransaction::MirrorCreated(e) => Ok(e.profile_id()),
+            MomokaTransaction::PostCreated(e) => Ok(e.profile_id()),
             _ => Err(MomokaVerifierError::InvalidTransactionType),
         }
     }
 
-    pub fn get_inner_object(&self) -> Result<JsonValue, MomokaVerifierError> {
+    /// Returns the profile id of the publication which is commented on or mirrored.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(&ProfileId))`: The pointed profile id of a comment or mirror.
+    /// - `Ok(None)`: If the transaction is a post, which does not point to a publication.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn profile_id_pointed(&self) -> Result<Option<&ProfileId>, MomokaVerifierError> {
         match self {
-            MomokaTransaction::CommentCreated(_) => {
-                let serialized = serde_json::to_string(self)
-                    .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
-                let parsed = json::parse(&serialized)
-                    .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
-                Ok(parsed["CommentCreated"].clone())
+            MomokaTransaction::CommentCreated(e) => {
+                Ok(Some(&e.typed_data().value.profile_id_pointed))
             }
-            MomokaTransaction::MirrorCreated(_) => {
-                let serialized = serde_json::to_string(self)
-                    .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
-                let parsed = json::parse(&serialized)
-                    .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
-                Ok(parsed["MirrorCreated"].clone())
+            MomokaTransaction::MirrorCreated(e) => {
+                Ok(Some(&e.typed_data().value.profile_id_pointed))
             }
-            MomokaTransaction::PostCreated(_) => {
-                let serialized = serde_json::to_string(self)
-                    .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
-                let parsed = json::parse(&serialized)
-                    .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
-                Ok(parsed["PostCreated"].clone())
+            MomokaTransaction::PostCreated(_) => Ok(None),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the publication id of the publication which is commented on or mirrored.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(&PublicationId))`: The pointed publication id of a comment or mirror.
+    /// - `Ok(None)`: If the transaction is a post, which does not point to a publication.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn pub_id_pointed(&self) -> Result<Option<&PublicationId>, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => Ok(Some(&e.typed_data().value.pub_id_pointed)),
+            MomokaTransaction::MirrorCreated(e) => Ok(Some(&e.typed_data().value.pub_id_pointed)),
+            MomokaTransaction::PostCreated(_) => Ok(None),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the collect module in the typed data of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(&Address))`: The collect module of a post or comment.
+    /// - `Ok(None)`: If the transaction is a mirror, which can not be collected.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn collect_module(&self) -> Result<Option<&Address>, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => Ok(Some(&e.typed_data().value.collect_module)),
+            MomokaTransaction::MirrorCreated(_) => Ok(None),
+            MomokaTransaction::PostCreated(e) => Ok(Some(&e.typed_data_value().collect_module)),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the reference module in the typed data of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(&Address)`: The reference module.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn reference_module(&self) -> Result<&Address, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => Ok(&e.typed_data().value.reference_module),
+            MomokaTransaction::MirrorCreated(e) => Ok(&e.typed_data().value.reference_module),
+            MomokaTransaction::PostCreated(e) => Ok(&e.typed_data_value().reference_module),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the signature nonce in the typed data of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(&u64)`: The typed data nonce.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn typed_data_nonce(&self) -> Result<&u64, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => Ok(e.nonce()),
+            MomokaTransaction::MirrorCreated(e) => Ok(e.nonce()),
+            MomokaTransaction::PostCreated(e) => Ok(&e.typed_data_value().nonce),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the signature deadline in the typed data of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(&u64)`: The typed data deadline.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn typed_data_deadline(&self) -> Result<&u64, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => Ok(&e.typed_data().value.deadline),
+            MomokaTransaction::MirrorCreated(e) => Ok(&e.typed_data().value.deadline),
+            MomokaTransaction::PostCreated(e) => Ok(&e.typed_data_value().deadline),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Returns the typed data signature of the transaction, this is the signature which could be executed on-chain.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(&str)`: The typed data signature.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid.
+    pub fn typed_data_signature(&self) -> Result<&str, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => Ok(e.signature()),
+            MomokaTransaction::MirrorCreated(e) => Ok(e.signature()),
+            MomokaTransaction::PostCreated(e) => Ok(&e.chain_proofs.this_publication.signature),
+            _ => Err(MomokaVerifierError::InvalidTransactionType),
+        }
+    }
+
+    /// Recovers the address which signed the typed data of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Address)`: The address of the typed data signer.
+    /// - `Err(MomokaVerifierError)`: If the transaction type is invalid or the signer can not be recovered.
+    pub fn typed_data_signer(&self) -> Result<Address, MomokaVerifierError> {
+        match self {
+            MomokaTransaction::CommentCreated(e) => {
+                comment::who_signed_typed_data(e.signature(), e.typed_data())
             }
+            MomokaTransaction::MirrorCreated(e) => {
+                mirror::who_signed_typed_data(e.signature(), e.typed_data())
+            }
+            MomokaTransaction::PostCreated(e) => post::who_signed_typed_data(
+                &e.chain_proofs.this_publication.signature,
+                &e.chain_proofs.this_publication.typed_data,
+            ),
             _ => Err(MomokaVerifierError::InvalidTransactionType),
         }
     }
@@ -782,6 +911,8 @@ pub struct TransactionSummary {
     pub submitter: Address,
     /// An optional `TimestampProofsResponse` object.
     pub timestamp_proofs_response: Option<TimestampProofsResponse>,
+    /// The owner of the timestamp proofs data item, if known.
+    pub timestamp_proofs_submitter: Option<Address>,
     /// The pointer if known!
     pub pointer_transaction_summary: Option<Box<TransactionSummary>>,
 }
@@ -792,6 +923,11 @@ impl TransactionSummary {
         self.timestamp_proofs_response = Some(response);
     }
 
+    /// Sets the owner of the timestamp proofs data item for the transaction summary.
+    pub fn set_timestamp_proofs_submitter(&mut self, submitter: Address) {
+        self.timestamp_proofs_submitter = Some(submitter);
+    }
+
     /// Sets the `TransactionSummary` for the pointer
     pub fn set_pointer_transaction_summary(&mut self, response: Box<TransactionSummary>) {
         self.pointer_transaction_summary = Some(response);
@@ -0,0 +1,145 @@
+use std::{collections::HashSet, str::FromStr};
+
+use ethers::types::U256;
+
+use super::transaction::{MomokaTransaction, TransactionAction};
+
+/// Which transactions are verified, anything else is skipped before it is checked.
+///
+/// Each filter which is set has to match, a filter matches if the transaction has any of its values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransactionFilter {
+    /// The profiles whose publications are verified, if filtered.
+    pub profile_ids: Option<HashSet<U256>>,
+    /// The publication types which are verified, if filtered.
+    pub actions: Option<HashSet<TransactionAction>>,
+    /// The profiles whose publications are commented on or mirrored, if filtered.
+    pub pointed_profile_ids: Option<HashSet<U256>>,
+}
+
+impl TransactionFilter {
+    /// Returns whether the transaction passes the filter and should be verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `momoka_tx` - The parsed transaction.
+    ///
+    /// # Returns
+    ///
+    /// `true` if every filter which is set matches the transaction. Posts never match a pointed profile filter.
+    pub fn matches(&self, momoka_tx: &MomokaTransaction) -> bool {
+        if let Some(profile_ids) = &self.profile_ids {
+            match momoka_tx.profile_id() {
+                Ok(profile_id) if profile_ids.contains(&profile_id.into()) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(actions) = &self.actions {
+            match momoka_tx.transaction_type() {
+                Ok(action) if actions.contains(action) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(pointed_profile_ids) = &self.pointed_profile_ids {
+            match momoka_tx.profile_id_pointed() {
+                Ok(Some(profile_id)) if pointed_profile_ids.contains(&profile_id.into()) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+/// Parses a profile id given as hex (`0x18`) or as a decimal number (`24`).
+///
+/// # Arguments
+///
+/// * `value` - The profile id.
+///
+/// # Returns
+///
+/// The profile id, or a message saying why it is not one.
+pub fn parse_profile_id(value: &str) -> Result<U256, String> {
+    let value = value.trim();
+    let profile_id = match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(value).ok(),
+    };
+
+    profile_id.ok_or_else(|| format!("{} is not a profile id", value))
+}
+
+impl FromStr for TransactionAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "POST_CREATED" => Ok(TransactionAction::PostCreated),
+            "COMMENT_CREATED" => Ok(TransactionAction::CommentCreated),
+            "MIRROR_CREATED" => Ok(TransactionAction::MirrorCreated),
+            _ => Err(format!(
+                "{} is not one of POST_CREATED, COMMENT_CREATED or MIRROR_CREATED",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::transaction::MomokaTransactionName;
+
+    fn comment() -> MomokaTransaction {
+        MomokaTransaction::from_json(
+            include_str!("../verifier/fixtures/comment-created-delegate-arweave-response.json"),
+            &MomokaTransactionName::CommentCreated,
+        )
+        .unwrap()
+    }
+
+    fn post() -> MomokaTransaction {
+        MomokaTransaction::from_json(
+            include_str!("../verifier/fixtures/post-created-delegate-arweave-response.json"),
+            &MomokaTransactionName::PostCreated,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_profile_id() {
+        assert_eq!(parse_profile_id("0x18"), Ok(U256::from(24)));
+        assert_eq!(parse_profile_id("24"), Ok(U256::from(24)));
+        assert!(parse_profile_id("0xzz").is_err());
+        assert!(parse_profile_id("abc").is_err());
+    }
+
+    #[test]
+    fn test_matches_every_filter_set() {
+        assert!(TransactionFilter::default().matches(&post()));
+
+        let filter = TransactionFilter {
+            profile_ids: Some(HashSet::from([U256::from(24)])),
+            actions: Some(HashSet::from([TransactionAction::CommentCreated])),
+            pointed_profile_ids: None,
+        };
+        assert!(filter.matches(&comment()));
+        assert!(!filter.matches(&post()));
+
+        let filter = TransactionFilter {
+            profile_ids: Some(HashSet::from([U256::from(1)])),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&comment()));
+
+        let filter = TransactionFilter {
+            pointed_profile_ids: Some(HashSet::from([U256::from(24)])),
+            ..Default::default()
+        };
+        assert!(filter.matches(&comment()));
+        assert!(!filter.matches(&post()));
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use super::{content::ContentStatus, transaction::MomokaTxId, verifier_error::MomokaVerifierError};
+
+/// A check run while verifying a transaction, in the order they are run.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum VerificationCheck {
+    /// The submitter signature recovers to a submitter of the environment.
+    SubmitterRecovery,
+    /// The publication id is the one generated from the event and DA id.
+    PublicationId,
+    /// The timestamp proofs were uploaded by a submitter of the environment.
+    TimestampProofsSubmitter,
+    /// The event timestamp is the block timestamp.
+    EventTimestamp,
+    /// The typed data deadline is the block timestamp.
+    TypedDataDeadline,
+    /// The block is the closest block to the timestamp proofs.
+    ClosestBlock,
+    /// The timestamp proofs are valid and for this transaction.
+    TimestampProofs,
+    /// The profile nonce and signer allow the comment or mirror.
+    ProfileDetails,
+    /// The collect and reference modules were whitelisted at the publication block.
+    ModulesWhitelisted,
+    /// The post simulation creates the expected publication.
+    Simulation,
+    /// The emitted event matches the typed data and the chain state.
+    EventCrossCheck,
+}
+
+/// The outcome of a check.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+}
+
+/// A check which was run with what it expected and what it found.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckEntry {
+    pub check: VerificationCheck,
+    pub status: CheckStatus,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    pub error: Option<MomokaVerifierError>,
+}
+
+/// Every check run while verifying a transaction, stopping at the first which failed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    pub tx_id: MomokaTxId,
+    pub checks: Vec<CheckEntry>,
+    /// The status of the publication content, if content checks are enabled and the transaction is valid.
+    pub content_status: Option<ContentStatus>,
+}
+
+impl VerificationReport {
+    /// Creates an empty report for a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_id` - The transaction being verified.
+    pub fn new(tx_id: MomokaTxId) -> Self {
+        VerificationReport {
+            tx_id,
+            checks: vec![],
+            content_status: None,
+        }
+    }
+
+    /// Records the outcome of a check and passes the result through.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - The check which was run.
+    /// * `expected` - The value the check expected, if it compares values.
+    /// * `actual` - The value the check found, if it compares values.
+    /// * `result` - The outcome of the check.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of the check, so it can be returned with `?`.
+    pub fn record<T>(
+        &mut self,
+        check: VerificationCheck,
+        expected: Option<String>,
+        actual: Option<String>,
+        result: Result<T, MomokaVerifierError>,
+    ) -> Result<T, MomokaVerifierError> {
+        self.checks.push(CheckEntry {
+            check,
+            status: if result.is_ok() {
+                CheckStatus::Passed
+            } else {
+                CheckStatus::Failed
+            },
+            expected,
+            actual,
+            error: result.as_ref().err().cloned(),
+        });
+
+        result
+    }
+
+    /// Returns the error of the check which failed, if any did.
+    pub fn error(&self) -> Option<&MomokaVerifierError> {
+        self.checks.iter().find_map(|entry| entry.error.as_ref())
+    }
+}
+
+impl Display for VerificationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "Verification report for {}", self.tx_id)?;
+        for entry in &self.checks {
+            write!(f, "  {:<6} {:<24}", entry.status, entry.check)?;
+            if let Some(expected) = &entry.expected {
+                write!(f, " expected: {}", expected)?;
+            }
+            if let Some(actual) = &entry.actual {
+                write!(f, " actual: {}", actual)?;
+            }
+            if let Some(error) = &entry.error {
+                write!(f, " - {}", error)?;
+            }
+            writeln!(f)?;
+        }
+        if let Some(content_status) = &self.content_status {
+            writeln!(f, "  Content {}", content_status)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_records_checks_in_order() {
+        let mut report = VerificationReport::new("tx".to_string());
+        let not_closest_block = MomokaVerifierError::NotClosestBlock {
+            chosen: Some(10),
+            closest: Some(12),
+        };
+
+        assert_eq!(
+            report.record(
+                VerificationCheck::EventTimestamp,
+                Some("1".to_string()),
+                Some("1".to_string()),
+                Ok(())
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            report.record::<()>(
+                VerificationCheck::ClosestBlock,
+                Some("10".to_string()),
+                Some("12".to_string()),
+                Err(not_closest_block.clone())
+            ),
+            Err(not_closest_block.clone())
+        );
+
+        assert_eq!(report.checks[0].status, CheckStatus::Passed);
+        assert_eq!(report.checks[1].status, CheckStatus::Failed);
+        assert_eq!(report.error(), Some(&not_closest_block));
+        assert_eq!(
+            report.to_string().lines().nth(2).unwrap(),
+            "  Failed ClosestBlock             expected: 10 actual: 12 - NotClosestBlock (chose block 10 but the closest block is 12)"
+        );
+    }
+}
@@ -1,54 +1,138 @@
-use serde::{Deserialize, Serialize};
+use ethers::types::{Address, U256};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     error::Error,
-    fmt::{Display, Formatter, Result},
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    str::FromStr,
+    sync::Arc,
 };
-use strum_macros::EnumString;
+use strum_macros::{EnumString, IntoStaticStr};
 
-#[derive(Debug, EnumString, Clone, PartialEq, Eq, Deserialize, Serialize)]
+/// The underlying error a `MomokaVerifierError` was raised from, if there was one.
+///
+/// The source only explains the failure, it is not part of the verdict, so two errors which
+/// only differ in their source are equal (nodes answering with differently worded errors still agree).
+#[derive(Debug, Clone, Default)]
+pub struct ErrorSource(Option<Arc<dyn Error + Send + Sync>>);
+
+impl<E: Error + Send + Sync + 'static> From<E> for ErrorSource {
+    fn from(error: E) -> Self {
+        ErrorSource(Some(Arc::new(error)))
+    }
+}
+
+impl ErrorSource {
+    /// Creates a source from the message of an error which can not be kept itself,
+    /// for example because it borrows the provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The error to keep the message of.
+    pub fn message(message: impl Display) -> Self {
+        ErrorSource(Some(Arc::new(SourceMessage(message.to_string()))))
+    }
+}
+
+/// An error only known by its message.
+#[derive(Debug)]
+struct SourceMessage(String);
+
+impl Display for SourceMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for SourceMessage {}
+
+impl PartialEq for ErrorSource {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ErrorSource {}
+
+/// The error raised when a transaction fails verification or can not be verified.
+///
+/// Variants carry the values the failed check compared and the error they were raised from.
+/// They are (de)serialized as their bare variant name, the context is only kept in memory.
+#[derive(Debug, EnumString, IntoStaticStr, Clone, PartialEq, Eq)]
 pub enum MomokaVerifierError {
     /// This means it has an invalid transaction type
     InvalidTransactionType,
     /// This means it has an invalid transaction format and could not parse it (missing data from the object)
-    InvalidTransactionFormat,
+    InvalidTransactionFormat {
+        source: ErrorSource,
+    },
     /// This means the main signature has not been signed by the same payload as the data itself
-    InvalidSignatureSubmitter,
+    InvalidSignatureSubmitter {
+        recovered: Option<Address>,
+        /// The submitters of the environment, one of which should have signed it.
+        expected: Vec<Address>,
+        source: ErrorSource,
+    },
     /// This means the submitted timestamp proof does not have a valid timestamp proof signature
-    TimestampProofInvalidSignature,
+    TimestampProofInvalidSignature {
+        source: ErrorSource,
+    },
     /// This means the type in the timestamp proofs do not match timestamp proofs are not portable
     TimestampProofInvalidType,
     /// This means the da id in the timestamp proofs do not match up timestamp proofs are not portable
     TimestampProofInvalidDAID,
     /// This means the timestamp proof uploaded was not done by a valid submitter
-    TimestampProofNotSubmitter,
+    TimestampProofNotSubmitter {
+        submitter: Address,
+    },
     /// We tried to call them 5 times and its errored out - this is not a bad proof but bundlr/arweave are having issues
-    CannotConnectToBundlr,
+    CannotConnectToBundlr {
+        source: ErrorSource,
+    },
     /// The DA tx could not be found or invalid on the bundlr/arweave nodes can happened if pasted it in wrong
-    InvalidTxID, // NOT USED!
+    InvalidTxID,
     /// This the typed data format is invalid (aka a invalid address type etc)
-    InvalidFormattedTypedData,
+    InvalidFormattedTypedData {
+        source: ErrorSource,
+    },
     /// This means it can not read the block from the node
-    BlockCantBeReadFromNode,
+    BlockCantBeReadFromNode {
+        source: ErrorSource,
+    },
     /// This means it can not read the data from the node
-    DataCantBeReadFromNode,
+    DataCantBeReadFromNode {
+        source: ErrorSource,
+    },
     /// This means the simulation was not able to be ran on the node, this does not mean that it would fail on chain, it means the nodes may of been down and needs rechecking
-    SimulationNodeCouldNotRun,
+    SimulationNodeCouldNotRun {
+        source: ErrorSource,
+    },
     /// This means the simulation was not successful and got rejected on-chain or the result from the simulation did not match the expected result
-    SimulationFailed,
+    SimulationFailed {
+        source: ErrorSource,
+    },
     /// This means the event emitted from the simulation does not match the expected event
-    EventMismatch,
+    EventMismatch {
+        expected_pub_id: Option<U256>,
+        actual_pub_id: Option<U256>,
+    },
     /// This means the event timestamp passed into the emitted event does not match the signature timestamp
     InvalidEventTimestamp,
     /// This means the deadline set in the typed data is not correct
     InvalidTypedDataDeadlineTimestamp,
     /// This means the generated publication id for the generic id does not match what it should be
-    GeneratedPublicationIdMismatch,
+    GeneratedPublicationIdMismatch {
+        expected: String,
+        actual: String,
+    },
     /// This means the pointer set in the chain proofs is not required but set anyway
     InvalidPointerSetNotNeeded,
     /// This means the pointer has failed verification
     PointerFailedVerification,
     /// This means the block processed against is not the closest block to the timestamp proofs
-    NotClosestBlock,
+    NotClosestBlock {
+        chosen: Option<u64>,
+        closest: Option<u64>,
+    },
     /// This means the timestamp proofs are not close enough to the block
     BlockTooFar, // NOT USED!
     /// This means the publication submitted does not have a valid pointer and a pointer is required
@@ -56,9 +140,24 @@ pub enum MomokaVerifierError {
     /// Some publications (comment and mirror) for now can only be on another DA publication not on evm chain publications
     PublicationNoneDA,
     /// This means the publication nonce is invalid at the time of submission
-    PublicationNonceInvalid,
+    PublicationNonceInvalid {
+        expected: u64,
+        actual: u64,
+    },
     /// This means the publication submisson was signed by a wallet that is not allowed
-    PublicationSignerNotAllowed,
+    PublicationSignerNotAllowed {
+        signer: Address,
+        owner: Address,
+        dispatcher: Address,
+    },
+    /// This means the collect module set on the publication was not whitelisted on the LensHub at the publication block
+    CollectModuleNotWhitelisted {
+        module: Address,
+    },
+    /// This means the reference module set on the publication was not whitelisted on the LensHub at the publication block
+    ReferenceModuleNotWhitelisted {
+        module: Address,
+    },
     /// This means the evm signature has already been used Only really starts to be able to be properly used when many submitters
     ChainSignatureAlreadyUsed,
     /// This means the publication submisson could not pass potentional due to a reorg
@@ -67,16 +166,223 @@ pub enum MomokaVerifierError {
     CacheError,
     // bundlr could not find last transaction (most likely API down)
     NoLastTransactionFound,
+    /// We tried to call the arweave gateway 5 times and its errored out - this is not a bad proof but the gateway is having issues
+    CannotConnectToArweave {
+        source: ErrorSource,
+    },
+    /// This means the data item was not included on arweave by the deadline height promised in the timestamp proofs
+    NotSettledBeforeDeadline,
+    /// This means the ANS-104 data item signature does not match its owner, or it was not signed by an ethereum style wallet
+    DataItemInvalidSignature {
+        source: ErrorSource,
+    },
+    /// This means the nodes queried for a quorum did not agree on the result, this is not a verdict on the publication, the nodes need rechecking
+    NodesDisagree,
+}
+
+impl MomokaVerifierError {
+    /// Returns the bare name of the error, as it is serialized.
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+
+    /// Returns whether the error is a verdict on the transaction, rather than the verifier not being
+    /// able to reach bundlr, arweave or the nodes (which means the transaction needs rechecking).
+    pub fn is_verdict(&self) -> bool {
+        !matches!(
+            self,
+            MomokaVerifierError::CannotConnectToBundlr { .. }
+                | MomokaVerifierError::BlockCantBeReadFromNode { .. }
+                | MomokaVerifierError::DataCantBeReadFromNode { .. }
+                | MomokaVerifierError::SimulationNodeCouldNotRun { .. }
+                | MomokaVerifierError::CannotConnectToArweave { .. }
+                | MomokaVerifierError::NodesDisagree
+                | MomokaVerifierError::CacheError
+                | MomokaVerifierError::NoLastTransactionFound
+        )
+    }
+
+    /// Returns the error this error was raised from, if there was one.
+    fn source_error(&self) -> Option<&Arc<dyn Error + Send + Sync>> {
+        match self {
+            MomokaVerifierError::InvalidTransactionFormat { source }
+            | MomokaVerifierError::InvalidSignatureSubmitter { source, .. }
+            | MomokaVerifierError::TimestampProofInvalidSignature { source }
+            | MomokaVerifierError::CannotConnectToBundlr { source }
+            | MomokaVerifierError::InvalidFormattedTypedData { source }
+            | MomokaVerifierError::BlockCantBeReadFromNode { source }
+            | MomokaVerifierError::DataCantBeReadFromNode { source }
+            | MomokaVerifierError::SimulationNodeCouldNotRun { source }
+            | MomokaVerifierError::SimulationFailed { source }
+            | MomokaVerifierError::CannotConnectToArweave { source }
+            | MomokaVerifierError::DataItemInvalidSignature { source } => source.0.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Formats an optional value, or `unknown` if it is not known.
+fn or_unknown<T: Debug>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or("unknown".to_string(), |value| format!("{:?}", value))
 }
 
 impl Display for MomokaVerifierError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let error_name = self;
-        write!(f, "{}", error_name)
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}", self.name())?;
+
+        match self {
+            MomokaVerifierError::InvalidSignatureSubmitter {
+                recovered,
+                expected,
+                ..
+            } => match (recovered, expected.is_empty()) {
+                (Some(recovered), true) => write!(f, " (recovered signer {:?})", recovered)?,
+                (Some(recovered), false) => write!(
+                    f,
+                    " (recovered signer {:?} but expected one of {:?})",
+                    recovered, expected
+                )?,
+                (None, false) => write!(f, " (expected one of {:?})", expected)?,
+                (None, true) => {}
+            },
+            MomokaVerifierError::TimestampProofNotSubmitter { submitter } => {
+                write!(f, " (uploaded by {:?})", submitter)?
+            }
+            MomokaVerifierError::EventMismatch {
+                expected_pub_id: Some(expected),
+                actual_pub_id: Some(actual),
+            } => write!(f, " (expected pub id {} but got {})", expected, actual)?,
+            MomokaVerifierError::GeneratedPublicationIdMismatch { expected, actual } => {
+                write!(f, " (expected {} but got {})", expected, actual)?
+            }
+            MomokaVerifierError::NotClosestBlock { chosen, closest } => write!(
+                f,
+                " (chose block {} but the closest block is {})",
+                or_unknown(chosen),
+                or_unknown(closest)
+            )?,
+            MomokaVerifierError::PublicationNonceInvalid { expected, actual } => {
+                write!(f, " (expected nonce {} but got {})", expected, actual)?
+            }
+            MomokaVerifierError::PublicationSignerNotAllowed {
+                signer,
+                owner,
+                dispatcher,
+            } => write!(
+                f,
+                " (signed by {:?}, owner is {:?} and dispatcher is {:?})",
+                signer, owner, dispatcher
+            )?,
+            MomokaVerifierError::CollectModuleNotWhitelisted { module }
+            | MomokaVerifierError::ReferenceModuleNotWhitelisted { module } => {
+                write!(f, " (module {:?})", module)?
+            }
+            _ => {}
+        }
+
+        if let Some(source) = self.source_error() {
+            write!(f, ": {}", source)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for MomokaVerifierError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source_error()
+            .map(|source| source.as_ref() as &(dyn Error + 'static))
+    }
+}
+
+impl Serialize for MomokaVerifierError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
     }
 }
 
-impl Error for MomokaVerifierError {}
+impl<'de> Deserialize<'de> for MomokaVerifierError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        MomokaVerifierError::from_str(&name).map_err(de::Error::custom)
+    }
+}
 
-// Implement the Send trait for your error type
-unsafe impl Send for MomokaVerifierError {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_as_bare_variant_name() {
+        let error = MomokaVerifierError::NotClosestBlock {
+            chosen: Some(10),
+            closest: Some(12),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&error).unwrap(),
+            "\"NotClosestBlock\""
+        );
+        assert_eq!(
+            serde_json::from_str::<MomokaVerifierError>("\"NotClosestBlock\"").unwrap(),
+            MomokaVerifierError::NotClosestBlock {
+                chosen: None,
+                closest: None
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "NotClosestBlock (chose block 10 but the closest block is 12)"
+        );
+    }
+
+    #[test]
+    fn test_keeps_the_source_error() {
+        let error = MomokaVerifierError::DataCantBeReadFromNode {
+            source: serde_json::from_str::<u64>("x").unwrap_err().into(),
+        };
+
+        assert!(error.source().is_some());
+        assert!(error.to_string().starts_with("DataCantBeReadFromNode: "));
+        assert_eq!(
+            error,
+            MomokaVerifierError::DataCantBeReadFromNode {
+                source: ErrorSource::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_shows_the_expected_submitters() {
+        let submitter = Address::from_low_u64_be(1);
+        let recovered = Address::from_low_u64_be(2);
+
+        let error = MomokaVerifierError::InvalidSignatureSubmitter {
+            recovered: Some(recovered),
+            expected: vec![submitter],
+            source: ErrorSource::default(),
+        };
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "InvalidSignatureSubmitter (recovered signer {:?} but expected one of [{:?}])",
+                recovered, submitter
+            )
+        );
+
+        let error = MomokaVerifierError::InvalidSignatureSubmitter {
+            recovered: None,
+            expected: vec![submitter],
+            source: ErrorSource::message("invalid signature length"),
+        };
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "InvalidSignatureSubmitter (expected one of [{:?}]): invalid signature length",
+                submitter
+            )
+        );
+    }
+}
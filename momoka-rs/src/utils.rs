@@ -1,4 +1,9 @@
+use std::{fs, io, path::Path};
+
 use chrono::Utc;
+use tracing::error;
+
+use crate::{evm::ProviderContext, types::verifier_error::MomokaVerifierError};
 
 /// Gets the current UTC time as a formatted string.
 ///
@@ -13,3 +18,86 @@ pub fn get_current_utc_string() -> String {
 
     formatted_time
 }
+
+/// Writes a file to a temporary path and renames it into place, so a reader never sees a partial
+/// file and a shutdown mid-write leaves the previous file as it was.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file, its directory is created if it does not exist.
+/// * `contents` - The contents to write.
+///
+/// # Returns
+///
+/// The error writing failed with, the temporary file is removed if it did.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+
+    let result = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&temp_path, contents))
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+
+    result
+}
+
+/// Checks a file an earlier run wrote was written for the environment and deployment being verified.
+///
+/// # Arguments
+///
+/// * `file` - What the file is, for the error logged (for example `the checkpoint momoka.json`).
+/// * `environment` - The environment the file was written for.
+/// * `deployment` - The deployment the file was written for.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// `CacheError` if the file was written for another environment or deployment.
+pub fn check_same_environment(
+    file: &str,
+    environment: &str,
+    deployment: &str,
+    provider_context: &ProviderContext,
+) -> Result<(), MomokaVerifierError> {
+    if environment != format!("{:?}", provider_context.environment)
+        || deployment != format!("{:?}", provider_context.deployment)
+    {
+        error!(
+            "{} is of {} {} but {:?} {:?} is being verified, remove it to start over",
+            file,
+            environment,
+            deployment,
+            provider_context.environment,
+            provider_context.deployment
+        );
+        return Err(MomokaVerifierError::CacheError);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomically_replaces_the_file() {
+        let dir = std::env::temp_dir().join(format!("momoka-utils-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("nested").join("file.json");
+
+        write_atomically(&path, b"first").unwrap();
+        write_atomically(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        // the temporary files were renamed into place
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,666 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use ethers::{
+    abi::{AbiDecode, AbiEncode},
+    contract::{
+        multicall_contract::{Aggregate3Call, Aggregate3Return, Call3},
+        MULTICALL_ADDRESS,
+    },
+    types::{Address, Block, Bytes, H256, U256},
+};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::{
+    archive::ArchiveMode,
+    cache::{
+        extend_batched_reads_cache, read_batched_profile_details_cache,
+        read_transaction_archive_cache, remove_batched_reads_cache,
+    },
+    contracts::lens_hub::{
+        get_profile_details, GetDispatcherCall, GetPubCountCall, IsCollectModuleWhitelistedCall,
+        IsReferenceModuleWhitelistedCall, LensProfileDetails, OwnerOfCall, ProfileDetailsKey,
+        SigNoncesCall,
+    },
+    environment::environment_to_lens_hub_contract,
+    evm::{quorum_outcome, quorum_read, ProviderContext},
+    http::post_with_timeout,
+    types::{
+        transaction::{MomokaTransaction, TransactionSummary},
+        verifier_error::{ErrorSource, MomokaVerifierError},
+    },
+};
+
+use super::transactions::{comment, mirror};
+
+/// The most requests sent to a node in one JSON-RPC batch, most providers reject larger batches.
+const MAX_BATCH_SIZE: usize = 100;
+
+/// The page the reads of a whole run are cached under while recording or replaying an archive,
+/// so they are kept until the chain state is written.
+pub const RECORDED_READS_PAGE: u64 = 0;
+
+/// The page the next batched reads are cached under.
+static NEXT_PAGE: AtomicU64 = AtomicU64::new(RECORDED_READS_PAGE + 1);
+
+/// The batched reads of a page being verified, removed from the cache once it is dropped.
+///
+/// Each page has its own reads, so pages verified at the same time do not replace each other's.
+#[derive(Debug)]
+pub struct BatchedPage(u64);
+
+impl Drop for BatchedPage {
+    fn drop(&mut self) {
+        if self.0 != RECORDED_READS_PAGE {
+            remove_batched_reads_cache(self.0);
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRpcRequest {
+    jsonrpc: &'static str,
+    id: usize,
+    method: &'static str,
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    id: usize,
+    result: Option<Value>,
+}
+
+/// The reads a page of transactions needs from the nodes, ordered so the same page always
+/// sends the same requests.
+#[derive(Debug, Default)]
+struct PageReads {
+    block_numbers: BTreeSet<u64>,
+    profile_details: BTreeSet<ProfileDetailsKey>,
+}
+
+/// The reads a single node answered.
+#[derive(Debug, Default)]
+struct NodeReads {
+    blocks: HashMap<u64, Block<H256>>,
+    profile_details: HashMap<ProfileDetailsKey, LensProfileDetails>,
+}
+
+/// Collects the reads a transaction needs: the blocks around its block number for the closest
+/// block check, and for comments and mirrors the profile details of the signer.
+///
+/// # Arguments
+///
+/// * `transaction` - The transaction summary.
+/// * `reads` - The page reads to add to.
+fn collect_reads(transaction: &TransactionSummary, reads: &mut PageReads) {
+    let momoka_tx = &transaction.momoka_tx;
+
+    if let Ok(block_number) = momoka_tx.block_number() {
+        if let Some(previous) = block_number.checked_sub(1) {
+            reads.block_numbers.insert(previous);
+        }
+        reads.block_numbers.insert(*block_number);
+        reads.block_numbers.insert(block_number + 1);
+    }
+
+    let profile_details = match momoka_tx {
+        MomokaTransaction::CommentCreated(publication) => {
+            comment::who_signed_typed_data(publication.signature(), publication.typed_data())
+                .ok()
+                .map(|signer| comment::profile_details_key(publication, signer))
+        }
+        MomokaTransaction::MirrorCreated(publication) => {
+            mirror::who_signed_typed_data(publication.signature(), publication.typed_data())
+                .ok()
+                .map(|signer| mirror::profile_details_key(publication, signer))
+        }
+        MomokaTransaction::PostCreated(_) => None,
+    };
+
+    if let Some(key) = profile_details {
+        reads.profile_details.insert(key);
+    }
+}
+
+/// Builds one Multicall3 `aggregate3` call which reads the profile details of every profile at a block.
+///
+/// The calls are deduplicated, so profiles and signers which appear in many transactions are only read once.
+///
+/// # Arguments
+///
+/// * `lens_hub` - The address of the LensHub.
+/// * `keys` - The profile details to read at the block.
+///
+/// # Returns
+///
+/// The calldata of the multicall, and the calldata of each call in the order they are in the multicall.
+fn merged_multicall(lens_hub: Address, keys: &[ProfileDetailsKey]) -> (Bytes, Vec<Bytes>) {
+    let mut calls: Vec<Bytes> = vec![];
+    for key in keys {
+        let profile_id = key.profile_id;
+        let module_calls = [
+            key.collect_module
+                .map(|collect_module| IsCollectModuleWhitelistedCall { collect_module }.encode()),
+            key.reference_module.map(|reference_module| {
+                IsReferenceModuleWhitelistedCall { reference_module }.encode()
+            }),
+        ];
+
+        for call in [
+            SigNoncesCall(key.signer).encode(),
+            GetPubCountCall { profile_id }.encode(),
+            GetDispatcherCall { profile_id }.encode(),
+            OwnerOfCall {
+                token_id: profile_id,
+            }
+            .encode(),
+        ]
+        .into_iter()
+        .chain(module_calls.into_iter().flatten())
+        {
+            let call = Bytes::from(call);
+            if !calls.contains(&call) {
+                calls.push(call);
+            }
+        }
+    }
+
+    let multicall = Aggregate3Call {
+        calls: calls
+            .iter()
+            .map(|call_data| Call3 {
+                target: lens_hub,
+                allow_failure: true,
+                call_data: call_data.clone(),
+            })
+            .collect(),
+    };
+
+    (Bytes::from(multicall.encode()), calls)
+}
+
+/// Decodes the profile details of each profile from the result of a merged multicall.
+///
+/// Profiles which had any of their calls fail are left out, so they are read on their own later.
+///
+/// # Arguments
+///
+/// * `keys` - The profile details read at the block.
+/// * `calls` - The calldata of each call in the multicall.
+/// * `return_data` - The raw result of the multicall.
+///
+/// # Returns
+///
+/// The profile details which could be decoded.
+fn decode_merged_multicall(
+    keys: &[ProfileDetailsKey],
+    calls: &[Bytes],
+    return_data: &[u8],
+) -> HashMap<ProfileDetailsKey, LensProfileDetails> {
+    let results = match Aggregate3Return::decode(return_data) {
+        Ok(results) if results.return_data.len() == calls.len() => results.return_data,
+        _ => return HashMap::new(),
+    };
+
+    let result_of = |call: Vec<u8>| -> Option<Bytes> {
+        let index = calls.iter().position(|c| c.as_ref() == call.as_slice())?;
+        let result = &results[index];
+        result.success.then(|| result.return_data.clone())
+    };
+
+    keys.iter()
+        .filter_map(|key| {
+            let profile_id = key.profile_id;
+
+            let sig_nonce = U256::decode(result_of(SigNoncesCall(key.signer).encode())?).ok()?;
+            let current_publication_id =
+                U256::decode(result_of(GetPubCountCall { profile_id }.encode())?).ok()?;
+            let dispatcher_address =
+                Address::decode(result_of(GetDispatcherCall { profile_id }.encode())?).ok()?;
+            let owner_of_address = Address::decode(result_of(
+                OwnerOfCall {
+                    token_id: profile_id,
+                }
+                .encode(),
+            )?)
+            .ok()?;
+            let collect_module_whitelisted = match key.collect_module {
+                Some(collect_module) => bool::decode(result_of(
+                    IsCollectModuleWhitelistedCall { collect_module }.encode(),
+                )?)
+                .ok()?,
+                None => true,
+            };
+            let reference_module_whitelisted = match key.reference_module {
+                Some(reference_module) => bool::decode(result_of(
+                    IsReferenceModuleWhitelistedCall { reference_module }.encode(),
+                )?)
+                .ok()?,
+                None => true,
+            };
+
+            Some((
+                *key,
+                LensProfileDetails {
+                    sig_nonce,
+                    current_publication_id,
+                    dispatcher_address,
+                    owner_of_address,
+                    collect_module_whitelisted,
+                    reference_module_whitelisted,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Sends requests to a node as JSON-RPC batches.
+///
+/// # Arguments
+///
+/// * `node_url` - The URL of the node.
+/// * `requests` - The method and params of each request.
+///
+/// # Returns
+///
+/// The result of each request in order, `None` where the node answered with an error.
+async fn batch_request(
+    node_url: &str,
+    requests: Vec<(&'static str, Value)>,
+) -> Result<Vec<Option<Value>>, MomokaVerifierError> {
+    let mut results: Vec<Option<Value>> = vec![None; requests.len()];
+
+    let requests: Vec<JsonRpcRequest> = requests
+        .into_iter()
+        .enumerate()
+        .map(|(id, (method, params))| JsonRpcRequest {
+            jsonrpc: "2.0",
+            id,
+            method,
+            params,
+        })
+        .collect();
+
+    for batch in requests.chunks(MAX_BATCH_SIZE) {
+        let responses: Vec<JsonRpcResponse> = post_with_timeout(node_url, &batch)
+            .await
+            .map_err(|e| MomokaVerifierError::DataCantBeReadFromNode { source: e.into() })?;
+
+        for response in responses {
+            if let Some(result) = results.get_mut(response.id) {
+                *result = response.result;
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Reads every block and profile details a page needs from a node in JSON-RPC batches.
+///
+/// # Arguments
+///
+/// * `node_url` - The URL of the node.
+/// * `lens_hub` - The address of the LensHub.
+/// * `reads` - The reads the page needs.
+///
+/// # Returns
+///
+/// The reads the node answered.
+async fn read_from_node(
+    node_url: &str,
+    lens_hub: Address,
+    reads: &PageReads,
+) -> Result<NodeReads, MomokaVerifierError> {
+    let block_numbers: Vec<u64> = reads.block_numbers.iter().cloned().collect();
+
+    let mut profile_details_by_block: BTreeMap<u64, Vec<ProfileDetailsKey>> = BTreeMap::new();
+    for key in &reads.profile_details {
+        profile_details_by_block
+            .entry(key.block_number)
+            .or_default()
+            .push(*key);
+    }
+    let multicalls: Vec<(Vec<ProfileDetailsKey>, Bytes, Vec<Bytes>)> = profile_details_by_block
+        .into_values()
+        .map(|keys| {
+            let (data, calls) = merged_multicall(lens_hub, &keys);
+            (keys, data, calls)
+        })
+        .collect();
+
+    let mut requests: Vec<(&'static str, Value)> = block_numbers
+        .iter()
+        .map(|block_number| {
+            (
+                "eth_getBlockByNumber",
+                json!([format!("{:#x}", block_number), false]),
+            )
+        })
+        .collect();
+    requests.extend(multicalls.iter().map(|(keys, data, _)| {
+        (
+            "eth_call",
+            json!([
+                { "to": MULTICALL_ADDRESS, "data": data },
+                format!("{:#x}", keys[0].block_number)
+            ]),
+        )
+    }));
+
+    let results = batch_request(node_url, requests).await?;
+    let (block_results, multicall_results) = results.split_at(block_numbers.len());
+
+    let mut node_reads = NodeReads::default();
+
+    for (block_number, result) in block_numbers.iter().zip(block_results) {
+        if let Some(block) = result
+            .clone()
+            .and_then(|result| serde_json::from_value::<Block<H256>>(result).ok())
+        {
+            // only keep what the closest block check uses so the nodes can be compared
+            node_reads.blocks.insert(
+                *block_number,
+                Block {
+                    hash: block.hash,
+                    number: block.number,
+                    timestamp: block.timestamp,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    for ((keys, _, calls), result) in multicalls.iter().zip(multicall_results) {
+        if let Some(return_data) = result
+            .clone()
+            .and_then(|result| serde_json::from_value::<Bytes>(result).ok())
+        {
+            node_reads
+                .profile_details
+                .extend(decode_merged_multicall(keys, calls, &return_data));
+        }
+    }
+
+    Ok(node_reads)
+}
+
+/// Picks the reads a quorum of the nodes agreed on.
+///
+/// # Arguments
+///
+/// * `keys` - The reads to agree on.
+/// * `node_reads` - The reads from each node.
+/// * `quorum` - How many nodes have to agree on a read.
+///
+/// # Returns
+///
+/// The agreed reads, reads the nodes did not agree on are left out.
+fn agreed_reads<K, V>(
+    keys: impl Iterator<Item = K>,
+    node_reads: &[Option<&HashMap<K, V>>],
+    quorum: usize,
+) -> HashMap<K, V>
+where
+    K: std::hash::Hash + Eq + Clone,
+    V: PartialEq + Clone,
+{
+    keys.filter_map(|key| {
+        let outcomes = node_reads
+            .iter()
+            .map(|reads| {
+                reads.and_then(|reads| reads.get(&key).cloned()).ok_or(
+                    MomokaVerifierError::DataCantBeReadFromNode {
+                        source: ErrorSource::default(),
+                    },
+                )
+            })
+            .collect();
+
+        quorum_outcome(outcomes, quorum)
+            .ok()
+            .map(|value| (key, value))
+    })
+    .collect()
+}
+
+/// Batches the chain reads of a page of transactions across every node.
+///
+/// Rather than every transaction reading its three blocks and its own profile details multicall
+/// from every node, the reads of the whole page are deduplicated and sent to each node as
+/// JSON-RPC batches, with the profile details of each block merged into one Multicall3 call.
+/// The reads a quorum of the nodes agreed on are cached for the page, anything not agreed on
+/// is read by the transaction itself as before.
+///
+/// When recording an archive the reads are added to the ones of earlier pages so they can all be
+/// written to the chain state, and offline the recorded chain state is left in place.
+///
+/// # Arguments
+///
+/// * `transactions` - The transactions of the page, including any pointer transactions.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The batched page, which has to be kept until the page has been verified.
+pub async fn batch_page_reads(
+    transactions: &[&TransactionSummary],
+    provider_context: &ProviderContext,
+) -> BatchedPage {
+    if provider_context.offline {
+        return BatchedPage(RECORDED_READS_PAGE);
+    }
+
+    let mut reads = PageReads::default();
+    for transaction in transactions {
+        collect_reads(transaction, &mut reads);
+    }
+
+    let lens_hub = environment_to_lens_hub_contract(&provider_context.environment).unwrap();
+
+    let node_reads = join_all(
+        provider_context
+            .node_urls
+            .iter()
+            .map(|node_url| read_from_node(node_url, lens_hub, &reads)),
+    )
+    .await;
+
+    if node_reads.iter().any(|reads| reads.is_err()) {
+        warn!("Could not batch the chain reads on every node, reading per transaction");
+    }
+
+    let blocks = agreed_reads(
+        reads.block_numbers.iter().cloned(),
+        &node_reads
+            .iter()
+            .map(|reads| reads.as_ref().ok().map(|reads| &reads.blocks))
+            .collect::<Vec<_>>(),
+        provider_context.quorum,
+    );
+    let profile_details = agreed_reads(
+        reads.profile_details.iter().cloned(),
+        &node_reads
+            .iter()
+            .map(|reads| reads.as_ref().ok().map(|reads| &reads.profile_details))
+            .collect::<Vec<_>>(),
+        provider_context.quorum,
+    );
+
+    let page = if is_recording() {
+        RECORDED_READS_PAGE
+    } else {
+        NEXT_PAGE.fetch_add(1, Ordering::Relaxed)
+    };
+    extend_batched_reads_cache(page, blocks, profile_details);
+
+    BatchedPage(page)
+}
+
+/// Returns true if the agreed chain reads are being recorded to an archive.
+pub fn is_recording() -> bool {
+    read_transaction_archive_cache().is_some_and(|archive| archive.mode == ArchiveMode::Record)
+}
+
+/// Reads the profile details the quorum agreed on, from the batched page reads if they were batched.
+///
+/// # Arguments
+///
+/// * `key` - The profile, signer, block and modules to read the details for.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The agreed profile details, or the error the quorum agreed on.
+pub async fn read_profile_details(
+    key: &ProfileDetailsKey,
+    provider_context: &ProviderContext,
+) -> Result<LensProfileDetails, MomokaVerifierError> {
+    if let Some(profile_details) = read_batched_profile_details_cache(key) {
+        return Ok(profile_details);
+    }
+
+    let lens_hub = environment_to_lens_hub_contract(&provider_context.environment).unwrap();
+
+    let profile_details = quorum_read(provider_context, |node| {
+        get_profile_details(lens_hub, key, node)
+    })
+    .await?;
+
+    if is_recording() {
+        extend_batched_reads_cache(
+            RECORDED_READS_PAGE,
+            HashMap::new(),
+            HashMap::from([(*key, profile_details.clone())]),
+        );
+    }
+
+    Ok(profile_details)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::read_batched_block_cache;
+
+    #[test]
+    fn test_merged_multicall_round_trip() {
+        let lens_hub = Address::from_low_u64_be(1);
+        let signer = Address::from_low_u64_be(2);
+        let owner = Address::from_low_u64_be(3);
+        let collect_module = Address::from_low_u64_be(4);
+        let key = |profile_id: u64| ProfileDetailsKey {
+            profile_id: U256::from(profile_id),
+            signer,
+            block_number: 100,
+            collect_module: Some(collect_module),
+            reference_module: None,
+        };
+
+        // the same signer and module on two profiles are only read once
+        let keys = vec![key(1), key(2)];
+        let (_, calls) = merged_multicall(lens_hub, &keys);
+        assert_eq!(calls.len(), 8);
+
+        let sig_nonces = SigNoncesCall(signer).encode();
+        let pub_count = GetPubCountCall {
+            profile_id: U256::from(1),
+        }
+        .encode();
+        let owner_of = OwnerOfCall {
+            token_id: U256::from(1),
+        }
+        .encode();
+        let whitelisted = IsCollectModuleWhitelistedCall { collect_module }.encode();
+
+        let results = Aggregate3Return {
+            return_data: calls
+                .iter()
+                .map(|call| {
+                    let (success, return_data): (bool, Vec<u8>) = match call.to_vec() {
+                        call if call == sig_nonces => (true, U256::from(5).encode()),
+                        call if call == pub_count => (true, U256::from(10).encode()),
+                        call if call == owner_of => (true, owner.encode()),
+                        call if call == whitelisted => (true, true.encode()),
+                        // the second profile does not exist
+                        call if call[..4] == owner_of[..4] => (false, vec![]),
+                        _ => (true, Address::zero().encode()),
+                    };
+                    ethers::contract::multicall_contract::Result {
+                        success,
+                        return_data: return_data.into(),
+                    }
+                })
+                .collect(),
+        }
+        .encode();
+
+        let profile_details = decode_merged_multicall(&keys, &calls, &results);
+
+        assert_eq!(profile_details.len(), 1);
+        assert_eq!(
+            profile_details[&keys[0]],
+            LensProfileDetails {
+                sig_nonce: U256::from(5),
+                current_publication_id: U256::from(10),
+                dispatcher_address: Address::zero(),
+                owner_of_address: owner,
+                collect_module_whitelisted: true,
+                reference_module_whitelisted: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_agreed_reads_leaves_out_disagreements() {
+        let first: HashMap<u64, u64> = HashMap::from([(1, 10), (2, 20)]);
+        let second: HashMap<u64, u64> = HashMap::from([(1, 10), (2, 21)]);
+
+        let agreed = agreed_reads(
+            [1u64, 2, 3].into_iter(),
+            &[Some(&first), Some(&second), None],
+            2,
+        );
+
+        assert_eq!(agreed, HashMap::from([(1, 10)]));
+    }
+
+    #[test]
+    fn test_batched_pages_keep_their_own_reads() {
+        let block = |number: u64| Block::<H256> {
+            number: Some(number.into()),
+            ..Default::default()
+        };
+
+        // two resync partitions verifying a page each
+        let first = BatchedPage(NEXT_PAGE.fetch_add(1, Ordering::Relaxed));
+        let second = BatchedPage(NEXT_PAGE.fetch_add(1, Ordering::Relaxed));
+        extend_batched_reads_cache(
+            first.0,
+            HashMap::from([(1_000_001, block(1_000_001))]),
+            HashMap::new(),
+        );
+        extend_batched_reads_cache(
+            second.0,
+            HashMap::from([(2_000_001, block(2_000_001))]),
+            HashMap::new(),
+        );
+
+        assert!(read_batched_block_cache(1_000_001).is_some());
+        assert!(read_batched_block_cache(2_000_001).is_some());
+
+        // the first page finishing does not drop the reads of the second
+        drop(first);
+        assert!(read_batched_block_cache(1_000_001).is_none());
+        assert!(read_batched_block_cache(2_000_001).is_some());
+
+        drop(second);
+        assert!(read_batched_block_cache(2_000_001).is_none());
+    }
+}
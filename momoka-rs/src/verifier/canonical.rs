@@ -0,0 +1,183 @@
+use std::fmt;
+
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use serde_json::value::RawValue;
+
+use crate::types::verifier_error::MomokaVerifierError;
+
+/// The top level fields of a JSON object in the order they were received, each value kept as
+/// the exact text it was received as.
+struct ReceivedFields<'a>(Vec<(String, &'a RawValue)>);
+
+impl<'de: 'a, 'a> Deserialize<'de> for ReceivedFields<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = ReceivedFields<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut fields = Vec::new();
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+
+                Ok(ReceivedFields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+/// Returns the exact message the submitter signed for a publication.
+///
+/// momoka-node deletes the submitter `signature` from the publication and signs
+/// `JSON.stringify` of what is left, so this removes only that key from the publication as it
+/// was received and writes the rest back in the same key order, with every value as the text it
+/// was received as. Nothing is rebuilt from the parsed publication, so fields this client does
+/// not know about are signed too.
+///
+/// # Arguments
+///
+/// * `received` - The publication JSON as it was received from bundlr.
+///
+/// # Returns
+///
+/// The message the submitter signed, or `InvalidTransactionFormat` if the publication is not a JSON object.
+pub fn signed_payload(received: &str) -> Result<String, MomokaVerifierError> {
+    let ReceivedFields(fields) = serde_json::from_str(received)
+        .map_err(|e| MomokaVerifierError::InvalidTransactionFormat { source: e.into() })?;
+
+    let fields = fields
+        .into_iter()
+        .filter(|(key, _)| key != "signature")
+        // serde_json escapes strings with the same rules as `JSON.stringify`
+        .map(|(key, value)| format!("{}:{}", serde_json::to_string(&key).unwrap(), value.get()))
+        .collect::<Vec<_>>();
+
+    Ok(format!("{{{}}}", fields.join(",")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Address, Signature};
+    use std::str::FromStr;
+
+    /// The address which signed the momoka-node mock publications.
+    const MOCK_SUBMITTER: &str = "0x82478df5a281a486070c11ebf808d3dd874fda86";
+
+    /// The momoka-node mock publications, as `JSON.stringify` outputs them, next to the
+    /// exact message momoka-node signs for them.
+    const GOLDEN: [(&str, &str); 7] = [
+        (
+            include_str!("fixtures/post-created-without-delegate-arweave-response.json"),
+            include_str!("fixtures/post-created-without-delegate-arweave-response.signed.json"),
+        ),
+        (
+            include_str!("fixtures/comment-created-delegate-arweave-response.json"),
+            include_str!("fixtures/comment-created-delegate-arweave-response.signed.json"),
+        ),
+        (
+            include_str!("fixtures/comment-created-without-delegate-arweave-response.json"),
+            include_str!("fixtures/comment-created-without-delegate-arweave-response.signed.json"),
+        ),
+        (
+            include_str!("fixtures/mirror-created-delegate-comment-arweave-response.json"),
+            include_str!("fixtures/mirror-created-delegate-comment-arweave-response.signed.json"),
+        ),
+        (
+            include_str!("fixtures/mirror-created-delegate-post-arweave-response.json"),
+            include_str!("fixtures/mirror-created-delegate-post-arweave-response.signed.json"),
+        ),
+        (
+            include_str!("fixtures/mirror-created-without-delegate-comment-arweave-response.json"),
+            include_str!(
+                "fixtures/mirror-created-without-delegate-comment-arweave-response.signed.json"
+            ),
+        ),
+        (
+            include_str!("fixtures/mirror-created-without-delegate-post-arweave-response.json"),
+            include_str!(
+                "fixtures/mirror-created-without-delegate-post-arweave-response.signed.json"
+            ),
+        ),
+    ];
+
+    fn recover(received: &str) -> Address {
+        let publication: serde_json::Value = serde_json::from_str(received).unwrap();
+        let signature = Signature::from_str(publication["signature"].as_str().unwrap()).unwrap();
+
+        signature
+            .recover(signed_payload(received).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_signed_payload_matches_momoka_node() {
+        for (received, signed) in GOLDEN {
+            assert_eq!(signed_payload(received).unwrap(), signed.trim_end());
+        }
+    }
+
+    #[test]
+    fn test_signed_payload_recovers_submitter() {
+        for (received, _) in GOLDEN {
+            assert_eq!(
+                recover(received),
+                Address::from_str(MOCK_SUBMITTER).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_signed_payload_rejects_reordered_publication() {
+        // the post delegate mock has `block` and `deadlineHeight` swapped from the order they
+        // were signed in, so it no longer gives the message momoka-node signed
+        let reordered = include_str!("fixtures/post-created-delegate-arweave-response.json");
+        let signed = include_str!("fixtures/post-created-delegate-arweave-response.signed.json");
+
+        assert_ne!(signed_payload(reordered).unwrap(), signed.trim_end());
+        assert_ne!(
+            recover(reordered),
+            Address::from_str(MOCK_SUBMITTER).unwrap()
+        );
+
+        // while the message momoka-node signed still recovers the submitter
+        let publication: serde_json::Value = serde_json::from_str(reordered).unwrap();
+        let signature = Signature::from_str(publication["signature"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            signature.recover(signed.trim_end()).unwrap(),
+            Address::from_str(MOCK_SUBMITTER).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_signed_payload_keeps_nested_key_order() {
+        let received = r#"{"b":{"z":1,"a":[{"y":null,"x":true}]},"signature":"0x","a":"1"}"#;
+
+        assert_eq!(
+            signed_payload(received).unwrap(),
+            r#"{"b":{"z":1,"a":[{"y":null,"x":true}]},"a":"1"}"#
+        );
+        assert!(signed_payload("[]").is_err());
+    }
+
+    #[test]
+    fn test_signed_payload_escapes_like_json_stringify() {
+        let received = r#"{"signature":"0x","content":"line\nbreak \"quoted\" \u0001 ü","empty":[],"pointer":null}"#;
+
+        assert_eq!(
+            signed_payload(received).unwrap(),
+            r#"{"content":"line\nbreak \"quoted\" \u0001 ü","empty":[],"pointer":null}"#
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::{HashMap, HashSet};
+
+use ethers::{
+    abi::AbiDecode,
+    contract::EthEvent,
+    providers::{Middleware, Provider, RetryClient},
+    types::{Address, BlockNumber, Filter, H256, U256},
+};
+use futures::{stream, StreamExt, TryStreamExt};
+use tracing::error;
+
+use crate::{
+    alert::{send_alert, Alert},
+    cache::{read_da_publication_cache, remove_da_publication_cache, set_da_publication_cache},
+    contracts::lens_hub::{
+        lens_hub_contract, CommentCreatedFilter, Eip712Signature, ILensHubCalls,
+        MirrorCreatedFilter, PostCreatedFilter,
+    },
+    environment::environment_to_lens_hub_contract,
+    evm::{parse_signature, ProviderContext, SigRequest},
+    export::export_double_publications,
+    output::{emit, OutputEvent},
+    transport::NodeTransport,
+    types::{
+        double_publication::{DaPublicationRecord, DoublePublication},
+        publication_id::PublicationId,
+        transaction::TransactionSummary,
+        verifier_error::{ErrorSource, MomokaVerifierError},
+    },
+    verifier::indexing::record_double_publication,
+};
+
+/// The most blocks after the DA publication block we search for the signature being executed,
+/// the typed data deadline is the block timestamp so it can not be executed much later.
+const DOUBLE_PUBLICATION_BLOCK_WINDOW: u64 = 64;
+
+/// How many nonces or publication events are read from the node at once.
+const DOUBLE_PUBLICATION_CONCURRENCY: usize = 10;
+
+/// Records a verified DA publication so the detector can watch for its typed data signature
+/// also being executed on the LensHub.
+///
+/// # Arguments
+///
+/// * `transaction` - The verified transaction summary.
+///
+/// # Returns
+///
+/// A `Result` indicating whether the publication could be recorded.
+pub fn record_da_publication(transaction: &TransactionSummary) -> Result<(), MomokaVerifierError> {
+    let momoka_tx = &transaction.momoka_tx;
+
+    set_da_publication_cache(DaPublicationRecord {
+        tx_id: transaction.id.clone(),
+        action: momoka_tx.transaction_type()?.clone(),
+        profile_id: momoka_tx.profile_id()?.clone(),
+        signer: momoka_tx.typed_data_signer()?,
+        nonce: *momoka_tx.typed_data_nonce()?,
+        deadline: *momoka_tx.typed_data_deadline()?,
+        signature: momoka_tx.typed_data_signature()?.to_owned(),
+        block_number: *momoka_tx.block_number()?,
+    });
+
+    Ok(())
+}
+
+/// Extracts the typed data signature from the calldata of a LensHub `*WithSig` call.
+///
+/// # Arguments
+///
+/// * `input` - The calldata of the on-chain transaction.
+///
+/// # Returns
+///
+/// The executed signature, or `None` if the calldata is not a post, comment or mirror with sig call.
+fn executed_signature(input: &[u8]) -> Option<Eip712Signature> {
+    match ILensHubCalls::decode(input).ok()? {
+        ILensHubCalls::PostWithSig(call) => Some(call.vars.sig),
+        ILensHubCalls::PostWithSigDispatcher(call) => Some(call.vars.sig),
+        ILensHubCalls::CommentWithSig(call) => Some(call.vars.sig),
+        ILensHubCalls::CommentWithSigDispatcher(call) => Some(call.vars.sig),
+        ILensHubCalls::MirrorWithSig(call) => Some(call.vars.sig),
+        ILensHubCalls::MirrorWithSigDispatcher(call) => Some(call.vars.sig),
+        _ => None,
+    }
+}
+
+/// Checks whether an executed on-chain signature is the same as the DA typed data signature.
+fn is_same_signature(executed: &Eip712Signature, signature: &SigRequest) -> bool {
+    executed.v == signature.v && executed.r == signature.r && executed.s == signature.s
+}
+
+/// Searches the LensHub publication events of the profile around the DA publication block
+/// for an on-chain transaction which executed the same typed data signature.
+///
+/// # Arguments
+///
+/// * `record` - The DA publication record.
+/// * `lens_hub` - The address of the LensHub.
+/// * `latest_block_number` - The latest block number on the node.
+/// * `provider` - The ethers provider.
+///
+/// # Returns
+///
+/// The double publication if the signature was executed on-chain, otherwise `None`.
+async fn find_executed_signature(
+    record: &DaPublicationRecord,
+    lens_hub: Address,
+    latest_block_number: u64,
+    provider: &Provider<RetryClient<NodeTransport>>,
+) -> Result<Option<DoublePublication>, MomokaVerifierError> {
+    let signature = parse_signature(&record.signature, record.deadline)?;
+
+    let mut profile_id = [0u8; 32];
+    U256::from(&record.profile_id).to_big_endian(&mut profile_id);
+
+    let filter = Filter::new()
+        .address(lens_hub)
+        .from_block(record.block_number)
+        .to_block(latest_block_number.min(record.block_number + DOUBLE_PUBLICATION_BLOCK_WINDOW))
+        .topic0(vec![
+            PostCreatedFilter::signature(),
+            CommentCreatedFilter::signature(),
+            MirrorCreatedFilter::signature(),
+        ])
+        .topic1(H256::from(profile_id));
+
+    let logs = provider
+        .get_logs(&filter)
+        .await
+        .map_err(|e| MomokaVerifierError::DataCantBeReadFromNode { source: e.into() })?;
+
+    for log in logs {
+        let (transaction_hash, block_number) = match (log.transaction_hash, log.block_number) {
+            (Some(transaction_hash), Some(block_number)) => (transaction_hash, block_number),
+            _ => continue,
+        };
+
+        let transaction = provider
+            .get_transaction(transaction_hash)
+            .await
+            .map_err(|e| MomokaVerifierError::DataCantBeReadFromNode { source: e.into() })?
+            .ok_or(MomokaVerifierError::DataCantBeReadFromNode {
+                source: ErrorSource::default(),
+            })?;
+
+        let executed = match executed_signature(&transaction.input) {
+            Some(executed) => executed,
+            None => continue,
+        };
+
+        if is_same_signature(&executed, &signature) {
+            let pub_id = log
+                .topics
+                .get(2)
+                .map(|topic| U256::from_big_endian(topic.as_bytes()))
+                .ok_or(MomokaVerifierError::DataCantBeReadFromNode {
+                    source: ErrorSource::default(),
+                })?;
+
+            return Ok(Some(DoublePublication {
+                tx_id: record.tx_id.clone(),
+                action: record.action.clone(),
+                profile_id: record.profile_id.clone(),
+                transaction_hash,
+                pub_id: PublicationId::new(pub_id),
+                block_number: block_number.as_u64(),
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Detects DA publications whose typed data signature was also executed on the LensHub.
+///
+/// Momoka publications do not consume `sigNonces`, so a signed `PostWithSig`, `CommentWithSig`
+/// or `MirrorWithSig` could also be submitted on-chain creating a duplicate publication. For every
+/// watched DA publication this checks whether the signer nonce has moved past the signed nonce,
+/// and if so searches the profile publication events for the transaction which executed the same
+/// signature. The nonces and the events are read concurrently, so a backlog of watched
+/// publications does not hold up following new transactions for long.
+///
+/// A DA publication stops being watched once its signature can no longer be executed, which is
+/// when the nonce has been used or the typed data deadline has passed.
+///
+/// # Arguments
+///
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The double publications found in this run.
+pub async fn detect_double_publications(
+    provider_context: &ProviderContext,
+) -> Result<Vec<DoublePublication>, MomokaVerifierError> {
+    let records = read_da_publication_cache();
+    if records.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let lens_hub_address = environment_to_lens_hub_contract(&provider_context.environment).unwrap();
+    let lens_hub = lens_hub_contract(lens_hub_address, &provider_context.node);
+
+    let latest_block = provider_context
+        .node
+        .get_block(BlockNumber::Latest)
+        .await
+        .map_err(|e| MomokaVerifierError::BlockCantBeReadFromNode { source: e.into() })?
+        .ok_or(MomokaVerifierError::BlockCantBeReadFromNode {
+            source: ErrorSource::default(),
+        })?;
+    let latest_block_number = latest_block
+        .number
+        .ok_or(MomokaVerifierError::BlockCantBeReadFromNode {
+            source: ErrorSource::default(),
+        })?
+        .as_u64();
+    let latest_block_timestamp = latest_block.timestamp.as_u64();
+
+    let signers: HashSet<Address> = records.iter().map(|record| record.signer).collect();
+    let nonces: HashMap<Address, u64> = stream::iter(signers)
+        .map(|signer| {
+            let lens_hub = &lens_hub;
+            async move {
+                let nonce = lens_hub.sig_nonces(signer).call().await.map_err(|e| {
+                    MomokaVerifierError::DataCantBeReadFromNode {
+                        source: ErrorSource::message(e),
+                    }
+                })?;
+                Ok::<_, MomokaVerifierError>((signer, nonce.as_u64()))
+            }
+        })
+        .buffer_unordered(DOUBLE_PUBLICATION_CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    // the signed nonce has been used on-chain, check if it was used by this signature
+    let nonce_used = |record: &DaPublicationRecord| nonces[&record.signer] > record.nonce;
+
+    let found: Vec<Option<DoublePublication>> = stream::iter(records.iter())
+        .map(|record| async move {
+            if !nonce_used(record) {
+                return Ok(None);
+            }
+
+            find_executed_signature(
+                record,
+                lens_hub_address,
+                latest_block_number,
+                &provider_context.node,
+            )
+            .await
+        })
+        .buffered(DOUBLE_PUBLICATION_CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    for record in &records {
+        if nonce_used(record) || latest_block_timestamp > record.deadline {
+            remove_da_publication_cache(&record.tx_id);
+        }
+    }
+
+    Ok(found.into_iter().flatten().collect())
+}
+
+/// Reports the double publications found, as an error log, an output event and an alert, and
+/// flags them in the trusted index and the export.
+///
+/// # Arguments
+///
+/// * `double_publications` - The double publications found.
+/// * `provider_context` - The provider context.
+pub async fn report_double_publications(
+    double_publications: &[DoublePublication],
+    provider_context: &ProviderContext,
+) {
+    for double_publication in double_publications {
+        error!(
+            "{:?} - DOUBLE PUBLICATION - also published on-chain as {}-{} in {:?}",
+            double_publication.tx_id,
+            double_publication.profile_id,
+            double_publication.pub_id,
+            double_publication.transaction_hash
+        );
+        emit(OutputEvent::DoublePublication(double_publication.clone()));
+        send_alert(Alert::double_publication(
+            double_publication,
+            provider_context,
+        ));
+        record_double_publication(double_publication);
+    }
+
+    export_double_publications(double_publications).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::lens_hub::{PostWithSigCall, PostWithSigData};
+    use ethers::abi::AbiEncode;
+
+    #[test]
+    fn test_executed_signature_matches_da_signature() {
+        let da_signature = format!("0x{}{}{}", "11".repeat(32), "22".repeat(32), "1b");
+        let signature = parse_signature(&da_signature, 1684234571).unwrap();
+
+        let input = PostWithSigCall {
+            vars: PostWithSigData {
+                profile_id: U256::from(1),
+                content_uri: "ar://content".to_string(),
+                collect_module: Address::zero(),
+                collect_module_init_data: Default::default(),
+                reference_module: Address::zero(),
+                reference_module_init_data: Default::default(),
+                sig: Eip712Signature {
+                    v: signature.v,
+                    r: signature.r,
+                    s: signature.s,
+                    deadline: U256::from(signature.deadline),
+                },
+            },
+        }
+        .encode();
+
+        let executed = executed_signature(&input).unwrap();
+        assert!(is_same_signature(&executed, &signature));
+
+        let other_signature = parse_signature(
+            &format!("0x{}{}{}", "33".repeat(32), "22".repeat(32), "1b"),
+            1684234571,
+        )
+        .unwrap();
+        assert!(!is_same_signature(&executed, &other_signature));
+
+        // not a with sig call
+        assert!(executed_signature(&[0u8; 4]).is_none());
+    }
+}
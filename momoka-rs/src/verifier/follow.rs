@@ -0,0 +1,147 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{
+    evm::ProviderContext,
+    types::verifier_error::MomokaVerifierError,
+    utils::{check_same_environment, write_atomically},
+    verifier::range::RangeSummary,
+};
+
+/// How far following new transactions got, written to disk after every page and on shutdown.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowCheckpoint {
+    /// The environment being followed.
+    pub environment: String,
+    /// The deployment being followed.
+    pub deployment: String,
+    /// The cursor of the last page which was fully verified, following resumes after it.
+    pub cursor: Option<String>,
+    /// What was found verifying the pages since the checkpoint was first written.
+    pub summary: RangeSummary,
+}
+
+impl FollowCheckpoint {
+    /// Starts a checkpoint following after the given cursor.
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The cursor following starts after.
+    /// * `provider_context` - The provider context.
+    pub fn new(cursor: Option<String>, provider_context: &ProviderContext) -> Self {
+        FollowCheckpoint {
+            environment: format!("{:?}", provider_context.environment),
+            deployment: format!("{:?}", provider_context.deployment),
+            cursor,
+            summary: RangeSummary::default(),
+        }
+    }
+
+    /// Records a page which was fully verified.
+    ///
+    /// # Arguments
+    ///
+    /// * `next` - The cursor of the page, following resumes after it.
+    /// * `listed` - How many transactions bundlr listed in the page.
+    /// * `results` - The result of each transaction of the page which was checked.
+    pub fn record_page(
+        &mut self,
+        next: Option<String>,
+        listed: usize,
+        results: &[Result<(), MomokaVerifierError>],
+    ) {
+        self.cursor = next;
+        self.summary.listed += listed;
+        for result in results {
+            // a transaction which could not be checked is not counted against the submitter
+            self.summary.record(result);
+        }
+    }
+}
+
+/// Reads the follow checkpoint from disk.
+///
+/// # Arguments
+///
+/// * `path` - The path of the checkpoint file.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The checkpoint, `None` if there is no checkpoint file or it can not be read, or `CacheError`
+/// if it was written following another environment or deployment.
+pub fn read_follow_checkpoint(
+    path: &str,
+    provider_context: &ProviderContext,
+) -> Result<Option<FollowCheckpoint>, MomokaVerifierError> {
+    let checkpoint: Option<FollowCheckpoint> = fs::read(path)
+        .ok()
+        .and_then(|contents| serde_json::from_slice(&contents).ok());
+
+    if let Some(checkpoint) = &checkpoint {
+        check_same_environment(
+            &format!("The checkpoint {}", path),
+            &checkpoint.environment,
+            &checkpoint.deployment,
+            provider_context,
+        )?;
+    }
+
+    Ok(checkpoint)
+}
+
+/// Writes the follow checkpoint to disk.
+///
+/// The file is written atomically so a shutdown mid-write never leaves a partial checkpoint.
+/// Failing to write is logged and ignored, the pages since the last checkpoint will just be
+/// verified again when resuming.
+///
+/// # Arguments
+///
+/// * `path` - The path of the checkpoint file.
+/// * `checkpoint` - The checkpoint to write.
+pub fn write_follow_checkpoint(path: &str, checkpoint: &FollowCheckpoint) {
+    if let Err(error) = write_atomically(Path::new(path), &serde_json::to_vec(checkpoint).unwrap())
+    {
+        warn!("Could not write the checkpoint - {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_page() {
+        let mut checkpoint = FollowCheckpoint {
+            environment: "Polygon".to_string(),
+            deployment: "Production".to_string(),
+            cursor: Some("a".to_string()),
+            summary: RangeSummary::default(),
+        };
+
+        checkpoint.record_page(
+            Some("b".to_string()),
+            4,
+            &[
+                Ok(()),
+                Err(MomokaVerifierError::PublicationNoPointer),
+                Err(MomokaVerifierError::NodesDisagree),
+            ],
+        );
+
+        assert_eq!(checkpoint.cursor, Some("b".to_string()));
+        assert_eq!(
+            checkpoint.summary,
+            RangeSummary {
+                listed: 4,
+                valid: 1,
+                invalid: 1,
+                unchecked: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use tracing::info;
+
+use crate::{
+    cache::{read_indexed_publication_cache, set_indexed_publication_cache},
+    output::{emit, OutputEvent},
+    types::{
+        double_publication::DoublePublication,
+        indexed_publication::{IndexedPublication, PublicationStatus},
+        transaction::{MomokaTxId, TransactionSummary},
+        verifier_error::MomokaVerifierError,
+    },
+};
+
+/// Indexes the publications of fetched transactions as unverified, so they can be shown before
+/// their proofs are checked.
+///
+/// Publications which were already indexed keep their status, a transaction fetched again
+/// (for example when it is rechecked) is not downgraded back to unverified.
+///
+/// # Arguments
+///
+/// * `transactions` - The transactions as fetched from bundlr.
+pub fn index_unverified_publications(transactions: &[TransactionSummary]) {
+    let mut indexed = 0;
+
+    for transaction in transactions {
+        if read_indexed_publication_cache(&transaction.id).is_some() {
+            continue;
+        }
+
+        // a transaction which is not a publication is reported invalid when its proof is checked
+        let Ok(publication) = IndexedPublication::unverified(transaction) else {
+            continue;
+        };
+
+        set_indexed_publication_cache(publication.clone());
+        emit(OutputEvent::PublicationIndexed(publication));
+        indexed += 1;
+    }
+
+    if indexed > 0 {
+        info!("Indexed {} publications as unverified", indexed);
+    }
+}
+
+/// Upgrades an indexed publication to verified or invalid once its proof was checked.
+///
+/// A publication whose proof could not be checked (for example because a node was unreachable)
+/// stays unverified until it is checked again.
+///
+/// # Arguments
+///
+/// * `tx_id` - The ID of the transaction.
+/// * `result` - The result of checking its proof.
+pub fn record_publication_verdict(tx_id: &MomokaTxId, result: &Result<(), MomokaVerifierError>) {
+    let Some(mut publication) = read_indexed_publication_cache(tx_id) else {
+        return;
+    };
+
+    let status = match result {
+        Ok(()) => PublicationStatus::Verified,
+        Err(error) if error.is_verdict() => PublicationStatus::Invalid,
+        Err(_) => return,
+    };
+
+    if publication.status == status {
+        return;
+    }
+
+    publication.status = status;
+    publication.error = result.as_ref().err().cloned();
+
+    set_indexed_publication_cache(publication.clone());
+    emit(OutputEvent::PublicationUpgraded(publication));
+}
+
+/// Flags an indexed publication which was also published on-chain.
+///
+/// The publication keeps its status, it was valid when it was verified.
+///
+/// # Arguments
+///
+/// * `double_publication` - The double publication found.
+pub fn record_double_publication(double_publication: &DoublePublication) {
+    let Some(mut publication) = read_indexed_publication_cache(&double_publication.tx_id) else {
+        return;
+    };
+
+    if publication.double_publication.as_ref() == Some(double_publication) {
+        return;
+    }
+
+    publication.double_publication = Some(double_publication.clone());
+
+    set_indexed_publication_cache(publication.clone());
+    emit(OutputEvent::PublicationUpgraded(publication));
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Address, H256, U256};
+
+    use super::*;
+    use crate::types::{
+        profile_id::ProfileId, publication_id::PublicationId, transaction::TransactionAction,
+        verifier_error::ErrorSource,
+    };
+
+    fn unverified_publication(tx_id: &str) -> IndexedPublication {
+        IndexedPublication {
+            tx_id: tx_id.to_string(),
+            action: TransactionAction::PostCreated,
+            profile_id: ProfileId::from(U256::one()),
+            publication_id: "0x01-0x01-DA-00000001".to_string(),
+            submitter: Address::zero(),
+            block_number: 1,
+            content_uri: Some("ar://content".to_string()),
+            pointer_tx_id: None,
+            status: PublicationStatus::Unverified,
+            error: None,
+            double_publication: None,
+        }
+    }
+
+    #[test]
+    fn test_record_publication_verdict_upgrades_status() {
+        let verified = "indexing-test-verified".to_string();
+        let invalid = "indexing-test-invalid".to_string();
+        let unchecked = "indexing-test-unchecked".to_string();
+        for tx_id in [&verified, &invalid, &unchecked] {
+            set_indexed_publication_cache(unverified_publication(tx_id));
+        }
+
+        record_publication_verdict(&verified, &Ok(()));
+        record_publication_verdict(&invalid, &Err(MomokaVerifierError::PublicationNoPointer));
+        record_publication_verdict(
+            &unchecked,
+            &Err(MomokaVerifierError::CannotConnectToBundlr {
+                source: ErrorSource::message("timed out"),
+            }),
+        );
+
+        let status = |tx_id| read_indexed_publication_cache(tx_id).unwrap().status;
+        assert_eq!(status(&verified), PublicationStatus::Verified);
+        assert_eq!(status(&invalid), PublicationStatus::Invalid);
+        assert_eq!(status(&unchecked), PublicationStatus::Unverified);
+        assert_eq!(
+            read_indexed_publication_cache(&invalid).unwrap().error,
+            Some(MomokaVerifierError::PublicationNoPointer)
+        );
+    }
+
+    #[test]
+    fn test_record_double_publication_flags_publication() {
+        let tx_id = "indexing-test-double-publication".to_string();
+        set_indexed_publication_cache(unverified_publication(&tx_id));
+        record_publication_verdict(&tx_id, &Ok(()));
+
+        let double_publication = DoublePublication {
+            tx_id: tx_id.clone(),
+            action: TransactionAction::PostCreated,
+            profile_id: ProfileId::from(U256::one()),
+            transaction_hash: H256::repeat_byte(1),
+            pub_id: PublicationId::new(U256::from(2)),
+            block_number: 3,
+        };
+        record_double_publication(&double_publication);
+
+        let publication = read_indexed_publication_cache(&tx_id).unwrap();
+        assert_eq!(publication.status, PublicationStatus::Verified);
+        assert_eq!(publication.double_publication, Some(double_publication));
+    }
+}
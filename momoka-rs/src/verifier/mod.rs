@@ -1,2 +1,11 @@
+pub mod batch;
+pub mod canonical;
+pub mod double_publication;
+pub mod follow;
+pub mod indexing;
 pub mod proof;
+pub mod range;
+pub mod report;
+pub mod resync;
+pub mod sampling;
 pub mod transactions;
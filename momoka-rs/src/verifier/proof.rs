@@ -1,30 +1,51 @@
-use json::JsonValue;
-use regex::Regex;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap},
+    future::Future,
     str::FromStr,
-    sync::Arc,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
+use tracing::{debug_span, error, info, warn, Instrument};
 
 use crate::{
+    alert::{is_alerting, send_alert, Alert},
+    arweave::verify::verify_settlements,
     bundlr::{
         api::{get_bulk_transactions_api, get_transaction_api},
         verify::verify_timestamp_proofs,
     },
     cache::{
-        read_signature_cache, read_transaction_cache, set_signature_cache, set_transaction_cache,
+        extend_batched_reads_cache, read_batched_block_cache, read_pending_settlement_cache,
+        read_signature_cache, read_transaction_cache, remove_pending_settlement_cache,
+        set_pending_settlement_cache, set_signature_cache, set_transaction_cache,
         TransactionCacheResult,
     },
-    evm::ProviderContext,
-    logger::Logger,
-    submitter::state::is_valid_submitter,
+    content::{
+        api::ContentGateways,
+        verify::{verify_content, verify_contents},
+    },
+    evm::{quorum_read, ProviderContext},
+    export::export_publications,
+    output::{emit, is_jsonl_output, OutputEvent, TransactionVerified},
+    submitter::state::{get_submitters, is_valid_submitter},
+    transport::NodeTransport,
     types::{
+        content::ContentStatus,
+        settlement::SettlementStatus,
         transaction::{MomokaTransaction, MomokaTxId, TransactionSummary},
-        verifier_error::MomokaVerifierError,
+        transaction_filter::TransactionFilter,
+        verification_report::{VerificationCheck, VerificationReport},
+        verifier_error::{ErrorSource, MomokaVerifierError},
+    },
+    verifier::{
+        batch::{batch_page_reads, is_recording, RECORDED_READS_PAGE},
+        canonical::signed_payload,
+        double_publication::record_da_publication,
+        indexing::{index_unverified_publications, record_publication_verdict},
     },
 };
 use ethers::{
-    providers::{Http, Middleware, Provider, RetryClient},
+    providers::{Middleware, Provider, RetryClient},
     types::{Address, Block, BlockNumber, Signature, H256, U256},
 };
 
@@ -122,24 +143,70 @@ fn get_closest_block(blocks: &[Block<H256>; 3], target_timestamp: U256) -> Optio
 /// ```
 async fn get_blocks(
     block_numbers: [u64; 3],
-    provider: &Provider<RetryClient<Http>>,
+    provider: &Provider<RetryClient<NodeTransport>>,
 ) -> Result<[Block<H256>; 3], MomokaVerifierError> {
-    let mut blocks = [Default::default(), Default::default(), Default::default()];
-
-    for (i, block_number) in block_numbers.iter().enumerate() {
-        let block = provider
-            .get_block(*block_number)
+    let get_block = |block_number: u64| async move {
+        provider
+            .get_block(block_number)
             .await
-            .map_err(|_| MomokaVerifierError::BlockCantBeReadFromNode)?
-            .ok_or(MomokaVerifierError::BlockCantBeReadFromNode)?;
+            .map_err(|e| MomokaVerifierError::BlockCantBeReadFromNode { source: e.into() })?
+            .ok_or(MomokaVerifierError::BlockCantBeReadFromNode {
+                source: ErrorSource::default(),
+            })
+    };
 
-        blocks[i] = block;
-    }
+    let (previous, block, next) = futures::try_join!(
+        get_block(block_numbers[0]),
+        get_block(block_numbers[1]),
+        get_block(block_numbers[2]),
+    )?;
+
+    Ok([previous, block, next])
+}
 
-    Ok(blocks)
+/// Returns the three block numbers the closest block check compares.
+fn closest_block_candidates(block_number: &u64) -> [u64; 3] {
+    [
+        block_number.checked_sub(1).unwrap(),
+        *block_number,
+        block_number.checked_add(1).unwrap(),
+    ]
+}
+
+/// Finds which of the blocks around the block number is the closest to the timestamp.
+///
+/// # Arguments
+///
+/// * `timestamp` - A reference to the timestamp in the Momoka transaction to use for finding the closest block.
+/// * `blocks` - The block before, the block itself and the block after.
+///
+/// # Returns
+///
+/// A `Result<u64, MomokaVerifierError>` containing the closest block number.
+fn closest_block_number(
+    timestamp: &u64,
+    blocks: &[Block<H256>; 3],
+) -> Result<u64, MomokaVerifierError> {
+    let closest_block = get_closest_block(blocks, U256::from(*timestamp)).ok_or(
+        MomokaVerifierError::BlockCantBeReadFromNode {
+            source: ErrorSource::default(),
+        },
+    )?;
+
+    closest_block
+        .number
+        .map(|n| n.as_u64())
+        .ok_or(MomokaVerifierError::NotClosestBlock {
+            chosen: None,
+            closest: None,
+        })
 }
 
-/// Verifies that the block number in a given Momoka transaction matches the closest block to its timestamp.
+/// Finds the closest block to the timestamp of a Momoka transaction.
+///
+/// The blocks are read from every node and have to be agreed by the quorum, unless the quorum
+/// already agreed on them in the batched reads of the page. Blocks read here are recorded the
+/// same as the batched ones when recording an archive, so the archive replays offline.
 ///
 /// # Arguments
 ///
@@ -149,124 +216,117 @@ async fn get_blocks(
 ///
 /// # Returns
 ///
-/// A `Result<(), MomokaVerifierError>` indicating whether the block number in the Momoka transaction matches the closest block to its timestamp.
+/// A `Result<u64, MomokaVerifierError>` containing the closest block number.
 ///
 /// # Errors
 ///
 /// Returns a `MomokaVerifierError` if any of the following errors occur:
 ///
 /// * The block numbers cannot be read from the node.
-/// * The block number in the closest block to the timestamp does not match the given block number, and it is not the next block due to latency.
 /// * The block number in the closest block to the timestamp does not exist.
+/// * The nodes do not agree on the closest block.
+async fn get_closest_block_number(
+    block_number: &u64,
+    timestamp: &u64,
+    provider_context: &ProviderContext,
+) -> Result<u64, MomokaVerifierError> {
+    let block_numbers = closest_block_candidates(block_number);
+    let blocks = match block_numbers.map(read_batched_block_cache) {
+        [Some(previous), Some(block), Some(next)] => [previous, block, next],
+        _ => {
+            let blocks =
+                quorum_read(provider_context, |node| get_blocks(block_numbers, node)).await?;
+
+            if is_recording() {
+                extend_batched_reads_cache(
+                    RECORDED_READS_PAGE,
+                    block_numbers.into_iter().zip(blocks.clone()).collect(),
+                    HashMap::new(),
+                );
+            }
+
+            blocks
+        }
+    };
+
+    closest_block_number(timestamp, &blocks)
+}
+
+/// Verifies that the block number in a given Momoka transaction is the closest block to its timestamp.
+///
+/// Due to latency with nodes, the next block is also accepted as the closest. When you do a request
+/// over the wire, the node provider may not have broadcasted yet, this means you may have 100-300ms
+/// latency which cannot be avoided. The signature still needs to conform to the past block, so it's
+/// still very valid.
+///
+/// # Arguments
+///
+/// * `block_number` - A reference to the block number in the Momoka transaction to verify.
+/// * `closest_block_number` - The closest block number to the timestamp.
+///
+/// # Returns
+///
+/// A `Result<(), MomokaVerifierError>` indicating whether the block number in the Momoka transaction matches the closest block to its timestamp.
 ///
 /// # Examples
 ///
 /// ```
-/// # use momoka_rs::{is_valid_choosen_block, EthereumNode};
-///
-/// let block_number = 42;
-/// let timestamp = 1620627000;
-/// let provider_context = ProviderContext{(/* ... */)};
-/// let result = is_valid_choosen_block(&block_number, &timestamp, &provider_context).await;
+/// let result = is_valid_choosen_block(&42, 43);
+/// assert!(result.is_ok());
 /// ```
-async fn is_valid_choosen_block(
+fn is_valid_choosen_block(
     block_number: &u64,
-    timestamp: &u64,
-    provider_context: &ProviderContext,
+    closest_block_number: u64,
 ) -> Result<(), MomokaVerifierError> {
-    let blocks = get_blocks(
-        [
-            block_number.checked_sub(1).unwrap(),
-            *block_number,
-            block_number.checked_add(1).unwrap(),
-        ],
-        &provider_context.node,
-    )
-    .await?;
-
-    let closest_block = get_closest_block(&blocks, U256::from(*timestamp));
-    if let Some(closest_block) = closest_block {
-        if let Some(closest_block_number) = closest_block.number.map(|n| n.as_u64()) {
-            if closest_block_number != *block_number {
-                // println!(
-                //     "The block you have chosen is not the closest block to the timestamp.
-                //     The closest block is {} and you have chosen {}.",
-                //     closest_block_number, block_number
-                // );
-
-                if closest_block_number == block_number.checked_add(1).unwrap_or_default() {
-                    // println!("
-                    //     Due to latency with nodes, we allow the next block to be accepted as the closest.
-                    //     When you do a request over the wire, the node provider may not have broadcasted yet,
-                    //     this means you may have 100-300ms latency which cannot be avoided. The signature still
-                    //     needs to conform to the past block, so it's still very valid.
-                    // ");
-                } else {
-                    return Err(MomokaVerifierError::NotClosestBlock);
-                }
-            }
-        } else {
-            return Err(MomokaVerifierError::NotClosestBlock);
-        }
-    } else {
-        return Err(MomokaVerifierError::BlockCantBeReadFromNode);
+    if closest_block_number != *block_number
+        && closest_block_number != block_number.checked_add(1).unwrap_or_default()
+    {
+        return Err(MomokaVerifierError::NotClosestBlock {
+            chosen: Some(*block_number),
+            closest: Some(closest_block_number),
+        });
     }
 
     Ok(())
 }
 
-/// the pattern to extract the signature out of the big payload
-const SIGNATURE_EXTRACT_PATTERN: &str = r#""signature"\s*:\s*"0x[a-fA-F0-9]{130}",?\s*"#;
-
-/// Extracts the address from a MomokaTransaction.
+/// Extracts the address of the submitter from a MomokaTransaction.
 ///
-/// This function removes the signature from the payload of the transaction,
-/// updates the verifying contract and collect module (if present), and recovers
-/// the address from the signature.
+/// The submitter signs the publication without its own signature, so this takes that exact
+/// message from the received publication with `signed_payload` and recovers the address from
+/// the signature.
 ///
 /// # Arguments
 ///
 /// * `transaction` - A reference to the MomokaTransaction from which to extract the address.
+/// * `submitters` - The submitters of the environment, reported if the signature is invalid.
 ///
 /// # Returns
 ///
 /// * `Result<Address, MomokaVerifierError>` - The extracted address if successful, or an error if any step fails.
 ///
-fn extract_address(transaction: &MomokaTransaction) -> Result<Address, MomokaVerifierError> {
-    // due to signing a huge payload of typed data we need to remove the signature from the payload
-    // this was due to some calls in the node code and this had some knock on here so we have to do it
-    // a bit dirty! - sorry!!
-
-    let mut inner_object = transaction.get_inner_object()?;
-
-    // Update verifying contract
-    let verifying_contract = transaction.verifying_contract()?;
-    inner_object["chainProofs"]["thisPublication"]["typedData"]["domain"]["verifyingContract"] =
-        JsonValue::String(ethers::utils::to_checksum(verifying_contract, None));
-
-    // Update collect module if present
-    if let Some(collect_module) = transaction.typed_data_collect_module()? {
-        let collect_module_checksum = ethers::utils::to_checksum(collect_module, None);
-        inner_object["chainProofs"]["thisPublication"]["typedData"]["value"]["collectModule"] =
-            JsonValue::String(collect_module_checksum.clone());
-        inner_object["event"]["collectModule"] = JsonValue::String(collect_module_checksum);
-    }
-
-    // Convert the inner object to a JSON string
-    let inner_object_string = json::stringify(inner_object.clone());
-
-    // Remove the signature from the payload
-    let re = Regex::new(SIGNATURE_EXTRACT_PATTERN)
-        .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
-    let omit_signature = re.replace(&inner_object_string, "").into_owned();
+fn extract_address(
+    transaction: &MomokaTransaction,
+    submitters: &[Address],
+) -> Result<Address, MomokaVerifierError> {
+    let signed_payload = signed_payload(transaction.received_json()?)?;
 
     // Recover the address from the signature
-    let signature = Signature::from_str(transaction.signature()?)
-        .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
+    let signature = Signature::from_str(transaction.signature()?).map_err(|e| {
+        MomokaVerifierError::InvalidSignatureSubmitter {
+            recovered: None,
+            expected: submitters.to_vec(),
+            source: e.into(),
+        }
+    })?;
 
-    let address = signature
-        .recover::<String>(omit_signature)
-        .map_err(|_| MomokaVerifierError::InvalidSignatureSubmitter)?;
+    let address = signature.recover::<String>(signed_payload).map_err(|e| {
+        MomokaVerifierError::InvalidSignatureSubmitter {
+            recovered: None,
+            expected: submitters.to_vec(),
+            source: e.into(),
+        }
+    })?;
 
     Ok(address)
 }
@@ -319,6 +379,7 @@ async fn verify_timestamp_proofs_match_transaction(
 ///
 /// * `transaction_summary` - A reference to the `TransactionSummary` struct to process the proof for.
 /// * `provider_context` - The provider context.
+/// * `report` - The report every check run is recorded in.
 ///
 /// # Returns
 ///
@@ -331,60 +392,141 @@ async fn verify_timestamp_proofs_match_transaction(
 ///
 /// let transaction_summary = TransactionSummary::new(/* ... */);
 /// let provider_context = ProviderContext{(/* ... */)};
-/// let result = process_proof(&transaction_summary, &provider_context).await;
+/// let mut report = VerificationReport::new(transaction_summary.id.clone());
+/// let result = process_proof(&transaction_summary, &provider_context, &mut report).await;
 /// ```
 async fn process_proof(
     transaction_summary: &TransactionSummary,
     provider_context: &ProviderContext,
+    report: &mut VerificationReport,
 ) -> Result<(), MomokaVerifierError> {
-    let signer_address = extract_address(&transaction_summary.momoka_tx)?;
-
-    if !is_valid_submitter(
-        &provider_context.environment,
-        &signer_address,
-        &provider_context.deployment,
-    ) {
-        println!("Invalid submitter");
-        return Err(MomokaVerifierError::InvalidSignatureSubmitter);
-    }
-
-    if !transaction_summary.momoka_tx.valid_publication_id()? {
-        return Err(MomokaVerifierError::GeneratedPublicationIdMismatch);
-    }
+    let momoka_tx = &transaction_summary.momoka_tx;
+    let submitters = format!(
+        "a {:?} {:?} submitter",
+        provider_context.environment, provider_context.deployment
+    );
+    let expected_submitters =
+        get_submitters(&provider_context.environment, &provider_context.deployment);
+    let is_submitter = |address: &Address| {
+        is_valid_submitter(
+            &provider_context.environment,
+            address,
+            &provider_context.deployment,
+        )
+    };
 
-    if !is_valid_submitter(
-        &provider_context.environment,
-        &transaction_summary.submitter,
-        &provider_context.deployment,
-    ) {
-        return Err(MomokaVerifierError::TimestampProofNotSubmitter);
-    }
+    // the checks are split into the phases their time is spent in, recovering signatures and
+    // hashing is CPU bound while the chain checks wait on the nodes
+    let crypto = debug_span!("crypto");
+    let chain = debug_span!("chain");
+
+    let entered = crypto.enter();
+    let signer_address = extract_address(momoka_tx, &expected_submitters);
+    report.record(
+        VerificationCheck::SubmitterRecovery,
+        Some(submitters.clone()),
+        signer_address.as_ref().ok().map(|a| format!("{:?}", a)),
+        signer_address.as_ref().map_err(Clone::clone).and_then(|a| {
+            if is_submitter(a) {
+                Ok(())
+            } else {
+                Err(MomokaVerifierError::InvalidSignatureSubmitter {
+                    recovered: Some(*a),
+                    expected: expected_submitters.clone(),
+                    source: ErrorSource::default(),
+                })
+            }
+        }),
+    )?;
+
+    let generated_publication_id = momoka_tx.generate_publication_id()?;
+    report.record(
+        VerificationCheck::PublicationId,
+        Some(generated_publication_id.clone()),
+        Some(momoka_tx.publication_id()?.clone()),
+        if momoka_tx.valid_publication_id()? {
+            Ok(())
+        } else {
+            Err(MomokaVerifierError::GeneratedPublicationIdMismatch {
+                expected: generated_publication_id,
+                actual: momoka_tx.publication_id()?.clone(),
+            })
+        },
+    )?;
 
-    if !transaction_summary.momoka_tx.is_valid_event_timestamp()? {
-        return Err(MomokaVerifierError::InvalidEventTimestamp);
-    }
+    let timestamp_proofs_submitters: Vec<&Address> =
+        std::iter::once(&transaction_summary.submitter)
+            .chain(transaction_summary.timestamp_proofs_submitter.as_ref())
+            .collect();
+    report.record(
+        VerificationCheck::TimestampProofsSubmitter,
+        Some(submitters),
+        Some(format!("{:?}", timestamp_proofs_submitters)),
+        match timestamp_proofs_submitters
+            .iter()
+            .find(|submitter| !is_submitter(submitter))
+        {
+            Some(submitter) => Err(MomokaVerifierError::TimestampProofNotSubmitter {
+                submitter: **submitter,
+            }),
+            None => Ok(()),
+        },
+    )?;
+
+    report.record(
+        VerificationCheck::EventTimestamp,
+        Some(momoka_tx.block_timestamp()?.to_string()),
+        Some(momoka_tx.event_timestamp()?.to_string()),
+        if momoka_tx.is_valid_event_timestamp()? {
+            Ok(())
+        } else {
+            Err(MomokaVerifierError::InvalidEventTimestamp)
+        },
+    )?;
+
+    report.record(
+        VerificationCheck::TypedDataDeadline,
+        Some(momoka_tx.block_timestamp()?.to_string()),
+        Some(momoka_tx.typed_data_deadline()?.to_string()),
+        if momoka_tx.is_valid_typed_data_deadline_timestamp()? {
+            Ok(())
+        } else {
+            Err(MomokaVerifierError::InvalidTypedDataDeadlineTimestamp)
+        },
+    )?;
 
-    if !transaction_summary
-        .momoka_tx
-        .is_valid_typed_data_deadline_timestamp()?
-    {
-        return Err(MomokaVerifierError::InvalidTypedDataDeadlineTimestamp);
-    }
+    drop(entered);
 
-    is_valid_choosen_block(
-        transaction_summary.momoka_tx.block_number()?,
-        transaction_summary
-            .momoka_tx
-            .third_party_proofs_timestamp()?,
+    let block_number = momoka_tx.block_number()?;
+    let closest_block_number = get_closest_block_number(
+        block_number,
+        momoka_tx.third_party_proofs_timestamp()?,
         provider_context,
     )
-    .await?;
-
-    verify_timestamp_proofs_match_transaction(transaction_summary).await?;
-
-    transaction_summary
-        .momoka_tx
-        .validate_transaction(provider_context)
+    .instrument(chain.clone())
+    .await;
+    report.record(
+        VerificationCheck::ClosestBlock,
+        Some(block_number.to_string()),
+        closest_block_number.as_ref().ok().map(|n| n.to_string()),
+        closest_block_number.and_then(|n| is_valid_choosen_block(block_number, n)),
+    )?;
+
+    report.record(
+        VerificationCheck::TimestampProofs,
+        Some(momoka_tx.data_availability_id()?.to_string()),
+        transaction_summary
+            .timestamp_proofs_response
+            .as_ref()
+            .map(|proofs| proofs.data_availability_id.to_string()),
+        verify_timestamp_proofs_match_transaction(transaction_summary)
+            .instrument(crypto)
+            .await,
+    )?;
+
+    momoka_tx
+        .validate_transaction(provider_context, report)
+        .instrument(chain)
         .await
 }
 
@@ -472,6 +614,14 @@ fn cached_signature(signature: &str) -> Result<bool, MomokaVerifierError> {
     Ok(cached.is_some())
 }
 
+/// A checked transaction next to the result of checking its proof, and the status of its content
+/// if content is checked and the transaction is valid.
+type CheckedTransaction = (
+    TransactionSummary,
+    Result<(), MomokaVerifierError>,
+    Option<ContentStatus>,
+);
+
 /// Processes timestamp proofs for a vector of transaction summaries.
 ///
 /// # Arguments
@@ -481,7 +631,8 @@ fn cached_signature(signature: &str) -> Result<bool, MomokaVerifierError> {
 ///
 /// # Returns
 ///
-/// A vector of `Result<(), MomokaVerifierError>` indicating whether each transaction's proof was processed successfully.
+/// Each transaction next to a `Result<(), MomokaVerifierError>` indicating whether its proof was processed successfully,
+/// and the status of its content.
 ///
 /// # Examples
 ///
@@ -495,7 +646,7 @@ fn cached_signature(signature: &str) -> Result<bool, MomokaVerifierError> {
 async fn process_proofs(
     mut transactions: Vec<TransactionSummary>,
     provider_context: &ProviderContext,
-) -> Result<Vec<Result<(), MomokaVerifierError>>, MomokaVerifierError> {
+) -> Result<Vec<CheckedTransaction>, MomokaVerifierError> {
     // to handle many TCP requests lets bulk grab the data if > 10 requests at once
     if transactions.len() > 10 {
         let pointer_tx_ids: HashMap<String, String> = transactions
@@ -514,10 +665,12 @@ async fn process_proofs(
             &pointer_tx_ids
                 .values()
                 .cloned()
-                .collect::<HashSet<String>>()
+                .collect::<BTreeSet<String>>()
                 .into_iter()
                 .collect::<Vec<String>>(),
+            None,
         )
+        .instrument(debug_span!("fetch_pointers", count = pointer_tx_ids.len()))
         .await?;
 
         for transaction in transactions.iter_mut() {
@@ -534,6 +687,24 @@ async fn process_proofs(
         }
     }
 
+    // the batched reads are dropped with the page once it has been verified
+    let _batched_page = batch_page_reads(
+        &transactions
+            .iter()
+            .flat_map(|transaction| {
+                std::iter::once(transaction)
+                    .chain(transaction.pointer_transaction_summary.as_deref())
+            })
+            .collect::<Vec<_>>(),
+        provider_context,
+    )
+    .instrument(debug_span!("batch_reads", count = transactions.len()))
+    .await;
+
+    // the reports of the transactions which failed, sent with their alerts
+    let alert_reports: Mutex<HashMap<MomokaTxId, VerificationReport>> = Mutex::new(HashMap::new());
+    let reports = &alert_reports;
+
     let futures = transactions.iter().map(|transaction| async move {
         if let Some(pointer) = transaction.momoka_tx.pointer()? {
             let tx_id: &MomokaTxId = &pointer.location.replace("ar://", "");
@@ -543,16 +714,23 @@ async fn process_proofs(
                 cached.unwrap()
             }
 
+            let mut pointer_report = VerificationReport::new(tx_id.clone());
+            let pointer_span = debug_span!("pointer", pointer_tx_id = %tx_id);
             if transaction.pointer_transaction_summary.is_some() {
                 process_proof(
                     transaction.pointer_transaction_summary.as_ref().unwrap(),
                     provider_context,
+                    &mut pointer_report,
                 )
+                .instrument(pointer_span)
                 .await
                 .map_err(|_| MomokaVerifierError::PointerFailedVerification)?;
             } else {
-                let pointer_transaction = get_transaction_api(tx_id).await?;
-                process_proof(&pointer_transaction, provider_context)
+                let pointer_transaction = get_transaction_api(tx_id)
+                    .instrument(debug_span!("fetch", tx_id = %tx_id))
+                    .await?;
+                process_proof(&pointer_transaction, provider_context, &mut pointer_report)
+                    .instrument(pointer_span)
                     .await
                     .map_err(|_| MomokaVerifierError::PointerFailedVerification)?;
             }
@@ -570,25 +748,349 @@ async fn process_proofs(
             return Err(MomokaVerifierError::ChainSignatureAlreadyUsed);
         }
 
+        let mut report = VerificationReport::new(transaction.id.clone());
         let result: Result<(), MomokaVerifierError> =
-            process_proof(transaction, provider_context).await;
+            process_proof(transaction, provider_context, &mut report).await;
 
         set_signature_cache(signature.to_owned());
         set_tx_cache(transaction.id.clone(), &result)?;
 
+        if result.is_err() && is_alerting() {
+            reports
+                .lock()
+                .unwrap()
+                .insert(transaction.id.clone(), report);
+        }
+
+        // written as a transaction event instead when the output is JSON lines
+        let log_verdict = !is_jsonl_output();
         match &result {
             Ok(()) => {
-                Logger.success(&format!("{:?} - OK", transaction.id));
+                if log_verdict {
+                    info!("{:?} - OK", transaction.id);
+                }
+
+                if provider_context.detect_double_publications {
+                    if let Err(err) = record_da_publication(transaction) {
+                        warn!(
+                            "{:?} - could not be watched for double publication - {:?}",
+                            transaction.id, err
+                        );
+                    }
+                }
             }
-            Err(err) => {
-                Logger.error(&format!("{:?} -FAILED - {:?}", transaction.id, err));
+            Err(err) if log_verdict => {
+                error!("{:?} -FAILED - {:?}", transaction.id, err);
             }
+            Err(_) => {}
         }
 
         result
     });
 
-    Ok(futures::future::join_all(futures).await)
+    let (mut results, durations): (Vec<_>, Vec<Duration>) = futures::future::join_all(
+        transactions
+            .iter()
+            .zip(futures)
+            .map(|(transaction, future)| {
+                timed(future.instrument(debug_span!("verify", tx_id = %transaction.id)))
+            }),
+    )
+    .await
+    .into_iter()
+    .unzip();
+
+    if let Some(gateway) = &provider_context.arweave_gateway {
+        let verified: Vec<&TransactionSummary> = transactions
+            .iter()
+            .zip(results.iter())
+            .filter(|(_, result)| result.is_ok())
+            .map(|(transaction, _)| transaction)
+            .collect();
+
+        let settlements = process_settlements(&verified, gateway).await?;
+
+        for (transaction, result) in transactions.iter().zip(results.iter_mut()) {
+            match settlements.get(&transaction.id) {
+                Some(Ok(SettlementStatus::TimestampedNotSettled { deadline_height })) => {
+                    warn!(
+                        "{:?} - TIMESTAMPED BUT NOT YET SETTLED - deadline height {}",
+                        transaction.id, deadline_height
+                    );
+                }
+                Some(Err(err)) => *result = Err(err.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    let mut contents = HashMap::new();
+    if let Some(gateways) = &provider_context.content_gateways {
+        let content_uris: HashMap<MomokaTxId, String> = transactions
+            .iter()
+            .zip(results.iter())
+            .filter(|(_, result)| result.is_ok())
+            .filter_map(|(transaction, _)| {
+                transaction
+                    .momoka_tx
+                    .content_uri()
+                    .ok()
+                    .flatten()
+                    .map(|content_uri| (transaction.id.clone(), content_uri.clone()))
+            })
+            .collect();
+
+        contents = process_contents(&content_uris, gateways).await;
+    }
+
+    let mut alert_reports = alert_reports.into_inner().unwrap();
+    for ((transaction, result), duration) in transactions.iter().zip(&results).zip(durations) {
+        report_verdict(
+            transaction,
+            result,
+            contents.get(&transaction.id),
+            Some(duration),
+            alert_reports.remove(&transaction.id),
+            provider_context,
+        );
+    }
+
+    export_publications(
+        transactions
+            .iter()
+            .zip(&results)
+            .map(|(transaction, result)| (transaction, result, contents.get(&transaction.id))),
+    )
+    .await;
+
+    Ok(transactions
+        .into_iter()
+        .zip(results)
+        .map(|(transaction, result)| {
+            let content = contents.remove(&transaction.id);
+            (transaction, result, content)
+        })
+        .collect())
+}
+
+/// Reports the verdict on a checked transaction: writes it as an output event, upgrades the
+/// indexed publication in trusted indexing mode and alerts on it if it is invalid.
+///
+/// # Arguments
+///
+/// * `transaction` - The checked transaction.
+/// * `result` - The result of checking its proof.
+/// * `content` - The status of its content, if it was checked.
+/// * `duration` - How long the proof took to check, if it was checked just now.
+/// * `report` - The report of the checks run on the transaction, if it was kept for alerting.
+/// * `provider_context` - The provider context.
+fn report_verdict(
+    transaction: &TransactionSummary,
+    result: &Result<(), MomokaVerifierError>,
+    content: Option<&ContentStatus>,
+    duration: Option<Duration>,
+    report: Option<VerificationReport>,
+    provider_context: &ProviderContext,
+) {
+    emit(OutputEvent::TransactionVerified(TransactionVerified::new(
+        &transaction.id,
+        Some(transaction),
+        result,
+        content,
+        duration,
+    )));
+
+    if provider_context.trusted_indexing {
+        record_publication_verdict(&transaction.id, result);
+    }
+
+    match result {
+        Err(error) if error.is_verdict() => send_alert(Alert::new(
+            &transaction.id,
+            Some(transaction),
+            error,
+            report,
+            provider_context,
+        )),
+        _ => {}
+    }
+}
+
+/// Runs a future and measures how long it took.
+///
+/// # Arguments
+///
+/// * `future` - The future to run.
+///
+/// # Returns
+///
+/// The output of the future next to how long it took, from when it was first polled.
+async fn timed<F: Future>(future: F) -> (F::Output, Duration) {
+    let started = Instant::now();
+    let output = future.await;
+    (output, started.elapsed())
+}
+
+/// Checks the content of the given verified publications resolves to valid Lens metadata.
+///
+/// The content status does not change the verdict on a transaction, it is only reported.
+///
+/// # Arguments
+///
+/// * `content_uris` - The transaction ids mapped to the content URI in their typed data.
+/// * `gateways` - The gateways to resolve the content through.
+///
+/// # Returns
+///
+/// A map of the transaction ids to the status of their content.
+async fn process_contents(
+    content_uris: &HashMap<MomokaTxId, String>,
+    gateways: &ContentGateways,
+) -> HashMap<MomokaTxId, ContentStatus> {
+    let contents = verify_contents(content_uris, gateways).await;
+
+    for (tx_id, content) in contents.iter() {
+        match content {
+            ContentStatus::Valid { version } => {
+                info!("{:?} - CONTENT OK - metadata {}", tx_id, version);
+            }
+            _ => {
+                warn!("{:?} - CONTENT {}", tx_id, content);
+            }
+        }
+    }
+
+    contents
+}
+
+/// Checks the given verified transactions were settled on Arweave before their deadline height.
+///
+/// Transactions which are timestamped but not yet seen on Arweave are kept in the pending
+/// settlement cache so they can be rechecked with `recheck_pending_settlements`. If the
+/// gateway can not be reached all the transactions are left pending.
+///
+/// # Arguments
+///
+/// * `transactions` - The verified transactions, the deadline height is read from their timestamp proofs.
+/// * `gateway` - The base URL of the Arweave gateway.
+///
+/// # Returns
+///
+/// A map of the transaction ids to their settlement result.
+async fn process_settlements(
+    transactions: &[&TransactionSummary],
+    gateway: &str,
+) -> Result<HashMap<MomokaTxId, Result<SettlementStatus, MomokaVerifierError>>, MomokaVerifierError>
+{
+    let deadlines: HashMap<MomokaTxId, u64> = transactions
+        .iter()
+        .filter_map(|transaction| {
+            transaction
+                .momoka_tx
+                .get_timestamp_proofs()
+                .ok()
+                .map(|proofs| (transaction.id.clone(), proofs.response.deadline_height))
+        })
+        .collect();
+
+    let settlements = match verify_settlements(gateway, &deadlines).await {
+        Ok(settlements) => settlements,
+        Err(err) => {
+            warn!(
+                "Could not check settlement of {} transactions - {:?}",
+                deadlines.len(),
+                err
+            );
+            deadlines
+                .iter()
+                .map(|(tx_id, deadline_height)| {
+                    (
+                        tx_id.clone(),
+                        Ok(SettlementStatus::TimestampedNotSettled {
+                            deadline_height: *deadline_height,
+                        }),
+                    )
+                })
+                .collect()
+        }
+    };
+
+    for transaction in transactions {
+        let tx_id = &transaction.id;
+        let Some(settlement) = settlements.get(tx_id) else {
+            continue;
+        };
+
+        match settlement {
+            Ok(SettlementStatus::TimestampedNotSettled { .. }) => {
+                set_pending_settlement_cache((*transaction).clone());
+            }
+            Ok(SettlementStatus::Settled { .. }) => {
+                remove_pending_settlement_cache(tx_id);
+            }
+            Err(err) => {
+                remove_pending_settlement_cache(tx_id);
+                set_tx_cache(tx_id.clone(), &Err(err.clone()))?;
+                error!("{:?} - FAILED - {:?}", tx_id, err);
+            }
+        }
+    }
+
+    Ok(settlements)
+}
+
+/// Rechecks the transactions which are timestamped but not yet settled on Arweave.
+///
+/// A transaction which was not settled before its deadline height is reported the way a
+/// verified transaction is: written as a verdict, alerted on, exported and upgraded to invalid
+/// in trusted indexing mode. This does nothing if no Arweave gateway is configured on the
+/// provider context.
+///
+/// # Arguments
+///
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// A map of the rechecked transaction ids to their settlement result.
+pub async fn recheck_pending_settlements(
+    provider_context: &ProviderContext,
+) -> Result<HashMap<MomokaTxId, Result<SettlementStatus, MomokaVerifierError>>, MomokaVerifierError>
+{
+    let gateway = match &provider_context.arweave_gateway {
+        Some(gateway) => gateway,
+        None => return Ok(HashMap::new()),
+    };
+
+    let pending = read_pending_settlement_cache();
+    let settlements = process_settlements(&pending.iter().collect::<Vec<_>>(), gateway).await?;
+
+    let mut failed = vec![];
+    for transaction in pending {
+        match settlements.get(&transaction.id) {
+            Some(Ok(SettlementStatus::Settled { block_height })) => {
+                info!(
+                    "{:?} - SETTLED - arweave block {}",
+                    transaction.id, block_height
+                );
+            }
+            Some(Err(error)) => failed.push((transaction, Err(error.clone()))),
+            _ => {}
+        }
+    }
+
+    for (transaction, result) in failed.iter() {
+        report_verdict(transaction, result, None, None, None, provider_context);
+    }
+
+    export_publications(
+        failed
+            .iter()
+            .map(|(transaction, result)| (transaction, result, None)),
+    )
+    .await;
+
+    Ok(settlements)
 }
 
 /// Checks the proofs of a vector of transaction IDs using the provided Ethereum node.
@@ -632,25 +1134,157 @@ pub async fn check_proofs(
     tx_ids: &Vec<MomokaTxId>,
     provider_context: &ProviderContext,
 ) -> Result<Vec<Result<(), MomokaVerifierError>>, MomokaVerifierError> {
+    let (checked, _) = fetch_and_process_proofs(
+        tx_ids,
+        provider_context.transaction_filter.as_ref(),
+        provider_context,
+    )
+    .await?;
+
+    Ok(checked.into_iter().map(|(_, result, _)| result).collect())
+}
+
+/// The outcome of checking the proof of a transaction which was asked for by its id.
+#[derive(Debug, Clone)]
+pub struct ProofOutcome {
+    /// The transaction ID which was asked for.
+    pub tx_id: MomokaTxId,
+    /// The transaction, if it could be fetched and parsed.
+    pub transaction: Option<TransactionSummary>,
+    /// Whether the proof is valid, `InvalidTxID` if bundlr did not return the transaction at all.
+    pub result: Result<(), MomokaVerifierError>,
+    /// The status of the content of a valid publication, if content is checked.
+    pub content: Option<ContentStatus>,
+}
+
+/// Checks the proofs of the given transactions and returns an outcome for every one of them.
+///
+/// Unlike `check_proofs` transactions which could not be fetched or parsed have an outcome too.
+/// When a filter is given the transactions which do not match it have no outcome, otherwise a
+/// transaction bundlr did not return at all is `InvalidTxID`.
+///
+/// # Arguments
+///
+/// * `tx_ids` - The transaction IDs to check proofs for.
+/// * `filter` - The filter transactions have to pass to be checked (optional), transactions asked
+///   for explicitly are checked whatever they are.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The outcome of each transaction in the same order as `tx_ids`.
+pub async fn check_proof_outcomes(
+    tx_ids: &Vec<MomokaTxId>,
+    filter: Option<&TransactionFilter>,
+    provider_context: &ProviderContext,
+) -> Result<Vec<ProofOutcome>, MomokaVerifierError> {
+    let (checked, mut failed) = fetch_and_process_proofs(tx_ids, filter, provider_context).await?;
+
+    let mut checked: HashMap<MomokaTxId, CheckedTransaction> = checked
+        .into_iter()
+        .map(|checked| (checked.0.id.clone(), checked))
+        .collect();
+
+    Ok(tx_ids
+        .iter()
+        .filter_map(
+            |tx_id| match (checked.remove(tx_id), failed.remove(tx_id)) {
+                (Some((transaction, result, content)), _) => Some(ProofOutcome {
+                    tx_id: tx_id.clone(),
+                    transaction: Some(transaction),
+                    result,
+                    content,
+                }),
+                (None, Some(error)) => Some(ProofOutcome {
+                    tx_id: tx_id.clone(),
+                    transaction: None,
+                    result: Err(error),
+                    content: None,
+                }),
+                // filtered out, bundlr returned it but it does not match the filter
+                (None, None) if filter.is_some() => None,
+                (None, None) => Some(ProofOutcome {
+                    tx_id: tx_id.clone(),
+                    transaction: None,
+                    result: Err(MomokaVerifierError::InvalidTxID),
+                    content: None,
+                }),
+            },
+        )
+        .collect())
+}
+
+/// Fetches the given transactions from bundlr and checks their proofs.
+///
+/// # Arguments
+///
+/// * `tx_ids` - The transaction IDs to check proofs for.
+/// * `filter` - The filter transactions have to pass to be checked (optional).
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The checked transactions next to their result and content status, and the transactions which could not be fetched or parsed.
+async fn fetch_and_process_proofs(
+    tx_ids: &Vec<MomokaTxId>,
+    filter: Option<&TransactionFilter>,
+    provider_context: &ProviderContext,
+) -> Result<
+    (
+        Vec<CheckedTransaction>,
+        HashMap<MomokaTxId, MomokaVerifierError>,
+    ),
+    MomokaVerifierError,
+> {
     let amount = tx_ids.len();
     let is_bulk = amount > 999;
     if is_bulk {
-        Logger.info(&format!("Fetching {} transactions from bundlr", amount));
+        info!("Fetching {} transactions from bundlr", amount);
     }
 
-    let transactions = get_bulk_transactions_api(tx_ids).await?;
+    let transactions = get_bulk_transactions_api(tx_ids, filter)
+        .instrument(debug_span!("fetch", count = tx_ids.len()))
+        .await?;
+
+    if provider_context.trusted_indexing {
+        index_unverified_publications(&transactions.success);
+    }
 
     if is_bulk {
-        Logger.info(&format!("Fetched {} transactions from bundlr", amount));
+        info!("Fetched {} transactions from bundlr", amount);
     }
 
-    for (id, error) in transactions.failed {
-        Logger.error(&format!("{:?} - FAILED - {:?}", id, error));
+    let amount = transactions.success.len() + transactions.failed.len();
+    if filter.is_some() && amount < tx_ids.len() {
+        info!(
+            "Skipped {} transactions which do not match the filter",
+            tx_ids.len() - amount
+        );
     }
 
-    Logger.info(&format!("Checking proofs for {} transactions", amount));
+    for (id, error) in transactions.failed.iter() {
+        if is_jsonl_output() {
+            emit(OutputEvent::TransactionVerified(TransactionVerified::new(
+                id,
+                None,
+                &Err(error.clone()),
+                None,
+                None,
+            )));
+        } else {
+            error!("{:?} - FAILED - {:?}", id, error);
+        }
 
-    process_proofs(transactions.success, provider_context).await
+        if error.is_verdict() {
+            send_alert(Alert::new(id, None, error, None, provider_context));
+        }
+    }
+
+    info!("Checking proofs for {} transactions", amount);
+
+    let checked = process_proofs(transactions.success, provider_context).await?;
+
+    Ok((checked, transactions.failed))
 }
 
 /// Asynchronously checks the proof for a single transaction ID and returns the result.
@@ -667,18 +1301,65 @@ pub async fn check_proofs(
 /// # Returns
 ///
 /// * `Ok(())` if the proof check is successful for the first transaction.
-/// * An `Err` containing the appropriate `MomokaVerifierError` if the proof check fails for the first transaction,
-///   or if the transaction ID is not found in the `check_proofs` results.
+/// * An `Err` containing the appropriate `MomokaVerifierError` if the proof check fails for the transaction,
+///   if it could not be fetched or parsed, or `InvalidTxID` if bundlr did not return it.
 pub async fn check_proof(
     tx_id: &MomokaTxId,
     provider_context: &ProviderContext,
 ) -> Result<(), MomokaVerifierError> {
-    let results = check_proofs(&vec![tx_id.to_string()], provider_context).await?;
+    check_proof_outcomes(&vec![tx_id.to_string()], None, provider_context)
+        .await?
+        .pop()
+        .map_or(Err(MomokaVerifierError::InvalidTxID), |outcome| {
+            outcome.result
+        })
+}
 
-    // Get the first result or return an error if the transaction ID is not found
-    match results.get(0) {
-        Some(Ok(())) => Ok(()),
-        Some(Err(err)) => Err(err.clone()),
-        None => panic!("Could not find the tx {:?}", tx_id),
+/// Verifies a single transaction and reports every check which was run.
+///
+/// Unlike `check_proof` this does not read or write the transaction and signature caches, so
+/// the transaction is always checked from scratch. For comments and mirrors the publication
+/// pointed to is verified first, if it fails the report has no checks for the transaction itself.
+/// When content checks are enabled the content status of a valid post or comment is attached.
+///
+/// # Arguments
+///
+/// * `tx_id` - The transaction ID to verify.
+/// * `provider_context` - A reference to the `ProviderContext` containing the necessary provider information.
+///
+/// # Returns
+///
+/// * `Ok(VerificationReport)` with every check run, the first failed check holds the verdict.
+/// * An `Err` if the transaction could not be fetched or the pointer failed verification.
+pub async fn check_proof_report(
+    tx_id: &MomokaTxId,
+    provider_context: &ProviderContext,
+) -> Result<VerificationReport, MomokaVerifierError> {
+    let transaction = get_transaction_api(tx_id).await?;
+
+    if let Some(pointer) = transaction.momoka_tx.pointer()? {
+        let pointer_tx_id: MomokaTxId = pointer.location.replace("ar://", "");
+        let pointer_transaction = get_transaction_api(&pointer_tx_id).await?;
+
+        process_proof(
+            &pointer_transaction,
+            provider_context,
+            &mut VerificationReport::new(pointer_tx_id),
+        )
+        .await
+        .map_err(|_| MomokaVerifierError::PointerFailedVerification)?;
+    }
+
+    let mut report = VerificationReport::new(transaction.id.clone());
+    let result = process_proof(&transaction, provider_context, &mut report).await;
+
+    if let (Ok(()), Some(gateways), Some(content_uri)) = (
+        result,
+        &provider_context.content_gateways,
+        transaction.momoka_tx.content_uri()?,
+    ) {
+        report.content_status = Some(verify_content(content_uri, gateways).await);
     }
+
+    Ok(report)
 }
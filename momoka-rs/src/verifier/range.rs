@@ -0,0 +1,355 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
+};
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    bundlr::api::{get_cursor_at_timestamp_api, get_transactions_api, TransactionOrder},
+    evm::ProviderContext,
+    types::verifier_error::MomokaVerifierError,
+    verifier::{proof::check_proof_outcomes, report::ProofStatus},
+};
+
+/// How many transactions are pulled from bundlr per page of the range.
+const RANGE_PAGE_SIZE: i32 = 1000;
+
+/// One end of a range of transactions, either a bundlr cursor or a point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeBound {
+    Cursor(String),
+    Timestamp(DateTime<Utc>),
+}
+
+impl FromStr for RangeBound {
+    type Err = String;
+
+    /// Parses a unix timestamp in seconds or an RFC 3339 date as a timestamp, anything else as a cursor.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("a cursor or timestamp is required".to_string());
+        }
+
+        if let Ok(seconds) = s.parse::<i64>() {
+            return Utc
+                .timestamp_opt(seconds, 0)
+                .single()
+                .map(RangeBound::Timestamp)
+                .ok_or_else(|| format!("{} is not a valid timestamp", s));
+        }
+
+        Ok(match DateTime::parse_from_rfc3339(s) {
+            Ok(date) => RangeBound::Timestamp(date.with_timezone(&Utc)),
+            Err(_) => RangeBound::Cursor(s.to_string()),
+        })
+    }
+}
+
+impl Display for RangeBound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            RangeBound::Cursor(cursor) => write!(f, "cursor {}", cursor),
+            RangeBound::Timestamp(date) => write!(f, "{}", date.to_rfc3339()),
+        }
+    }
+}
+
+/// What was found verifying a range of transactions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RangeSummary {
+    /// How many transactions bundlr listed in the range.
+    pub listed: usize,
+    /// How many of them were valid.
+    pub valid: usize,
+    /// How many of them were invalid.
+    pub invalid: usize,
+    /// How many of them could not be checked because bundlr, arweave or the nodes could not answer.
+    #[serde(default)]
+    pub unchecked: usize,
+}
+
+impl RangeSummary {
+    /// How many of the listed transactions were skipped because they did not match the filter.
+    pub fn filtered_out(&self) -> usize {
+        self.listed
+            .saturating_sub(self.valid + self.invalid + self.unchecked)
+    }
+
+    /// Records the result of checking a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of checking its proof.
+    pub fn record(&mut self, result: &Result<(), MomokaVerifierError>) {
+        match ProofStatus::of(result) {
+            ProofStatus::Valid => self.valid += 1,
+            ProofStatus::Invalid => self.invalid += 1,
+            ProofStatus::Unchecked => self.unchecked += 1,
+        }
+    }
+
+    /// Adds what was found verifying another part of the range.
+    pub fn merge(&mut self, other: &RangeSummary) {
+        self.listed += other.listed;
+        self.valid += other.valid;
+        self.invalid += other.invalid;
+        self.unchecked += other.unchecked;
+    }
+}
+
+/// A page of a range which was verified.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangePage {
+    /// What was found verifying the page.
+    pub summary: RangeSummary,
+    /// The cursor the next page is pulled after.
+    pub next: Option<String>,
+    /// When the last transaction of the page was uploaded in milliseconds, if bundlr said.
+    pub last_timestamp: Option<u64>,
+    /// Whether the page reached the end of the range, or there are no more transactions.
+    pub reached_end: bool,
+}
+
+impl Display for RangeSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} transactions in range - {} valid, {} invalid, {} could not be checked, {} filtered out",
+            self.listed,
+            self.valid,
+            self.invalid,
+            self.unchecked,
+            self.filtered_out()
+        )
+    }
+}
+
+/// Resolves the start of a range to the cursor the range is pulled after.
+///
+/// # Arguments
+///
+/// * `from` - The start of the range, a cursor is used as is.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The cursor of the last transaction uploaded before a timestamp, or `None` if the range starts
+/// before the first transaction.
+async fn resolve_from(
+    from: &RangeBound,
+    provider_context: &ProviderContext,
+) -> Result<Option<String>, MomokaVerifierError> {
+    match from {
+        RangeBound::Cursor(cursor) => Ok(Some(cursor.clone())),
+        RangeBound::Timestamp(date) => match (date.timestamp_millis() - 1).try_into() {
+            Ok(timestamp) => {
+                get_cursor_at_timestamp_api(
+                    &provider_context.environment,
+                    &provider_context.deployment,
+                    timestamp,
+                )
+                .await
+            }
+            Err(_) => Ok(None),
+        },
+    }
+}
+
+/// Resolves the end of a range to the cursor of the last transaction in it.
+///
+/// # Arguments
+///
+/// * `to` - The end of the range, a cursor is used as is.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The cursor of the last transaction uploaded at or before a timestamp, or `None` if no
+/// transaction was uploaded by then.
+async fn resolve_to(
+    to: &RangeBound,
+    provider_context: &ProviderContext,
+) -> Result<Option<String>, MomokaVerifierError> {
+    match to {
+        RangeBound::Cursor(cursor) => Ok(Some(cursor.clone())),
+        RangeBound::Timestamp(date) => match date.timestamp_millis().try_into() {
+            Ok(timestamp) => {
+                get_cursor_at_timestamp_api(
+                    &provider_context.environment,
+                    &provider_context.deployment,
+                    timestamp,
+                )
+                .await
+            }
+            Err(_) => Ok(None),
+        },
+    }
+}
+
+/// Verifies the next page of transactions of a range.
+///
+/// # Arguments
+///
+/// * `after` - The cursor the page is pulled after, `None` to start at the first transaction.
+/// * `to_cursor` - The cursor of the last transaction in the range.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The verified page, or an error if bundlr could not list the page or it could not be checked.
+pub async fn verify_range_page(
+    after: &Option<String>,
+    to_cursor: &str,
+    provider_context: &ProviderContext,
+) -> Result<RangePage, MomokaVerifierError> {
+    let page = get_transactions_api(
+        &provider_context.environment,
+        &provider_context.deployment,
+        RANGE_PAGE_SIZE,
+        after,
+        TransactionOrder::Ascending,
+        None,
+    )
+    .await?
+    .transactions;
+
+    let mut range_page = RangePage {
+        reached_end: !page.page_info.has_next_page,
+        ..Default::default()
+    };
+    // ordered so the requests made for the page are the same every time, for replaying them
+    let mut tx_ids = BTreeSet::new();
+    for edge in page.edges {
+        tx_ids.insert(edge.node.id);
+        range_page.next = Some(edge.cursor);
+        range_page.last_timestamp = edge.node.timestamp;
+        if range_page.next.as_deref() == Some(to_cursor) {
+            range_page.reached_end = true;
+            break;
+        }
+    }
+
+    if tx_ids.is_empty() {
+        range_page.reached_end = true;
+        range_page.next = after.clone();
+        return Ok(range_page);
+    }
+
+    range_page.summary.listed = tx_ids.len();
+    // every listed transaction gets an outcome, so one which could not be fetched is not lost
+    let outcomes = check_proof_outcomes(
+        &tx_ids.into_iter().collect(),
+        provider_context.transaction_filter.as_ref(),
+        provider_context,
+    )
+    .await?;
+    for outcome in outcomes {
+        range_page.summary.record(&outcome.result);
+    }
+
+    Ok(range_page)
+}
+
+/// Verifies every transaction between two bounds and summarises the verdicts.
+///
+/// The transactions after the `from` cursor (or uploaded at or after the `from` timestamp) are
+/// verified page by page, up to and including the transaction at the `to` cursor (or the last one
+/// uploaded at or before the `to` timestamp).
+///
+/// # Arguments
+///
+/// * `from` - The start of the range.
+/// * `to` - The end of the range.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The summary of the range, or an error if bundlr could not list the range or a page could not be checked.
+pub async fn verify_range(
+    from: &RangeBound,
+    to: &RangeBound,
+    provider_context: &ProviderContext,
+) -> Result<RangeSummary, MomokaVerifierError> {
+    let mut summary = RangeSummary::default();
+
+    if let (RangeBound::Timestamp(from), RangeBound::Timestamp(to)) = (from, to) {
+        if from > to {
+            return Ok(summary);
+        }
+    }
+
+    let Some(to_cursor) = resolve_to(to, provider_context).await? else {
+        return Ok(summary);
+    };
+    let mut next = resolve_from(from, provider_context).await?;
+    // the range is pulled after the start cursor, so a range ending there is empty
+    if next.as_ref() == Some(&to_cursor) {
+        return Ok(summary);
+    }
+
+    info!("Verifying transactions from {} to {}", from, to);
+
+    loop {
+        let page = verify_range_page(&next, &to_cursor, provider_context).await?;
+        summary.merge(&page.summary);
+
+        next = page.next;
+        if page.reached_end {
+            break;
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_range_bounds() {
+        assert_eq!(
+            RangeBound::from_str("1686830000"),
+            Ok(RangeBound::Timestamp(
+                Utc.timestamp_opt(1686830000, 0).unwrap()
+            ))
+        );
+        assert_eq!(
+            RangeBound::from_str("2023-06-15T11:53:20Z"),
+            Ok(RangeBound::Timestamp(
+                Utc.timestamp_opt(1686830000, 0).unwrap()
+            ))
+        );
+        assert_eq!(
+            RangeBound::from_str("MTY4NjgzMDAwMDAwMA"),
+            Ok(RangeBound::Cursor("MTY4NjgzMDAwMDAwMA".to_string()))
+        );
+        assert!(RangeBound::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_summary_records_unchecked_transactions() {
+        let mut summary = RangeSummary {
+            listed: 4,
+            ..Default::default()
+        };
+        summary.record(&Ok(()));
+        summary.record(&Err(MomokaVerifierError::InvalidTxID));
+        summary.record(&Err(MomokaVerifierError::NodesDisagree));
+
+        assert_eq!(
+            (summary.valid, summary.invalid, summary.unchecked),
+            (1, 1, 1)
+        );
+        assert_eq!(summary.filtered_out(), 1);
+
+        // checkpoints written before transactions could be unchecked still load
+        let summary: RangeSummary =
+            serde_json::from_str(r#"{"listed":2,"valid":1,"invalid":1}"#).unwrap();
+        assert_eq!(summary.unchecked, 0);
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::HashSet,
+    io::{self, BufRead},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bundlr::api::CHUNK_SIZE,
+    evm::ProviderContext,
+    types::{
+        content::ContentStatus,
+        transaction::{MomokaTxId, TransactionAction},
+        verifier_error::MomokaVerifierError,
+    },
+    verifier::proof::{check_proof_outcomes, ProofOutcome},
+};
+
+/// The verdict on a transaction in a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProofStatus {
+    /// The transaction is valid.
+    Valid,
+    /// The transaction is invalid.
+    Invalid,
+    /// The transaction could not be checked because bundlr, arweave or the nodes could not answer.
+    Unchecked,
+}
+
+impl ProofStatus {
+    /// Returns the verdict a proof check result is reported as.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of checking the proof.
+    pub fn of(result: &Result<(), MomokaVerifierError>) -> Self {
+        match result {
+            Ok(()) => ProofStatus::Valid,
+            Err(error) if error.is_verdict() => ProofStatus::Invalid,
+            Err(_) => ProofStatus::Unchecked,
+        }
+    }
+
+    /// The name of the status as it is written in reports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofStatus::Valid => "VALID",
+            ProofStatus::Invalid => "INVALID",
+            ProofStatus::Unchecked => "UNCHECKED",
+        }
+    }
+}
+
+/// A row of the report, one per transaction ID.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofReportRow {
+    pub tx_id: MomokaTxId,
+    pub status: ProofStatus,
+    /// The variant of the error which made the transaction invalid or unchecked.
+    pub error: Option<MomokaVerifierError>,
+    pub action: Option<TransactionAction>,
+    pub profile_id: Option<String>,
+    pub publication_id: Option<String>,
+    /// The status of the content of a valid publication, `None` if content is not checked.
+    pub content: Option<ContentStatus>,
+}
+
+impl From<ProofOutcome> for ProofReportRow {
+    fn from(outcome: ProofOutcome) -> Self {
+        let momoka_tx = outcome.transaction.as_ref().map(|tx| &tx.momoka_tx);
+
+        ProofReportRow {
+            tx_id: outcome.tx_id,
+            status: ProofStatus::of(&outcome.result),
+            error: outcome.result.err(),
+            action: momoka_tx.and_then(|tx| tx.transaction_type().ok().cloned()),
+            profile_id: momoka_tx.and_then(|tx| tx.profile_id().ok().map(|id| id.to_string())),
+            publication_id: momoka_tx.and_then(|tx| tx.publication_id().ok().cloned()),
+            content: outcome.content,
+        }
+    }
+}
+
+/// The format a report is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+/// Reads the transaction IDs to verify, one per line.
+///
+/// Blank lines and lines starting with `#` are skipped, and an ID listed twice is only verified once.
+///
+/// # Arguments
+///
+/// * `reader` - The file or stdin to read the IDs from.
+///
+/// # Returns
+///
+/// The transaction IDs in the order they were first listed.
+pub fn read_tx_ids(reader: impl BufRead) -> io::Result<Vec<MomokaTxId>> {
+    let mut seen = HashSet::new();
+    let mut tx_ids = vec![];
+
+    for line in reader.lines() {
+        let line = line?;
+        let tx_id = line.trim();
+        if tx_id.is_empty() || tx_id.starts_with('#') {
+            continue;
+        }
+        if seen.insert(tx_id.to_string()) {
+            tx_ids.push(tx_id.to_string());
+        }
+    }
+
+    Ok(tx_ids)
+}
+
+/// Verifies every transaction ID and builds a report row for each of them.
+///
+/// # Arguments
+///
+/// * `tx_ids` - The transaction IDs to verify.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// A row for every transaction ID in the same order, or an error if a chunk could not be checked.
+pub async fn verify_tx_ids(
+    tx_ids: &[MomokaTxId],
+    provider_context: &ProviderContext,
+) -> Result<Vec<ProofReportRow>, MomokaVerifierError> {
+    let mut rows = Vec::with_capacity(tx_ids.len());
+
+    for chunk in tx_ids.chunks(CHUNK_SIZE) {
+        let outcomes = check_proof_outcomes(&chunk.to_vec(), None, provider_context).await?;
+        rows.extend(outcomes.into_iter().map(ProofReportRow::from));
+    }
+
+    Ok(rows)
+}
+
+/// Quotes a CSV field if it has a separator, quote or line break in it.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes the report in the given format.
+///
+/// # Arguments
+///
+/// * `rows` - The rows of the report.
+/// * `format` - The format to write.
+///
+/// # Returns
+///
+/// The report, a JSON array of the rows or a CSV file with a header.
+pub fn format_report(rows: &[ProofReportRow], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => serde_json::to_string_pretty(rows).unwrap(),
+        ReportFormat::Csv => {
+            let mut csv = "txId,status,error,action,profileId,publicationId,content\n".to_string();
+            for row in rows {
+                let action = row
+                    .action
+                    .as_ref()
+                    .map(|action| serde_json::to_value(action).unwrap());
+                let fields = [
+                    row.tx_id.as_str(),
+                    row.status.as_str(),
+                    row.error.as_ref().map_or("", |error| error.name()),
+                    action
+                        .as_ref()
+                        .and_then(|action| action.as_str())
+                        .unwrap_or(""),
+                    row.profile_id.as_deref().unwrap_or(""),
+                    row.publication_id.as_deref().unwrap_or(""),
+                    row.content.as_ref().map_or("", |content| content.name()),
+                ];
+                csv.push_str(
+                    &fields
+                        .iter()
+                        .map(|field| csv_field(field))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                );
+                csv.push('\n');
+            }
+            csv
+        }
+    }
+}
+
+/// Returns the exit code for a report.
+///
+/// # Arguments
+///
+/// * `rows` - The rows of the report.
+///
+/// # Returns
+///
+/// `0` if every transaction is valid, `1` if any is invalid, otherwise `2` if any could not be checked.
+pub fn report_exit_code(rows: &[ProofReportRow]) -> i32 {
+    if rows.iter().any(|row| row.status == ProofStatus::Invalid) {
+        1
+    } else if rows.iter().any(|row| row.status == ProofStatus::Unchecked) {
+        2
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn row(tx_id: &str, status: ProofStatus, error: Option<MomokaVerifierError>) -> ProofReportRow {
+        ProofReportRow {
+            tx_id: tx_id.to_string(),
+            status,
+            error,
+            action: Some(TransactionAction::PostCreated),
+            profile_id: Some("0x18".to_string()),
+            publication_id: Some("0x18-0x01-DA-1".to_string()),
+            content: None,
+        }
+    }
+
+    #[test]
+    fn test_read_tx_ids() {
+        let input = "a\n\n# comment\n b \na\nc\n";
+        assert_eq!(
+            read_tx_ids(Cursor::new(input)).unwrap(),
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_format_report() {
+        let rows = vec![
+            ProofReportRow {
+                content: Some(ContentStatus::Unavailable {
+                    reason: "timed out".to_string(),
+                }),
+                ..row("a", ProofStatus::Valid, None)
+            },
+            row(
+                "b",
+                ProofStatus::Invalid,
+                Some(MomokaVerifierError::PublicationNoPointer),
+            ),
+        ];
+
+        assert_eq!(
+            format_report(&rows, ReportFormat::Csv),
+            "txId,status,error,action,profileId,publicationId,content\n\
+             a,VALID,,POST_CREATED,0x18,0x18-0x01-DA-1,UNAVAILABLE\n\
+             b,INVALID,PublicationNoPointer,POST_CREATED,0x18,0x18-0x01-DA-1,\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_report(&rows, ReportFormat::Json)).unwrap();
+        assert_eq!(json[1]["status"], "INVALID");
+        assert_eq!(json[1]["error"], "PublicationNoPointer");
+        assert_eq!(json[1]["action"], "POST_CREATED");
+        assert_eq!(json[0]["content"]["UNAVAILABLE"]["reason"], "timed out");
+    }
+
+    #[test]
+    fn test_report_exit_code() {
+        let valid = row("a", ProofStatus::Valid, None);
+        let unchecked = row(
+            "b",
+            ProofStatus::Unchecked,
+            Some(MomokaVerifierError::NodesDisagree),
+        );
+        let invalid = row(
+            "c",
+            ProofStatus::Invalid,
+            Some(MomokaVerifierError::InvalidTxID),
+        );
+
+        assert_eq!(report_exit_code(std::slice::from_ref(&valid)), 0);
+        assert_eq!(report_exit_code(&[valid.clone(), unchecked.clone()]), 2);
+        assert_eq!(report_exit_code(&[valid, unchecked, invalid]), 1);
+    }
+}
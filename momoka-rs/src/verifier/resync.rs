@@ -0,0 +1,416 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    bundlr::api::{
+        get_cursor_at_timestamp_api, get_first_transaction_api, get_last_transaction_api,
+    },
+    evm::ProviderContext,
+    shutdown::is_shutdown_requested,
+    types::verifier_error::MomokaVerifierError,
+    utils::{check_same_environment, write_atomically},
+    verifier::range::{verify_range_page, RangeSummary},
+};
+
+/// A slice of history verified by its own worker.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncPartition {
+    /// The cursor the partition starts after, `None` for the first partition.
+    pub after: Option<String>,
+    /// The cursor of the last transaction in the partition.
+    pub to: String,
+    /// When the partition starts in milliseconds.
+    pub start_timestamp: u64,
+    /// When the partition ends in milliseconds.
+    pub end_timestamp: u64,
+    /// The cursor of the last page verified, the worker resumes after it.
+    pub checkpoint: Option<String>,
+    /// When the last transaction verified was uploaded in milliseconds.
+    pub checkpoint_timestamp: Option<u64>,
+    /// Whether every transaction in the partition was verified.
+    pub done: bool,
+    /// What was found verifying the partition so far.
+    pub summary: RangeSummary,
+}
+
+impl ResyncPartition {
+    /// How far through the partition the worker is, from 0 to 1.
+    fn progress(&self) -> f64 {
+        if self.done {
+            return 1.0;
+        }
+
+        let duration = self.end_timestamp.saturating_sub(self.start_timestamp);
+        match self.checkpoint_timestamp {
+            Some(timestamp) if duration > 0 => {
+                (timestamp.saturating_sub(self.start_timestamp) as f64 / duration as f64).min(1.0)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// The plan of a resync and how far each partition got, written to disk after every page.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncCheckpoint {
+    /// The environment the resync is of.
+    pub environment: String,
+    /// The deployment the resync is of.
+    pub deployment: String,
+    /// The cursor of the last transaction when the resync was planned, following resumes after it.
+    pub end_cursor: String,
+    /// The partitions of history.
+    pub partitions: Vec<ResyncPartition>,
+}
+
+impl ResyncCheckpoint {
+    /// How far through the whole resync the workers are, from 0 to 1, weighting each partition by how much time it covers.
+    pub fn progress(&self) -> f64 {
+        let total: u64 = self
+            .partitions
+            .iter()
+            .map(|partition| partition.end_timestamp - partition.start_timestamp)
+            .sum();
+
+        if total == 0 {
+            let done = self.partitions.iter().filter(|p| p.done).count();
+            return done as f64 / self.partitions.len().max(1) as f64;
+        }
+
+        self.partitions
+            .iter()
+            .map(|partition| {
+                partition.progress() * (partition.end_timestamp - partition.start_timestamp) as f64
+            })
+            .sum::<f64>()
+            / total as f64
+    }
+
+    /// Adds up what was found verifying every partition so far.
+    pub fn summary(&self) -> RangeSummary {
+        let mut summary = RangeSummary::default();
+        for partition in &self.partitions {
+            summary.merge(&partition.summary);
+        }
+
+        summary
+    }
+
+    /// Returns whether every partition was verified.
+    pub fn is_done(&self) -> bool {
+        self.partitions.iter().all(|partition| partition.done)
+    }
+}
+
+/// Splits the time between two timestamps into even partitions.
+///
+/// # Arguments
+///
+/// * `start` - When the first transaction was uploaded in milliseconds.
+/// * `end` - When the last transaction was uploaded in milliseconds.
+/// * `partitions` - How many partitions to split into.
+///
+/// # Returns
+///
+/// The start and end timestamp of each partition, each one starting where the previous ended.
+fn partition_timestamps(start: u64, end: u64, partitions: usize) -> Vec<(u64, u64)> {
+    let partitions = partitions.max(1) as u64;
+    let width = end.saturating_sub(start) / partitions;
+
+    (0..partitions)
+        .map(|i| {
+            let partition_start = start + width * i;
+            let partition_end = if i == partitions - 1 {
+                end
+            } else {
+                start + width * (i + 1)
+            };
+            (partition_start, partition_end)
+        })
+        .collect()
+}
+
+/// Plans a resync of the whole history, resolving the partition boundaries to bundlr cursors.
+///
+/// # Arguments
+///
+/// * `partitions` - How many partitions to split history into.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The checkpoint of the planned resync, with nothing verified yet.
+async fn plan_resync(
+    partitions: usize,
+    provider_context: &ProviderContext,
+) -> Result<ResyncCheckpoint, MomokaVerifierError> {
+    let environment = &provider_context.environment;
+    let deployment = &provider_context.deployment;
+
+    let first = get_first_transaction_api(environment, deployment).await?;
+    let last = get_last_transaction_api(environment, deployment).await?;
+
+    let start = first.node.timestamp.unwrap_or_default();
+    let end = last.node.timestamp.unwrap_or(start);
+
+    let mut planned = vec![];
+    let mut after: Option<String> = None;
+    let timestamps = partition_timestamps(start, end, partitions);
+    let count = timestamps.len();
+    for (i, (start_timestamp, end_timestamp)) in timestamps.into_iter().enumerate() {
+        let to = if i == count - 1 {
+            Some(last.cursor.clone())
+        } else {
+            get_cursor_at_timestamp_api(environment, deployment, end_timestamp).await?
+        };
+
+        // nothing was uploaded in the partition when it ends where the previous one did
+        let Some(to) = to else {
+            continue;
+        };
+        if after.as_ref() == Some(&to) {
+            continue;
+        }
+
+        planned.push(ResyncPartition {
+            after: after.clone(),
+            to: to.clone(),
+            start_timestamp,
+            end_timestamp,
+            checkpoint: None,
+            checkpoint_timestamp: None,
+            done: false,
+            summary: RangeSummary::default(),
+        });
+        after = Some(to);
+    }
+
+    Ok(ResyncCheckpoint {
+        environment: format!("{:?}", environment),
+        deployment: format!("{:?}", deployment),
+        end_cursor: last.cursor,
+        partitions: planned,
+    })
+}
+
+/// Reads the checkpoint of a resync from disk.
+///
+/// # Arguments
+///
+/// * `path` - The path of the checkpoint file.
+///
+/// # Returns
+///
+/// The checkpoint, or `None` if there is no checkpoint file or it can not be read.
+fn read_checkpoint(path: &str) -> Option<ResyncCheckpoint> {
+    fs::read(path)
+        .ok()
+        .and_then(|contents| serde_json::from_slice(&contents).ok())
+}
+
+/// Writes the checkpoint of a resync to disk.
+///
+/// The file is written atomically so an interrupted write never leaves a partial checkpoint.
+/// Failing to write is logged and ignored, the pages since the last checkpoint will just be
+/// verified again when resuming.
+///
+/// # Arguments
+///
+/// * `path` - The path of the checkpoint file.
+/// * `checkpoint` - The checkpoint to write.
+fn write_checkpoint(path: &str, checkpoint: &ResyncCheckpoint) {
+    if let Err(error) = write_atomically(Path::new(path), &serde_json::to_vec(checkpoint).unwrap())
+    {
+        warn!("Could not write the resync checkpoint - {}", error);
+    }
+}
+
+/// Logs the combined progress of every partition.
+///
+/// # Arguments
+///
+/// * `checkpoint` - The checkpoint of the resync.
+fn log_progress(checkpoint: &ResyncCheckpoint) {
+    let summary = checkpoint.summary();
+    info!(
+        "Resync {:.1}% complete - {}/{} partitions done, {} valid, {} invalid, {} could not be checked, {} filtered out",
+        checkpoint.progress() * 100.0,
+        checkpoint.partitions.iter().filter(|p| p.done).count(),
+        checkpoint.partitions.len(),
+        summary.valid,
+        summary.invalid,
+        summary.unchecked,
+        summary.filtered_out()
+    );
+}
+
+/// Verifies one partition page by page, checkpointing after every page.
+///
+/// The worker stops after the page it is on once a shutdown is requested, the partition carries
+/// on from the checkpoint when the resync is resumed.
+///
+/// # Arguments
+///
+/// * `index` - The index of the partition in the checkpoint.
+/// * `checkpoint` - The checkpoint shared by every worker.
+/// * `checkpoint_path` - The path the checkpoint is written to.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// `Ok(())` once the partition is verified or a shutdown is requested, or the error a page could
+/// not be checked with.
+async fn resync_partition(
+    index: usize,
+    checkpoint: Arc<Mutex<ResyncCheckpoint>>,
+    checkpoint_path: &str,
+    provider_context: &ProviderContext,
+) -> Result<(), MomokaVerifierError> {
+    let (mut next, to) = {
+        let checkpoint = checkpoint.lock().unwrap();
+        let partition = &checkpoint.partitions[index];
+        if partition.done {
+            return Ok(());
+        }
+        (
+            partition.checkpoint.clone().or(partition.after.clone()),
+            partition.to.clone(),
+        )
+    };
+
+    loop {
+        let page = verify_range_page(&next, &to, provider_context).await?;
+        next = page.next.clone();
+
+        let mut checkpoint = checkpoint.lock().unwrap();
+        let partition = &mut checkpoint.partitions[index];
+        partition.checkpoint = page.next;
+        partition.checkpoint_timestamp = page.last_timestamp.or(partition.checkpoint_timestamp);
+        partition.done = page.reached_end;
+        partition.summary.merge(&page.summary);
+
+        write_checkpoint(checkpoint_path, &checkpoint);
+        log_progress(&checkpoint);
+
+        if page.reached_end || is_shutdown_requested() {
+            return Ok(());
+        }
+    }
+}
+
+/// Resyncs the whole history with a worker per partition, resuming from the checkpoint file if there is one.
+///
+/// # Arguments
+///
+/// * `partitions` - How many partitions to split history into when planning a new resync.
+/// * `checkpoint_path` - The path of the checkpoint file.
+/// * `provider_context` - The provider context.
+///
+/// # Returns
+///
+/// The checkpoint, finished unless a shutdown was requested. Its `end_cursor` is where following
+/// new transactions carries on from.
+pub async fn resync(
+    partitions: usize,
+    checkpoint_path: &str,
+    provider_context: &ProviderContext,
+) -> Result<ResyncCheckpoint, MomokaVerifierError> {
+    let checkpoint = match read_checkpoint(checkpoint_path) {
+        Some(checkpoint) => {
+            check_same_environment(
+                &format!("The resync checkpoint {}", checkpoint_path),
+                &checkpoint.environment,
+                &checkpoint.deployment,
+                provider_context,
+            )?;
+
+            info!("Resuming resync from the checkpoint {}", checkpoint_path);
+            checkpoint
+        }
+        None => {
+            info!(
+                "Planning resync in {} partitions, checkpointing to {}",
+                partitions, checkpoint_path
+            );
+            let checkpoint = plan_resync(partitions, provider_context).await?;
+            write_checkpoint(checkpoint_path, &checkpoint);
+            checkpoint
+        }
+    };
+
+    if checkpoint.is_done() {
+        info!(
+            "The resync checkpointed to {} is already complete, remove it to resync again",
+            checkpoint_path
+        );
+        return Ok(checkpoint);
+    }
+
+    log_progress(&checkpoint);
+
+    let count = checkpoint.partitions.len();
+    let checkpoint = Arc::new(Mutex::new(checkpoint));
+    futures::future::try_join_all((0..count).map(|index| {
+        resync_partition(index, checkpoint.clone(), checkpoint_path, provider_context)
+    }))
+    .await?;
+
+    let checkpoint = checkpoint.lock().unwrap().clone();
+    Ok(checkpoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partition(start_timestamp: u64, end_timestamp: u64) -> ResyncPartition {
+        ResyncPartition {
+            after: None,
+            to: "cursor".to_string(),
+            start_timestamp,
+            end_timestamp,
+            checkpoint: None,
+            checkpoint_timestamp: None,
+            done: false,
+            summary: RangeSummary::default(),
+        }
+    }
+
+    #[test]
+    fn test_partition_timestamps() {
+        assert_eq!(
+            partition_timestamps(0, 100, 3),
+            vec![(0, 33), (33, 66), (66, 100)]
+        );
+        assert_eq!(partition_timestamps(10, 10, 2), vec![(10, 10), (10, 10)]);
+        assert_eq!(partition_timestamps(0, 100, 0), vec![(0, 100)]);
+    }
+
+    #[test]
+    fn test_progress_is_weighted_by_partition_duration() {
+        let mut checkpoint = ResyncCheckpoint {
+            environment: "Polygon".to_string(),
+            deployment: "Production".to_string(),
+            end_cursor: "cursor".to_string(),
+            partitions: vec![partition(0, 300), partition(300, 400)],
+        };
+        assert_eq!(checkpoint.progress(), 0.0);
+
+        checkpoint.partitions[0].checkpoint_timestamp = Some(150);
+        checkpoint.partitions[1].done = true;
+        assert_eq!(checkpoint.progress(), 0.625);
+        assert!(!checkpoint.is_done());
+
+        checkpoint.partitions[0].done = true;
+        assert_eq!(checkpoint.progress(), 1.0);
+        assert!(checkpoint.is_done());
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use ethers::core::rand::{seq::SliceRandom, thread_rng, Rng};
+
+use crate::{
+    types::{transaction::MomokaTxId, verifier_error::MomokaVerifierError},
+    verifier::report::ProofStatus,
+};
+
+/// The z-score of the confidence the upper bound of the invalid rate is given at (95%).
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// A running audit which verifies a random sample of the transactions instead of all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingAudit {
+    /// The fraction of transactions which are verified, from 0 to 1.
+    pub rate: f64,
+    /// How many transactions were listed to sample from.
+    pub seen: usize,
+    /// How many sampled transactions got a verdict.
+    pub checked: usize,
+    /// How many sampled transactions were invalid.
+    pub invalid: usize,
+    /// How many sampled transactions could not be checked, for example because a node was down.
+    pub unchecked: usize,
+}
+
+impl SamplingAudit {
+    /// Creates an audit verifying the given fraction of transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The fraction of transactions to verify, from 0 to 1.
+    pub fn new(rate: f64) -> Self {
+        SamplingAudit {
+            rate,
+            seen: 0,
+            checked: 0,
+            invalid: 0,
+            unchecked: 0,
+        }
+    }
+
+    /// Picks the transactions of a page which are verified.
+    ///
+    /// Each transaction is picked with the audit's rate. If none is picked but the expected sample
+    /// of the page is at least half a transaction, one is picked at random so small rates still
+    /// sample small pages.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_ids` - The transactions of the page.
+    ///
+    /// # Returns
+    ///
+    /// The transactions to verify.
+    pub fn sample(&mut self, tx_ids: &[MomokaTxId]) -> Vec<MomokaTxId> {
+        self.seen += tx_ids.len();
+
+        let mut rng = thread_rng();
+        let mut sample: Vec<MomokaTxId> = tx_ids
+            .iter()
+            .filter(|_| rng.gen_bool(self.rate))
+            .cloned()
+            .collect();
+
+        if sample.is_empty() && tx_ids.len() as f64 * self.rate >= 0.5 {
+            sample.extend(tx_ids.choose(&mut rng).cloned());
+        }
+
+        sample
+    }
+
+    /// Records the verdicts of the sampled transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - The result of each sampled transaction, including those which could not be
+    ///   fetched or parsed.
+    pub fn record(&mut self, results: &[Result<(), MomokaVerifierError>]) {
+        for result in results {
+            match ProofStatus::of(result) {
+                ProofStatus::Valid => self.checked += 1,
+                ProofStatus::Invalid => {
+                    self.checked += 1;
+                    self.invalid += 1;
+                }
+                ProofStatus::Unchecked => self.unchecked += 1,
+            }
+        }
+    }
+
+    /// Returns the upper bound of the submitter's invalid rate at 95% confidence.
+    ///
+    /// This is the upper end of the Wilson score interval of the sampled invalid rate, which
+    /// stays meaningful when no invalid transaction has been sampled yet.
+    pub fn invalid_rate_upper_bound(&self) -> f64 {
+        if self.checked == 0 {
+            return 1.0;
+        }
+
+        let n = self.checked as f64;
+        let p = self.invalid as f64 / n;
+        let z2 = CONFIDENCE_Z * CONFIDENCE_Z;
+
+        let centre = p + z2 / (2.0 * n);
+        let margin = CONFIDENCE_Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+
+        ((centre + margin) / (1.0 + z2 / n)).min(1.0)
+    }
+}
+
+impl Display for SamplingAudit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "checked {} of {} transactions, {} invalid, {} could not be checked - invalid rate at most {:.3}% at 95% confidence",
+            self.checked,
+            self.seen,
+            self.invalid,
+            self.unchecked,
+            self.invalid_rate_upper_bound() * 100.0
+        )
+    }
+}
+
+/// Parses a sample rate given as a fraction (`0.01`) or a percentage (`1%`).
+///
+/// # Arguments
+///
+/// * `value` - The sample rate.
+///
+/// # Returns
+///
+/// The fraction of transactions to verify, or a message saying why it is not a rate.
+pub fn parse_sample_rate(value: &str) -> Result<f64, String> {
+    let rate = match value.strip_suffix('%') {
+        Some(percentage) => percentage.parse::<f64>().map(|rate| rate / 100.0),
+        None => value.parse::<f64>(),
+    }
+    .map_err(|_| format!("{} is not a sample rate", value))?;
+
+    if rate > 0.0 && rate <= 1.0 {
+        Ok(rate)
+    } else {
+        Err(format!("{} is not between 0 and 100%", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sample_rate() {
+        assert_eq!(parse_sample_rate("0.01"), Ok(0.01));
+        assert_eq!(parse_sample_rate("1%"), Ok(0.01));
+        assert!(parse_sample_rate("0").is_err());
+        assert!(parse_sample_rate("150%").is_err());
+        assert!(parse_sample_rate("abc").is_err());
+    }
+
+    #[test]
+    fn test_invalid_rate_upper_bound() {
+        let mut audit = SamplingAudit::new(0.01);
+        assert_eq!(audit.invalid_rate_upper_bound(), 1.0);
+
+        // no invalid transaction in 1000 checked bounds the rate below half a percent
+        audit.record(&vec![Ok(()); 1000]);
+        let bound = audit.invalid_rate_upper_bound();
+        assert!(bound > 0.003 && bound < 0.004, "{}", bound);
+
+        // errors which are not a verdict do not count against the submitter
+        // a transaction bundlr did not return, or which could not be parsed, is a verdict too
+        audit.record(&[
+            Err(MomokaVerifierError::NodesDisagree),
+            Err(MomokaVerifierError::PublicationNoPointer),
+            Err(MomokaVerifierError::InvalidTxID),
+        ]);
+        assert_eq!(audit.checked, 1002);
+        assert_eq!(audit.invalid, 2);
+        assert_eq!(audit.unchecked, 1);
+        assert!(audit.invalid_rate_upper_bound() > bound);
+    }
+
+    #[test]
+    fn test_sample_picks_at_least_one_of_a_big_enough_page() {
+        let tx_ids: Vec<MomokaTxId> = (0..100).map(|i| i.to_string()).collect();
+
+        let mut audit = SamplingAudit::new(0.005);
+        assert!(!audit.sample(&tx_ids).is_empty());
+        assert_eq!(audit.seen, 100);
+
+        let mut audit = SamplingAudit::new(1.0);
+        assert_eq!(audit.sample(&tx_ids).len(), 100);
+    }
+}
@@ -7,7 +7,13 @@ use ethers::types::{
 use serde::Serialize;
 use serde_json::Value;
 
-use crate::types::verifier_error::MomokaVerifierError;
+use crate::{
+    contracts::lens_hub::{LensProfileDetails, ProfileDetailsKey},
+    types::{
+        verification_report::{VerificationCheck, VerificationReport},
+        verifier_error::MomokaVerifierError,
+    },
+};
 
 /// Recovers the address from a signed typed data using a given signature.
 ///
@@ -35,9 +41,9 @@ pub fn recovery_signed_typed_data<TValue: Serialize>(
 
     let message: BTreeMap<String, Value> = serde_json::from_str(
         &serde_json::to_string(value)
-            .map_err(|_| MomokaVerifierError::InvalidFormattedTypedData)?,
+            .map_err(|e| MomokaVerifierError::InvalidFormattedTypedData { source: e.into() })?,
     )
-    .map_err(|_| MomokaVerifierError::InvalidFormattedTypedData)?;
+    .map_err(|e| MomokaVerifierError::InvalidFormattedTypedData { source: e.into() })?;
 
     let typed_data: TypedData = TypedData {
         domain: domain.clone(),
@@ -48,14 +54,146 @@ pub fn recovery_signed_typed_data<TValue: Serialize>(
 
     let hash = typed_data
         .encode_eip712()
-        .map_err(|_| MomokaVerifierError::InvalidFormattedTypedData)?;
+        .map_err(|e| MomokaVerifierError::InvalidFormattedTypedData { source: e.into() })?;
 
     let signature = Signature::from_str(signature)
-        .map_err(|_| MomokaVerifierError::InvalidFormattedTypedData)?;
+        .map_err(|e| MomokaVerifierError::InvalidFormattedTypedData { source: e.into() })?;
 
     let address = signature
         .recover(hash)
-        .map_err(|_| MomokaVerifierError::InvalidFormattedTypedData)?;
+        .map_err(|e| MomokaVerifierError::InvalidFormattedTypedData { source: e.into() })?;
 
     Ok(address)
 }
+
+/// Formats a module address for a report, or `none` if the publication does not set the module.
+///
+/// # Arguments
+///
+/// * `module` - The module address, if one is set.
+pub fn or_none(module: Option<Address>) -> String {
+    module.map_or("none".to_string(), |module| format!("{:?}", module))
+}
+
+/// Checks the modules set by a comment or mirror were whitelisted at the publication block.
+///
+/// # Arguments
+///
+/// * `key` - The key the profile details were read for.
+/// * `profile_details` - The profile details read at the publication block.
+///
+/// # Returns
+///
+/// * `Result<(), MomokaVerifierError>` - Ok(()) if the modules were whitelisted, or an Err with the module which was not.
+fn check_modules_whitelisted(
+    key: &ProfileDetailsKey,
+    profile_details: &LensProfileDetails,
+) -> Result<(), MomokaVerifierError> {
+    match (key.collect_module, key.reference_module) {
+        (Some(module), _) if !profile_details.collect_module_whitelisted => {
+            Err(MomokaVerifierError::CollectModuleNotWhitelisted { module })
+        }
+        (_, Some(module)) if !profile_details.reference_module_whitelisted => {
+            Err(MomokaVerifierError::ReferenceModuleNotWhitelisted { module })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks the modules set by a comment or mirror were whitelisted at the publication block and
+/// records the check on the report.
+///
+/// # Arguments
+///
+/// * `report` - The report of the checks run on the transaction.
+/// * `key` - The key the profile details were read for.
+/// * `profile_details` - The profile details read at the publication block.
+///
+/// # Returns
+///
+/// * `Result<(), MomokaVerifierError>` - Ok(()) if the modules were whitelisted, or an Err with the module which was not.
+pub fn record_modules_whitelisted(
+    report: &mut VerificationReport,
+    key: &ProfileDetailsKey,
+    profile_details: &LensProfileDetails,
+) -> Result<(), MomokaVerifierError> {
+    report.record(
+        VerificationCheck::ModulesWhitelisted,
+        Some(format!(
+            "collect module {} reference module {}",
+            or_none(key.collect_module),
+            or_none(key.reference_module)
+        )),
+        Some(format!(
+            "collect module whitelisted {} reference module whitelisted {}",
+            profile_details.collect_module_whitelisted,
+            profile_details.reference_module_whitelisted
+        )),
+        check_modules_whitelisted(key, profile_details),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+
+    use super::*;
+
+    #[test]
+    fn test_rejects_modules_which_are_not_whitelisted() {
+        let collect_module = Address::from_low_u64_be(1);
+        let reference_module = Address::from_low_u64_be(2);
+        let key = ProfileDetailsKey {
+            profile_id: U256::one(),
+            signer: Address::zero(),
+            block_number: 1,
+            collect_module: Some(collect_module),
+            reference_module: Some(reference_module),
+        };
+        let profile_details =
+            |collect_module_whitelisted, reference_module_whitelisted| LensProfileDetails {
+                sig_nonce: U256::zero(),
+                current_publication_id: U256::zero(),
+                dispatcher_address: Address::zero(),
+                owner_of_address: Address::zero(),
+                collect_module_whitelisted,
+                reference_module_whitelisted,
+            };
+
+        let mut report = VerificationReport::new("tx".to_string());
+        assert_eq!(
+            record_modules_whitelisted(&mut report, &key, &profile_details(true, true)),
+            Ok(())
+        );
+        assert_eq!(
+            record_modules_whitelisted(&mut report, &key, &profile_details(false, true)),
+            Err(MomokaVerifierError::CollectModuleNotWhitelisted {
+                module: collect_module
+            })
+        );
+        assert_eq!(
+            record_modules_whitelisted(&mut report, &key, &profile_details(true, false)),
+            Err(MomokaVerifierError::ReferenceModuleNotWhitelisted {
+                module: reference_module
+            })
+        );
+
+        // a mirror sets no collect module, only its reference module is checked
+        let mirror = ProfileDetailsKey {
+            collect_module: None,
+            ..key
+        };
+        assert_eq!(
+            record_modules_whitelisted(&mut report, &mirror, &profile_details(false, true)),
+            Ok(())
+        );
+
+        assert_eq!(report.checks.len(), 4);
+        assert_eq!(
+            report.checks[1].error,
+            Some(MomokaVerifierError::CollectModuleNotWhitelisted {
+                module: collect_module
+            })
+        );
+    }
+}
@@ -1,17 +1,19 @@
 use crate::{
-    contracts::lens_hub::get_profile_details,
-    environment::environment_to_lens_hub_contract,
+    contracts::lens_hub::ProfileDetailsKey,
     evm::ProviderContext,
     types::{
         eip721::{CreateMirrorEIP712TypedData, CreateMirrorEIP712TypedDataValue},
         evm_event::MirrorCreatedEventEmittedResponse,
+        publication_id::PublicationId,
         transaction::{MirrorCreatedPublication, TransactionPointerType},
+        verification_report::{VerificationCheck, VerificationReport},
         verifier_error::MomokaVerifierError,
     },
+    verifier::batch::read_profile_details,
 };
 use ethers::types::{Address, U256};
 
-use super::common::recovery_signed_typed_data;
+use super::common::{record_modules_whitelisted, recovery_signed_typed_data};
 
 /// Retrieves the address of the signer who signed the given mirror typed data using the provided signature.
 ///
@@ -23,7 +25,7 @@ use super::common::recovery_signed_typed_data;
 /// # Returns
 ///
 /// The address of the signer on success, or an error of type `MomokaVerifierError` if the operation fails.
-fn who_signed_typed_data(
+pub fn who_signed_typed_data(
     signature: &str,
     typed_data: &CreateMirrorEIP712TypedData,
 ) -> Result<Address, MomokaVerifierError> {
@@ -36,6 +38,31 @@ fn who_signed_typed_data(
     )
 }
 
+/// Builds the key the profile details of the mirror are read for.
+///
+/// # Arguments
+///
+/// * `publication` - The mirror publication.
+/// * `signer` - The address which signed the typed data.
+///
+/// # Returns
+///
+/// The profile, signer, block and modules of the mirror. The reference module is left out when it is not set.
+pub fn profile_details_key(
+    publication: &MirrorCreatedPublication,
+    signer: Address,
+) -> ProfileDetailsKey {
+    let value = &publication.typed_data().value;
+
+    ProfileDetailsKey {
+        profile_id: publication.profile_id().into(),
+        signer,
+        block_number: publication.chain_proofs.this_publication.block_number,
+        collect_module: None,
+        reference_module: (!value.reference_module.is_zero()).then_some(value.reference_module),
+    }
+}
+
 /// Cross-checks the event emitted in the MirrorCreated transaction
 /// with the provided pub_count_at_block and typed_data.
 ///
@@ -62,11 +89,17 @@ fn cross_check_event(
         || !event.reference_module_return_data.is_empty()
         || !value.reference_module_init_data.is_empty()
     {
-        return Err(MomokaVerifierError::EventMismatch);
+        return Err(MomokaVerifierError::EventMismatch {
+            expected_pub_id: None,
+            actual_pub_id: None,
+        });
     }
 
     if pub_count_at_block + U256::one() != event.pub_id.clone().into() {
-        return Err(MomokaVerifierError::EventMismatch);
+        return Err(MomokaVerifierError::EventMismatch {
+            expected_pub_id: Some(pub_count_at_block + U256::one()),
+            actual_pub_id: Some(event.pub_id.clone().into()),
+        });
     }
 
     Ok(())
@@ -83,6 +116,7 @@ fn cross_check_event(
 ///
 /// * `publication` - A reference to the `MirrorCreatedPublication` to be verified.
 /// * `provider_context` - The provider context.
+/// * `report` - The report the profile details and event checks are recorded in.
 ///
 /// # Returns
 ///
@@ -97,6 +131,8 @@ fn cross_check_event(
 /// * `PublicationNoneDA` - If the pointer type is not on the Data Availability (DA) chain.
 /// * `PublicationNonceInvalid` - If the signature nonce does not match the publication nonce.
 /// * `PublicationSignerNotAllowed` - If the signer address is not allowed based on the profile details.
+/// * `CollectModuleNotWhitelisted` - If the collect module was not whitelisted at the publication block.
+/// * `ReferenceModuleNotWhitelisted` - If the reference module was not whitelisted at the publication block.
 /// * `EventMismatch` - If the event fails to cross-check with the profile's current publication ID.
 /// * `GetProfileDetailsError` - If there is an error retrieving profile details from the Ethereum node.
 /// * `WhoSignedTypedDataError` - If there is an error verifying the signature of the publication.
@@ -104,6 +140,7 @@ fn cross_check_event(
 pub async fn verifier_mirror(
     publication: &MirrorCreatedPublication,
     provider_context: &ProviderContext,
+    report: &mut VerificationReport,
 ) -> Result<(), MomokaVerifierError> {
     let pointer = publication
         .chain_proofs
@@ -119,28 +156,52 @@ pub async fn verifier_mirror(
 
     let address: Address = who_signed_typed_data(publication.signature(), typed_data)?;
 
-    let profile_details = get_profile_details(
-        environment_to_lens_hub_contract(&provider_context.environment).unwrap(),
-        publication.profile_id(),
-        address,
-        publication.chain_proofs.this_publication.block_number,
-        &provider_context.node,
-    )
-    .await?;
+    let key = profile_details_key(publication, address);
+    let profile_details = read_profile_details(&key, provider_context).await?;
 
-    if &profile_details.sig_nonce.as_u64() != publication.nonce() {
-        return Err(MomokaVerifierError::PublicationNonceInvalid);
-    }
+    let expected_nonce = profile_details.sig_nonce.as_u64();
+    report.record(
+        VerificationCheck::ProfileDetails,
+        Some(format!(
+            "nonce {} signed by {:?}",
+            publication.nonce(),
+            address
+        )),
+        Some(format!(
+            "nonce {} dispatcher {:?} owner {:?}",
+            profile_details.sig_nonce,
+            profile_details.dispatcher_address,
+            profile_details.owner_of_address
+        )),
+        if &expected_nonce != publication.nonce() {
+            Err(MomokaVerifierError::PublicationNonceInvalid {
+                expected: expected_nonce,
+                actual: *publication.nonce(),
+            })
+        } else if profile_details.dispatcher_address != address
+            && profile_details.owner_of_address != address
+        {
+            Err(MomokaVerifierError::PublicationSignerNotAllowed {
+                signer: address,
+                owner: profile_details.owner_of_address,
+                dispatcher: profile_details.dispatcher_address,
+            })
+        } else {
+            Ok(())
+        },
+    )?;
 
-    if profile_details.dispatcher_address != address && profile_details.owner_of_address != address
-    {
-        return Err(MomokaVerifierError::PublicationSignerNotAllowed);
-    }
+    record_modules_whitelisted(report, &key, &profile_details)?;
 
-    cross_check_event(
-        &publication.event,
-        &profile_details.current_publication_id,
-        &typed_data.value,
+    report.record(
+        VerificationCheck::EventCrossCheck,
+        Some(PublicationId::new(profile_details.current_publication_id + U256::one()).to_string()),
+        Some(publication.event.pub_id.to_string()),
+        cross_check_event(
+            &publication.event,
+            &profile_details.current_publication_id,
+            &typed_data.value,
+        ),
     )?;
 
     Ok(())
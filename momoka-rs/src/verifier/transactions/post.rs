@@ -1,14 +1,86 @@
-use crate::contracts::lens_hub::{lens_hub_contract, Eip712Signature, ILensHub, PostWithSigData};
+use crate::cache::read_batched_block_cache;
+use crate::contracts::lens_hub::{
+    lens_hub_contract, Eip712Signature, GetPubCountCall, ILensHub, PostWithSigCall,
+    PostWithSigData, PostWithSigDispatcherCall,
+};
 use crate::environment::environment_to_lens_hub_contract;
-use crate::evm::{parse_signature, ProviderContext};
-use crate::types::eip721::CreatePostEIP712TypedDataValue;
+use crate::evm::{parse_signature, quorum_read, ProviderContext};
+use crate::simulation::LocalEvm;
+use crate::transport::NodeTransport;
+use crate::types::eip721::{CreatePostEIP712TypedData, CreatePostEIP712TypedDataValue};
 use crate::types::evm_event::PostCreatedEventEmittedResponse;
 use crate::types::profile_id::ProfileId;
-use crate::types::{transaction::PostCreatedPublication, verifier_error::MomokaVerifierError};
+use crate::types::publication_id::PublicationId;
+use crate::types::verification_report::{VerificationCheck, VerificationReport};
+use crate::types::{
+    transaction::PostCreatedPublication,
+    verifier_error::{ErrorSource, MomokaVerifierError},
+};
 
+use ethers::abi::{AbiDecode, AbiEncode};
 use ethers::prelude::*;
 use ethers::types::U256;
 
+use super::common::recovery_signed_typed_data;
+
+/// Retrieves the address of the signer who signed the given post typed data using the provided signature.
+///
+/// # Arguments
+///
+/// * `signature` - The signature of the signer.
+/// * `typed_data` - The post typed data containing the domain, types, and value.
+///
+/// # Returns
+///
+/// The address of the signer on success, or an error of type `MomokaVerifierError` if the operation fails.
+pub fn who_signed_typed_data(
+    signature: &str,
+    typed_data: &CreatePostEIP712TypedData,
+) -> Result<Address, MomokaVerifierError> {
+    recovery_signed_typed_data(
+        signature,
+        &typed_data.domain.to_ethers_type(),
+        &typed_data.types.post_with_sig,
+        &typed_data.value,
+        "PostWithSig".to_string(),
+    )
+}
+
+/// Builds the `postWithSig` call data from the publication typed data and signature.
+///
+/// # Arguments
+///
+/// * `publication` - A reference to the `PostCreatedPublication`.
+///
+/// # Returns
+///
+/// * A `Result` containing the `PostWithSigData` on success, or a `MomokaVerifierError` if the signature could not be parsed.
+fn post_with_sig_data(
+    publication: &PostCreatedPublication,
+) -> Result<PostWithSigData, MomokaVerifierError> {
+    let typed_data_value = publication.typed_data_value().clone();
+
+    let sig = parse_signature(
+        &publication.chain_proofs.this_publication.signature,
+        typed_data_value.deadline,
+    )?;
+
+    Ok(PostWithSigData {
+        profile_id: publication.profile_id().clone().into(),
+        content_uri: typed_data_value.content_uri.clone(),
+        collect_module: typed_data_value.collect_module,
+        collect_module_init_data: typed_data_value.collect_module_init_data.clone().into(),
+        reference_module: typed_data_value.reference_module,
+        reference_module_init_data: typed_data_value.reference_module_init_data.clone().into(),
+        sig: Eip712Signature {
+            v: sig.v,
+            r: sig.r,
+            s: sig.s,
+            deadline: sig.deadline.into(),
+        },
+    })
+}
+
 /// Simulates a transaction by calling the appropriate method on the Lens Hub contract.
 ///
 /// This function takes a `PostCreatedPublication` and simulates the transaction by constructing
@@ -43,30 +115,10 @@ use ethers::types::U256;
 /// }
 /// ```
 async fn simulate_transaction(
-    lens_hub: &ILensHub<&Provider<RetryClient<Http>>>,
+    lens_hub: &ILensHub<&Provider<RetryClient<NodeTransport>>>,
     publication: &PostCreatedPublication,
 ) -> Result<U256, MomokaVerifierError> {
-    let typed_data_value = publication.typed_data_value().clone();
-
-    let sig = parse_signature(
-        &publication.chain_proofs.this_publication.signature,
-        typed_data_value.deadline,
-    )?;
-
-    let sig_request = PostWithSigData {
-        profile_id: publication.profile_id().clone().into(),
-        content_uri: typed_data_value.content_uri.clone(),
-        collect_module: typed_data_value.collect_module,
-        collect_module_init_data: typed_data_value.collect_module_init_data.clone().into(),
-        reference_module: typed_data_value.reference_module,
-        reference_module_init_data: typed_data_value.reference_module_init_data.clone().into(),
-        sig: Eip712Signature {
-            v: sig.v,
-            r: sig.r,
-            s: sig.s,
-            deadline: sig.deadline.into(),
-        },
-    };
+    let sig_request = post_with_sig_data(publication)?;
 
     let block_number = publication.chain_proofs.this_publication.block_number;
 
@@ -76,19 +128,43 @@ async fn simulate_transaction(
             .block(block_number)
             .call()
             .await
-            .map_err(|_| MomokaVerifierError::DataCantBeReadFromNode)?
+            .map_err(simulation_call_error)?
     } else {
         lens_hub
             .post_with_sig(sig_request)
             .block(block_number)
             .call()
             .await
-            .map_err(|_| MomokaVerifierError::DataCantBeReadFromNode)?
+            .map_err(simulation_call_error)?
     };
 
     Ok(result)
 }
 
+/// Maps a failed simulation call on a node to an error.
+///
+/// A revert fails the simulation, the same as a revert in the local EVM does, so both backends
+/// reach the same verdict. Anything else means the node could not be read.
+///
+/// # Arguments
+///
+/// * `error` - The error of the simulation call.
+///
+/// # Returns
+///
+/// * `SimulationFailed` if the call reverted, otherwise `DataCantBeReadFromNode`.
+fn simulation_call_error<M: Middleware>(error: ContractError<M>) -> MomokaVerifierError {
+    if error.is_revert() {
+        MomokaVerifierError::SimulationFailed {
+            source: ErrorSource::message(error),
+        }
+    } else {
+        MomokaVerifierError::DataCantBeReadFromNode {
+            source: ErrorSource::message(error),
+        }
+    }
+}
+
 /// Retrieves the expected simulation result from the Lens Hub contract.
 ///
 /// This function queries the Lens Hub contract using the provided profile ID and block number
@@ -124,7 +200,7 @@ async fn simulate_transaction(
 /// }
 /// ```
 async fn get_expected_simulation_result(
-    lens_hub: &ILensHub<&Provider<RetryClient<Http>>>,
+    lens_hub: &ILensHub<&Provider<RetryClient<NodeTransport>>>,
     profile_id: &ProfileId,
     block_number: u64,
 ) -> Result<U256, MomokaVerifierError> {
@@ -133,11 +209,161 @@ async fn get_expected_simulation_result(
         .block(block_number)
         .call()
         .await
-        .map_err(|_| MomokaVerifierError::DataCantBeReadFromNode)?;
+        .map_err(|e| MomokaVerifierError::DataCantBeReadFromNode {
+            source: ErrorSource::message(e),
+        })?;
 
     Ok(result + U256::from(1u64))
 }
 
+/// Simulates the post on a single node and checks it creates the expected publication.
+///
+/// # Arguments
+///
+/// * `lens_hub` - The address of the Lens Hub contract.
+/// * `publication` - A reference to the `PostCreatedPublication` to be simulated.
+/// * `provider` - The node to run the simulation on.
+///
+/// # Returns
+///
+/// * A `Result` containing the simulated publication id on success, `SimulationFailed` if the
+///   simulation did not create the expected publication, or `PotentialReorg` if the block
+///   the publication was made against can no longer be found.
+async fn simulate_on_node(
+    lens_hub: Address,
+    publication: &PostCreatedPublication,
+    provider: &Provider<RetryClient<NodeTransport>>,
+) -> Result<U256, MomokaVerifierError> {
+    let lens_hub = lens_hub_contract(lens_hub, provider);
+
+    let simulation_result = simulate_transaction(&lens_hub, publication).await?;
+
+    let expected_simulation_result = get_expected_simulation_result(
+        &lens_hub,
+        publication.profile_id(),
+        publication.chain_proofs.this_publication.block_number,
+    )
+    .await?;
+
+    if simulation_result != expected_simulation_result {
+        return Err(simulation_mismatch(publication, provider).await);
+    }
+
+    Ok(simulation_result)
+}
+
+/// Works out why a simulation did not create the expected publication.
+///
+/// # Arguments
+///
+/// * `publication` - A reference to the simulated `PostCreatedPublication`.
+/// * `provider` - The node to look the publication block up on.
+///
+/// # Returns
+///
+/// * `SimulationFailed` if the block the publication was made against can still be found,
+///   otherwise `PotentialReorg`.
+async fn simulation_mismatch(
+    publication: &PostCreatedPublication,
+    provider: &Provider<RetryClient<NodeTransport>>,
+) -> MomokaVerifierError {
+    let result = provider
+        .get_block(publication.chain_proofs.this_publication.block_hash)
+        .await;
+
+    match result {
+        Ok(_block) => MomokaVerifierError::SimulationFailed {
+            source: ErrorSource::default(),
+        },
+        Err(_err) => MomokaVerifierError::PotentialReorg,
+    }
+}
+
+/// Works out why a simulation did not create the expected publication from the recorded chain
+/// state, as the nodes can not be asked offline.
+///
+/// # Arguments
+///
+/// * `publication` - A reference to the simulated `PostCreatedPublication`.
+///
+/// # Returns
+///
+/// * `SimulationFailed` if the recorded block at the publication block number is the block the
+///   publication was made against, otherwise `PotentialReorg`.
+fn recorded_simulation_mismatch(publication: &PostCreatedPublication) -> MomokaVerifierError {
+    let this_publication = &publication.chain_proofs.this_publication;
+
+    match read_batched_block_cache(this_publication.block_number) {
+        Some(block) if block.hash == Some(this_publication.block_hash) => {
+            MomokaVerifierError::SimulationFailed {
+                source: ErrorSource::default(),
+            }
+        }
+        _ => MomokaVerifierError::PotentialReorg,
+    }
+}
+
+/// Simulates the post in the local EVM against the chain state at the publication block.
+///
+/// The state the simulation touches is fetched from the nodes, agreed by the quorum, and
+/// cached on disk so repeat simulations of the block do not need an archive node.
+///
+/// # Arguments
+///
+/// * `lens_hub` - The address of the Lens Hub contract.
+/// * `publication` - A reference to the `PostCreatedPublication` to be simulated.
+/// * `provider_context` - The provider context used to fetch the state.
+/// * `cache_dir` - The directory the state snapshots are cached in.
+///
+/// # Returns
+///
+/// * A `Result` containing the simulated publication id on success, `SimulationFailed` if the
+///   simulation did not create the expected publication, or `PotentialReorg` if the block
+///   the publication was made against can no longer be found.
+async fn simulate_locally(
+    lens_hub: Address,
+    publication: &PostCreatedPublication,
+    provider_context: &ProviderContext,
+    cache_dir: &str,
+) -> Result<U256, MomokaVerifierError> {
+    let block_number = publication.chain_proofs.this_publication.block_number;
+    let mut local_evm = LocalEvm::at_block(provider_context, cache_dir, block_number).await?;
+
+    let vars = post_with_sig_data(publication)?;
+    let data = if publication.chain_proofs.this_publication.signed_by_delegate {
+        PostWithSigDispatcherCall { vars }.encode()
+    } else {
+        PostWithSigCall { vars }.encode()
+    };
+
+    let simulation_result = local_evm.call(lens_hub, data.into()).await;
+    let pub_count = local_evm
+        .call(
+            lens_hub,
+            GetPubCountCall {
+                profile_id: publication.profile_id().clone().into(),
+            }
+            .encode()
+            .into(),
+        )
+        .await;
+    local_evm.save();
+
+    let simulation_result = U256::decode(simulation_result?)
+        .map_err(|e| MomokaVerifierError::SimulationFailed { source: e.into() })?;
+    let pub_count = U256::decode(pub_count?)
+        .map_err(|e| MomokaVerifierError::SimulationNodeCouldNotRun { source: e.into() })?;
+
+    if simulation_result != pub_count + U256::from(1u64) {
+        if provider_context.offline {
+            return Err(recorded_simulation_mismatch(publication));
+        }
+        return Err(simulation_mismatch(publication, &provider_context.node).await);
+    }
+
+    Ok(simulation_result)
+}
+
 /// Cross-checks the event data with the simulated publication result and typed data.
 ///
 /// This function verifies the consistency of the event data by comparing it with the
@@ -171,8 +397,14 @@ fn cross_check_event(
     simulated_pub_result: U256,
     value: &CreatePostEIP712TypedDataValue,
 ) -> Result<(), MomokaVerifierError> {
-    if simulated_pub_result != event.pub_id.clone().into()
-        || value.profile_id != event.profile_id
+    if simulated_pub_result != event.pub_id.clone().into() {
+        return Err(MomokaVerifierError::EventMismatch {
+            expected_pub_id: Some(simulated_pub_result),
+            actual_pub_id: Some(event.pub_id.clone().into()),
+        });
+    }
+
+    if value.profile_id != event.profile_id
         || value.content_uri != event.content_uri
         || value.collect_module != event.collect_module
         || !event.collect_module_return_data.is_empty()
@@ -181,7 +413,10 @@ fn cross_check_event(
         || !value.collect_module_init_data.is_empty()
         || !value.reference_module_init_data.is_empty()
     {
-        return Err(MomokaVerifierError::EventMismatch);
+        return Err(MomokaVerifierError::EventMismatch {
+            expected_pub_id: None,
+            actual_pub_id: None,
+        });
     }
 
     Ok(())
@@ -197,6 +432,7 @@ fn cross_check_event(
 ///
 /// * `publication` - The post created publication to be verified.
 /// * `provider_context` - The provider context used for simulation and block retrieval.
+/// * `report` - The report the simulation and event checks are recorded in.
 ///
 /// # Errors
 ///
@@ -207,6 +443,7 @@ fn cross_check_event(
 /// * `SimulationFailed` - Indicates that the simulation of the transaction failed.
 /// * `PotentialReorg` - Indicates a potential reorganization of the blockchain.
 /// * `CrossCheckFailed` - Indicates that the cross-check of the event data failed.
+/// * `NodesDisagree` - Indicates that the nodes did not agree on the simulation.
 ///
 /// # Examples
 ///
@@ -214,53 +451,148 @@ fn cross_check_event(
 /// let publication = create_post_publication();
 /// let provider_context = create_provider_context();
 ///
-/// if let Err(err) = verifier_post(&publication, &provider_context) {
+/// let mut report = VerificationReport::new(publication.id.clone());
+/// if let Err(err) = verifier_post(&publication, &provider_context, &mut report) {
 ///     println!("Publication verification failed: {:?}", err);
 /// }
 /// ```
 pub async fn verifier_post(
     publication: &PostCreatedPublication,
     provider_context: &ProviderContext,
+    report: &mut VerificationReport,
 ) -> Result<(), MomokaVerifierError> {
     if publication.chain_proofs.pointer.is_some() {
         return Err(MomokaVerifierError::InvalidPointerSetNotNeeded);
     }
 
-    let lens_hub = lens_hub_contract(
-        environment_to_lens_hub_contract(&provider_context.environment).unwrap(),
-        provider_context.node.provider(),
-    );
+    let lens_hub = environment_to_lens_hub_contract(&provider_context.environment).unwrap();
 
-    let simulation_result = simulate_transaction(&lens_hub, publication).await?;
+    let simulation_result = match &provider_context.local_simulation {
+        Some(cache_dir) => {
+            simulate_locally(lens_hub, publication, provider_context, cache_dir).await
+        }
+        None => {
+            quorum_read(provider_context, |node| {
+                simulate_on_node(lens_hub, publication, node)
+            })
+            .await
+        }
+    };
+    let simulation_result = report.record(
+        VerificationCheck::Simulation,
+        None,
+        simulation_result
+            .as_ref()
+            .ok()
+            .map(|pub_id| PublicationId::new(*pub_id).to_string()),
+        simulation_result,
+    )?;
 
-    let expected_simulation_result = get_expected_simulation_result(
-        &lens_hub,
-        publication.profile_id(),
-        publication.chain_proofs.this_publication.block_number,
-    )
-    .await?;
+    report.record(
+        VerificationCheck::EventCrossCheck,
+        Some(PublicationId::new(simulation_result).to_string()),
+        Some(publication.event.pub_id.to_string()),
+        cross_check_event(
+            &publication.event,
+            simulation_result,
+            publication.typed_data_value(),
+        ),
+    )?;
 
-    if simulation_result != expected_simulation_result {
-        let result = provider_context
-            .node
-            .get_block(publication.chain_proofs.this_publication.block_hash)
-            .await;
-
-        match result {
-            Ok(_block) => {
-                return Err(MomokaVerifierError::SimulationFailed);
-            }
-            Err(_err) => {
-                return Err(MomokaVerifierError::PotentialReorg);
-            }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        environment::{Deployment, Environment},
+        evm::evm_provider,
+        simulation::snapshot::{write_snapshot, AccountSnapshot, BlockSnapshot, StateSnapshot},
+        stand_in,
+        types::transaction::{MomokaTransaction, MomokaTransactionName},
+    };
+
+    fn post() -> PostCreatedPublication {
+        match MomokaTransaction::from_json(
+            include_str!("../fixtures/post-created-delegate-arweave-response.json"),
+            &MomokaTransactionName::PostCreated,
+        )
+        .unwrap()
+        {
+            MomokaTransaction::PostCreated(publication) => publication,
+            _ => unreachable!(),
         }
     }
 
-    cross_check_event(
-        &publication.event,
-        simulation_result,
-        publication.typed_data_value(),
-    )?;
+    #[tokio::test]
+    async fn test_revert_fails_the_simulation_on_both_backends() {
+        let publication = post();
+        let block_number = publication.chain_proofs.this_publication.block_number;
+        let lens_hub = environment_to_lens_hub_contract(&Environment::Mumbai).unwrap();
 
-    Ok(())
+        // a node on which every call reverts
+        let node = stand_in::serve(|request| {
+            (request.method == "POST").then(|| {
+                r#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted","data":"0x"}}"#
+                    .to_string()
+            })
+        });
+        let on_node = simulate_on_node(lens_hub, &publication, &evm_provider(&node)).await;
+
+        // the same in the local EVM, where the lens hub code reverts every call
+        let cache_dir = std::env::temp_dir().join(format!("momoka-{}", uuid::Uuid::new_v4()));
+        let cache_dir = cache_dir.to_str().unwrap();
+        let mut snapshot = StateSnapshot {
+            block: Some(BlockSnapshot {
+                number: block_number,
+                timestamp: U256::from(publication.chain_proofs.this_publication.block_timestamp),
+                coinbase: Address::zero(),
+                gas_limit: U256::from(30_000_000u64),
+                difficulty: U256::zero(),
+                prevrandao: H256::zero(),
+                chain_id: 80001,
+            }),
+            ..Default::default()
+        };
+        for (address, code) in [
+            (Address::zero(), Bytes::default()),
+            (lens_hub, Bytes::from(vec![0x60, 0x00, 0x60, 0x00, 0xfd])),
+        ] {
+            snapshot.accounts.insert(
+                address,
+                AccountSnapshot {
+                    balance: U256::zero(),
+                    nonce: 0,
+                    code,
+                },
+            );
+        }
+        write_snapshot(cache_dir, &Environment::Mumbai, block_number, &snapshot);
+
+        let provider_context = ProviderContext {
+            environment: Environment::Mumbai,
+            node: evm_provider(&node),
+            nodes: vec![evm_provider(&node)],
+            node_urls: vec![node.clone()],
+            quorum: 1,
+            deployment: Deployment::Production,
+            arweave_gateway: None,
+            detect_double_publications: false,
+            local_simulation: Some(cache_dir.to_string()),
+            content_gateways: None,
+            transaction_filter: None,
+            offline: false,
+            trusted_indexing: false,
+        };
+        let locally = simulate_locally(lens_hub, &publication, &provider_context, cache_dir).await;
+
+        assert!(matches!(
+            on_node,
+            Err(MomokaVerifierError::SimulationFailed { .. })
+        ));
+        assert_eq!(on_node, locally);
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
 }